@@ -0,0 +1,18 @@
+use hyprland_util::HyprlandClient;
+
+#[tokio::main]
+async fn main() -> hyprland_util::Result<()> {
+  let mut client = HyprlandClient::new()?;
+
+  loop {
+    match client.output().await {
+      Ok(output) => println!("Output: {:?}", output),
+      Err(e) => {
+        println!("Error: {:?}", e);
+        break;
+      }
+    }
+  }
+
+  Ok(())
+}
@@ -0,0 +1,345 @@
+use std::{
+  path::{Path, PathBuf},
+  time::Duration,
+};
+
+use tokio::{
+  io::{AsyncBufReadExt, BufReader},
+  net::UnixStream,
+  sync::mpsc,
+  task,
+};
+use tracing::warn;
+
+use crate::{
+  ipc::{self, HyprlandEvent, EVENT_SOCKET_NAME},
+  Error, HyprlandMonitor, HyprlandOutput, HyprlandWindow,
+  HyprlandWorkspace,
+};
+
+/// Events that trigger a new output.
+const REFRESH_EVENTS: &[&str] = &[
+  "workspace",
+  "focusedmon",
+  "activewindow",
+  "windowtitle",
+  "openwindow",
+  "closewindow",
+  "movewindow",
+  "changefloatingmode",
+  "fullscreen",
+  "createworkspace",
+  "destroyworkspace",
+  "moveworkspace",
+  "renameworkspace",
+  "monitoradded",
+  "monitorremoved",
+];
+
+/// Name of the submap when no submap is active.
+const DEFAULT_SUBMAP: &str = "default";
+
+/// Delay between attempts to reconnect to Hyprland.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Client for Hyprland's IPC sockets.
+pub struct HyprlandClient {
+  socket_dir: PathBuf,
+  output_rx: mpsc::Receiver<crate::Result<HyprlandOutput>>,
+  listen_task: task::JoinHandle<()>,
+}
+
+impl HyprlandClient {
+  /// Creates a new client for the Hyprland instance given by the
+  /// `HYPRLAND_INSTANCE_SIGNATURE` environment variable.
+  ///
+  /// Must be called from within a Tokio runtime.
+  pub fn new() -> crate::Result<Self> {
+    Ok(Self::with_socket_dir(default_socket_dir()?))
+  }
+
+  /// Creates a new client using the sockets within the given directory.
+  ///
+  /// Must be called from within a Tokio runtime.
+  pub fn with_socket_dir(socket_dir: PathBuf) -> Self {
+    let (output_tx, output_rx) = mpsc::channel(100);
+
+    let listen_task =
+      task::spawn(Self::listen(socket_dir.clone(), output_tx));
+
+    Self {
+      socket_dir,
+      output_rx,
+      listen_task,
+    }
+  }
+
+  /// Receives the next output.
+  ///
+  /// An output is emitted on connection and whenever a workspace, window,
+  /// monitor, or submap event occurs. Connection errors are emitted as
+  /// `Err`, after which the client keeps trying to reconnect.
+  pub async fn output(&mut self) -> crate::Result<HyprlandOutput> {
+    self.output_rx.recv().await.ok_or(Error::SocketRead)?
+  }
+
+  /// Runs a dispatcher with the given arguments (e.g. `workspace 2`).
+  pub async fn dispatch(&self, dispatcher: &str) -> crate::Result<()> {
+    let reply =
+      ipc::request(&self.socket_dir, &format!("dispatch {}", dispatcher))
+        .await?;
+
+    match reply.trim() {
+      "ok" => Ok(()),
+      err => Err(Error::Dispatch(err.to_string())),
+    }
+  }
+
+  /// Listens for events and emits outputs until the client is dropped.
+  async fn listen(
+    socket_dir: PathBuf,
+    output_tx: mpsc::Sender<crate::Result<HyprlandOutput>>,
+  ) {
+    loop {
+      let err = match Self::listen_once(&socket_dir, &output_tx).await {
+        Ok(()) => return,
+        Err(err) => err,
+      };
+
+      warn!("Hyprland IPC connection failed: {}", err);
+
+      if output_tx.send(Err(err)).await.is_err() {
+        return;
+      }
+
+      tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+  }
+
+  /// Connects and emits outputs until the connection fails.
+  ///
+  /// Returns `Ok` if the receiver has been dropped.
+  async fn listen_once(
+    socket_dir: &Path,
+    output_tx: &mpsc::Sender<crate::Result<HyprlandOutput>>,
+  ) -> crate::Result<()> {
+    let event_stream =
+      UnixStream::connect(socket_dir.join(EVENT_SOCKET_NAME)).await?;
+
+    let mut lines = BufReader::new(event_stream).lines();
+
+    // The submap is only queried once, since it's included in the
+    // `submap` event.
+    let mut submap = Self::query_submap(socket_dir).await?;
+    let output = Self::query_output(socket_dir, &submap).await?;
+
+    if output_tx.send(Ok(output)).await.is_err() {
+      return Ok(());
+    }
+
+    while let Some(line) = lines.next_line().await? {
+      let Some(event) = HyprlandEvent::parse(&line) else {
+        continue;
+      };
+
+      if event.name == "submap" {
+        submap = match event.data.as_str() {
+          "" => DEFAULT_SUBMAP.to_string(),
+          name => name.to_string(),
+        };
+      } else if !REFRESH_EVENTS.contains(&event.name.as_str()) {
+        continue;
+      }
+
+      let output = Self::query_output(socket_dir, &submap).await?;
+
+      if output_tx.send(Ok(output)).await.is_err() {
+        return Ok(());
+      }
+    }
+
+    Err(Error::SocketRead)
+  }
+
+  /// Queries the current state of Hyprland.
+  async fn query_output(
+    socket_dir: &Path,
+    submap: &str,
+  ) -> crate::Result<HyprlandOutput> {
+    let monitors: Vec<HyprlandMonitor> =
+      ipc::request_json(socket_dir, "monitors").await?;
+
+    let workspaces: Vec<HyprlandWorkspace> =
+      ipc::request_json(socket_dir, "workspaces").await?;
+
+    // Hyprland replies with an empty object if no window has focus.
+    let active_window: serde_json::Value =
+      ipc::request_json(socket_dir, "activewindow").await?;
+
+    let active_window = match active_window.get("address") {
+      Some(_) => {
+        Some(serde_json::from_value::<HyprlandWindow>(active_window)?)
+      }
+      None => None,
+    };
+
+    Ok(HyprlandOutput {
+      monitors,
+      workspaces,
+      active_window,
+      submap: submap.to_string(),
+    })
+  }
+
+  /// Queries the name of the current submap.
+  ///
+  /// Older versions of Hyprland don't support this request, in
+  /// which case the default submap is assumed.
+  async fn query_submap(socket_dir: &Path) -> crate::Result<String> {
+    let reply = ipc::request(socket_dir, "submap").await?;
+
+    Ok(match reply.trim() {
+      "" | "unknown request" => DEFAULT_SUBMAP.to_string(),
+      name => name.to_string(),
+    })
+  }
+}
+
+impl Drop for HyprlandClient {
+  fn drop(&mut self) {
+    self.listen_task.abort();
+  }
+}
+
+/// Gets the socket directory from the `HYPRLAND_INSTANCE_SIGNATURE` and
+/// `XDG_RUNTIME_DIR` environment variables.
+pub fn default_socket_dir() -> crate::Result<PathBuf> {
+  let signature = std::env::var("HYPRLAND_INSTANCE_SIGNATURE")
+    .map_err(|_| Error::InstanceSignature)?;
+
+  let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from);
+
+  Ok(ipc::instance_socket_dir(&signature, runtime_dir.as_deref()))
+}
+
+#[cfg(test)]
+mod tests {
+  use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::UnixListener,
+  };
+
+  use super::*;
+  use crate::REQUEST_SOCKET_NAME;
+
+  /// Recorded replies from Hyprland.
+  const MONITORS: &str = include_str!("../fixtures/monitors.json");
+  const WORKSPACES: &str = include_str!("../fixtures/workspaces.json");
+  const ACTIVE_WINDOW: &str =
+    include_str!("../fixtures/activewindow.json");
+
+  /// Starts a stand-in Hyprland instance that replays recorded replies.
+  ///
+  /// The given events are sent to each connection on the event socket,
+  /// and received dispatches are forwarded to the returned channel.
+  fn start_server(
+    name: &str,
+    events: &'static str,
+  ) -> (PathBuf, mpsc::Receiver<String>) {
+    let socket_dir = std::env::temp_dir().join(format!(
+      "hyprland-util-{}-{}",
+      name,
+      std::process::id()
+    ));
+
+    let _ = std::fs::remove_dir_all(&socket_dir);
+    std::fs::create_dir_all(&socket_dir).unwrap();
+
+    let request_listener =
+      UnixListener::bind(socket_dir.join(REQUEST_SOCKET_NAME)).unwrap();
+
+    let event_listener =
+      UnixListener::bind(socket_dir.join(EVENT_SOCKET_NAME)).unwrap();
+
+    let (dispatch_tx, dispatch_rx) = mpsc::channel(10);
+
+    task::spawn(async move {
+      while let Ok((mut stream, _)) = request_listener.accept().await {
+        let mut buf = [0; 1024];
+        let len = stream.read(&mut buf).await.unwrap();
+        let request = String::from_utf8_lossy(&buf[..len]).to_string();
+
+        let reply = match request.as_str() {
+          "j/monitors" => MONITORS,
+          "j/workspaces" => WORKSPACES,
+          "j/activewindow" => ACTIVE_WINDOW,
+          "submap" => "unknown request",
+          "dispatch workspace invalid" => "Invalid workspace",
+          _ => {
+            let dispatch = request.strip_prefix("dispatch ").unwrap();
+            dispatch_tx.send(dispatch.to_string()).await.unwrap();
+            "ok"
+          }
+        };
+
+        stream.write_all(reply.as_bytes()).await.unwrap();
+      }
+    });
+
+    task::spawn(async move {
+      let mut streams = Vec::new();
+
+      while let Ok((mut stream, _)) = event_listener.accept().await {
+        stream.write_all(events.as_bytes()).await.unwrap();
+
+        // Keep the connection open.
+        streams.push(stream);
+      }
+    });
+
+    (socket_dir, dispatch_rx)
+  }
+
+  #[tokio::test]
+  async fn test_output() {
+    let (socket_dir, _) = start_server(
+      "output",
+      "activelayout>>keyboard,English (US)\nsubmap>>resize\n",
+    );
+
+    let mut client = HyprlandClient::with_socket_dir(socket_dir);
+
+    let output = client.output().await.unwrap();
+    assert_eq!(output.monitors.len(), 2);
+    assert_eq!(output.workspaces[1].name, "web");
+    assert_eq!(output.active_window.unwrap().title, "~/zebar");
+    assert_eq!(output.submap, "default");
+
+    // Unrelated events are ignored, so the next output is from the
+    // submap event.
+    let output = client.output().await.unwrap();
+    assert_eq!(output.submap, "resize");
+  }
+
+  #[tokio::test]
+  async fn test_dispatch() {
+    let (socket_dir, mut dispatch_rx) = start_server("dispatch", "");
+    let client = HyprlandClient::with_socket_dir(socket_dir);
+
+    client.dispatch("workspace 2").await.unwrap();
+    assert_eq!(dispatch_rx.recv().await.unwrap(), "workspace 2");
+
+    let res = client.dispatch("workspace invalid").await;
+    assert!(
+      matches!(res, Err(Error::Dispatch(err)) if err == "Invalid workspace")
+    );
+  }
+
+  #[tokio::test]
+  async fn test_connection_error() {
+    let mut client =
+      HyprlandClient::with_socket_dir(PathBuf::from("/nonexistent/hypr"));
+
+    assert!(matches!(client.output().await, Err(Error::Socket(_))));
+  }
+}
@@ -0,0 +1,31 @@
+use serde::{Serialize, Serializer};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+  #[error("Failed to get socket directory. `HYPRLAND_INSTANCE_SIGNATURE` is not set.")]
+  InstanceSignature,
+
+  #[error("Socket error: {0}")]
+  Socket(#[from] std::io::Error),
+
+  #[error("Failed to parse IPC reply: {0}")]
+  ReplyParse(#[from] serde_json::Error),
+
+  #[error("Failed to read next output from socket.")]
+  SocketRead,
+
+  #[error("Dispatch failed: {0}")]
+  Dispatch(String),
+}
+
+impl Serialize for Error {
+  fn serialize<S>(
+    &self,
+    serializer: S,
+  ) -> std::result::Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    serializer.serialize_str(&self.to_string())
+  }
+}
@@ -0,0 +1,116 @@
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use tokio::{
+  io::{AsyncReadExt, AsyncWriteExt},
+  net::UnixStream,
+};
+
+/// Name of the socket that accepts requests (e.g. `j/monitors`).
+pub const REQUEST_SOCKET_NAME: &str = ".socket.sock";
+
+/// Name of the socket that broadcasts events.
+pub const EVENT_SOCKET_NAME: &str = ".socket2.sock";
+
+/// Gets the socket directory of the Hyprland instance with the given
+/// signature.
+///
+/// Hyprland v0.40+ places its sockets in `$XDG_RUNTIME_DIR/hypr`, whereas
+/// older versions use `/tmp/hypr`.
+pub fn instance_socket_dir(
+  signature: &str,
+  runtime_dir: Option<&Path>,
+) -> PathBuf {
+  runtime_dir
+    .map(|dir| dir.join("hypr").join(signature))
+    .filter(|dir| dir.exists())
+    .unwrap_or_else(|| Path::new("/tmp/hypr").join(signature))
+}
+
+/// Event received from the event socket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HyprlandEvent {
+  /// Name of the event (e.g. `workspace`).
+  pub name: String,
+
+  /// Comma-separated event data (e.g. the workspace name).
+  pub data: String,
+}
+
+impl HyprlandEvent {
+  /// Parses an event from a line in the format `EVENT>>DATA`.
+  pub fn parse(line: &str) -> Option<Self> {
+    line.split_once(">>").map(|(name, data)| Self {
+      name: name.to_string(),
+      data: data.to_string(),
+    })
+  }
+}
+
+/// Sends a request and returns the raw reply.
+///
+/// Hyprland closes the connection after replying, so a new connection is
+/// made for each request.
+pub async fn request(
+  socket_dir: &Path,
+  request: &str,
+) -> crate::Result<String> {
+  let mut stream =
+    UnixStream::connect(socket_dir.join(REQUEST_SOCKET_NAME)).await?;
+
+  stream.write_all(request.as_bytes()).await?;
+
+  let mut reply = String::new();
+  stream.read_to_string(&mut reply).await?;
+
+  Ok(reply)
+}
+
+/// Sends a request with the JSON flag (`j/`) and parses the reply.
+pub(crate) async fn request_json<T: DeserializeOwned>(
+  socket_dir: &Path,
+  command: &str,
+) -> crate::Result<T> {
+  let reply = request(socket_dir, &format!("j/{}", command)).await?;
+
+  Ok(serde_json::from_str(&reply)?)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_event_parse() {
+    assert_eq!(
+      HyprlandEvent::parse("activewindow>>kitty,~/zebar >> nvim"),
+      Some(HyprlandEvent {
+        name: "activewindow".to_string(),
+        data: "kitty,~/zebar >> nvim".to_string(),
+      })
+    );
+
+    assert_eq!(HyprlandEvent::parse("invalid"), None);
+  }
+
+  #[test]
+  fn test_instance_socket_dir() {
+    let runtime_dir = std::env::temp_dir()
+      .join(format!("hyprland-util-runtime-{}", std::process::id()));
+
+    std::fs::create_dir_all(runtime_dir.join("hypr/abc")).unwrap();
+
+    assert_eq!(
+      instance_socket_dir("abc", Some(&runtime_dir)),
+      runtime_dir.join("hypr/abc")
+    );
+
+    // Falls back to `/tmp/hypr` if the runtime directory has no sockets.
+    assert_eq!(
+      instance_socket_dir("xyz", Some(&runtime_dir)),
+      PathBuf::from("/tmp/hypr/xyz")
+    );
+
+    let _ = std::fs::remove_dir_all(&runtime_dir);
+  }
+}
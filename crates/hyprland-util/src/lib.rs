@@ -0,0 +1,11 @@
+mod client;
+mod error;
+mod ipc;
+mod types;
+
+pub use client::*;
+pub use error::*;
+pub use ipc::*;
+pub use types::*;
+
+pub type Result<T> = std::result::Result<T, Error>;
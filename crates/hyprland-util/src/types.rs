@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HyprlandOutput {
+  pub monitors: Vec<HyprlandMonitor>,
+  pub workspaces: Vec<HyprlandWorkspace>,
+
+  /// The focused window, if any.
+  pub active_window: Option<HyprlandWindow>,
+
+  /// Name of the current submap (keybind mode), or `default` if no submap
+  /// is active.
+  pub submap: String,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HyprlandMonitor {
+  pub id: i64,
+  pub name: String,
+  #[serde(default)]
+  pub description: String,
+  pub x: i32,
+  pub y: i32,
+  pub width: u32,
+  pub height: u32,
+  pub scale: f32,
+  pub active_workspace: HyprlandWorkspaceRef,
+  pub focused: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HyprlandWorkspace {
+  pub id: i64,
+  pub name: String,
+
+  /// Name of the monitor that the workspace is on.
+  pub monitor: String,
+
+  #[serde(rename(deserialize = "monitorID"))]
+  pub monitor_id: i64,
+
+  /// Number of windows on the workspace.
+  pub windows: u32,
+
+  #[serde(rename(deserialize = "hasfullscreen"))]
+  pub has_fullscreen: bool,
+
+  #[serde(default, rename(deserialize = "lastwindowtitle"))]
+  pub last_window_title: String,
+}
+
+/// Reference to a workspace by ID and name.
+#[derive(Debug, Clone, Deserialize, PartialEq, Serialize)]
+pub struct HyprlandWorkspaceRef {
+  pub id: i64,
+  pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HyprlandWindow {
+  /// Address of the window (e.g. `0x5581d4f8b0c0`).
+  pub address: String,
+
+  pub class: String,
+  pub title: String,
+  pub initial_class: String,
+  pub initial_title: String,
+  pub pid: i32,
+  pub floating: bool,
+  pub workspace: HyprlandWorkspaceRef,
+
+  /// ID of the monitor that the window is on.
+  pub monitor: i64,
+
+  /// Position of the window as `[x, y]`.
+  pub at: [i32; 2],
+
+  /// Size of the window as `[width, height]`.
+  pub size: [i32; 2],
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_deserialization() {
+    let monitors: Vec<HyprlandMonitor> =
+      serde_json::from_str(include_str!("../fixtures/monitors.json"))
+        .unwrap();
+
+    let workspaces: Vec<HyprlandWorkspace> =
+      serde_json::from_str(include_str!("../fixtures/workspaces.json"))
+        .unwrap();
+
+    let window: HyprlandWindow =
+      serde_json::from_str(include_str!("../fixtures/activewindow.json"))
+        .unwrap();
+
+    assert_eq!(monitors[1].active_workspace.name, "web");
+    assert_eq!(workspaces[0].monitor_id, 0);
+    assert!(workspaces[1].has_fullscreen);
+    assert_eq!(window.class, "kitty");
+    assert_eq!(window.size, [1270, 1400]);
+  }
+}
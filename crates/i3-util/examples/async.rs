@@ -0,0 +1,18 @@
+use i3_util::I3Client;
+
+#[tokio::main]
+async fn main() -> i3_util::Result<()> {
+  let mut client = I3Client::new()?;
+
+  loop {
+    match client.output().await {
+      Ok(output) => println!("Output: {:?}", output),
+      Err(e) => {
+        println!("Error: {:?}", e);
+        break;
+      }
+    }
+  }
+
+  Ok(())
+}
@@ -0,0 +1,325 @@
+use std::{
+  path::{Path, PathBuf},
+  time::Duration,
+};
+
+use tokio::{net::UnixStream, sync::mpsc, task};
+use tracing::warn;
+
+use crate::{
+  ipc::{self, EventType, MessageType},
+  BindingState, CommandOutcome, Error, I3Monitor, I3Output, I3Workspace,
+  SubscribeReply, TreeNode,
+};
+
+/// Events that trigger a new output.
+const SUBSCRIBED_EVENTS: &str =
+  r#"["workspace","window","mode","output"]"#;
+
+/// Delay between attempts to reconnect to the window manager.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Client for i3 and Sway's IPC interface.
+pub struct I3Client {
+  socket_path: PathBuf,
+  output_rx: mpsc::Receiver<crate::Result<I3Output>>,
+  listen_task: task::JoinHandle<()>,
+}
+
+impl I3Client {
+  /// Creates a new client using the socket path from the `SWAYSOCK` or
+  /// `I3SOCK` environment variables.
+  ///
+  /// Must be called from within a Tokio runtime.
+  pub fn new() -> crate::Result<Self> {
+    Ok(Self::with_socket_path(default_socket_path()?))
+  }
+
+  /// Creates a new client using the given socket path.
+  ///
+  /// Must be called from within a Tokio runtime.
+  pub fn with_socket_path(socket_path: PathBuf) -> Self {
+    let (output_tx, output_rx) = mpsc::channel(100);
+
+    let listen_task =
+      task::spawn(Self::listen(socket_path.clone(), output_tx));
+
+    Self {
+      socket_path,
+      output_rx,
+      listen_task,
+    }
+  }
+
+  /// Receives the next output.
+  ///
+  /// An output is emitted on connection and whenever a workspace, window,
+  /// binding mode, or monitor event occurs. Connection errors are emitted
+  /// as `Err`, after which the client keeps trying to reconnect.
+  pub async fn output(&mut self) -> crate::Result<I3Output> {
+    self.output_rx.recv().await.ok_or(Error::SocketRead)?
+  }
+
+  /// Runs a command (e.g. `workspace number 2`).
+  ///
+  /// Multiple commands can be chained with `;` or `,`. Returns an error if
+  /// any of them fail.
+  pub async fn run_command(&self, command: &str) -> crate::Result<()> {
+    let mut stream = UnixStream::connect(&self.socket_path).await?;
+
+    let outcomes: Vec<CommandOutcome> =
+      ipc::query(&mut stream, MessageType::RunCommand, command.as_bytes())
+        .await?;
+
+    match outcomes.into_iter().find(|outcome| !outcome.success) {
+      Some(outcome) => Err(Error::Command(
+        outcome
+          .error
+          .unwrap_or_else(|| "Unknown error.".to_string()),
+      )),
+      None => Ok(()),
+    }
+  }
+
+  /// Listens for events and emits outputs until the client is dropped.
+  async fn listen(
+    socket_path: PathBuf,
+    output_tx: mpsc::Sender<crate::Result<I3Output>>,
+  ) {
+    loop {
+      let err = match Self::listen_once(&socket_path, &output_tx).await {
+        Ok(()) => return,
+        Err(err) => err,
+      };
+
+      warn!("i3 IPC connection failed: {}", err);
+
+      if output_tx.send(Err(err)).await.is_err() {
+        return;
+      }
+
+      tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+  }
+
+  /// Connects and emits outputs until the connection fails.
+  ///
+  /// Returns `Ok` if the receiver has been dropped.
+  async fn listen_once(
+    socket_path: &Path,
+    output_tx: &mpsc::Sender<crate::Result<I3Output>>,
+  ) -> crate::Result<()> {
+    // Events are received on a separate connection, so that replies to
+    // queries aren't interleaved with events.
+    let mut event_stream = UnixStream::connect(socket_path).await?;
+    let mut query_stream = UnixStream::connect(socket_path).await?;
+
+    let reply: SubscribeReply = ipc::query(
+      &mut event_stream,
+      MessageType::Subscribe,
+      SUBSCRIBED_EVENTS.as_bytes(),
+    )
+    .await?;
+
+    if !reply.success {
+      return Err(Error::Subscribe);
+    }
+
+    let output = Self::query_output(&mut query_stream).await?;
+
+    if output_tx.send(Ok(output)).await.is_err() {
+      return Ok(());
+    }
+
+    loop {
+      let (message_type, _) = ipc::read_message(&mut event_stream).await?;
+
+      match EventType::from_message_type(message_type) {
+        Some(EventType::Shutdown) => return Err(Error::Shutdown),
+        Some(
+          EventType::Workspace
+          | EventType::Window
+          | EventType::Mode
+          | EventType::Output,
+        ) => {
+          let output = Self::query_output(&mut query_stream).await?;
+
+          if output_tx.send(Ok(output)).await.is_err() {
+            return Ok(());
+          }
+        }
+        _ => {}
+      }
+    }
+  }
+
+  /// Queries the current state of the window manager.
+  async fn query_output(
+    stream: &mut UnixStream,
+  ) -> crate::Result<I3Output> {
+    let monitors: Vec<I3Monitor> =
+      ipc::query(stream, MessageType::GetOutputs, &[]).await?;
+
+    let workspaces: Vec<I3Workspace> =
+      ipc::query(stream, MessageType::GetWorkspaces, &[]).await?;
+
+    let tree: TreeNode =
+      ipc::query(stream, MessageType::GetTree, &[]).await?;
+
+    let binding_state: BindingState =
+      ipc::query(stream, MessageType::GetBindingState, &[]).await?;
+
+    Ok(I3Output {
+      monitors: monitors
+        .into_iter()
+        .filter(|monitor| monitor.active)
+        .collect(),
+      workspaces,
+      focused_window: tree.focused_window(),
+      binding_mode: binding_state.name,
+    })
+  }
+}
+
+impl Drop for I3Client {
+  fn drop(&mut self) {
+    self.listen_task.abort();
+  }
+}
+
+/// Gets the IPC socket path from the `SWAYSOCK` or `I3SOCK` environment
+/// variables.
+pub fn default_socket_path() -> crate::Result<PathBuf> {
+  std::env::var_os("SWAYSOCK")
+    .or_else(|| std::env::var_os("I3SOCK"))
+    .filter(|path| !path.is_empty())
+    .map(PathBuf::from)
+    .ok_or(Error::SocketPath)
+}
+
+#[cfg(test)]
+mod tests {
+  use tokio::{net::UnixListener, sync::mpsc};
+
+  use super::*;
+
+  /// Recorded replies from Sway.
+  const OUTPUTS: &str = include_str!("../fixtures/outputs.json");
+  const WORKSPACES: &str = include_str!("../fixtures/workspaces.json");
+  const TREE: &str = include_str!("../fixtures/tree.json");
+  const WORKSPACE_EVENT: &str =
+    include_str!("../fixtures/workspace_event.json");
+
+  /// Starts a stand-in window manager that replays recorded replies.
+  ///
+  /// A workspace event is sent after each subscription, and received
+  /// commands are forwarded to the returned channel.
+  fn start_server(name: &str) -> (PathBuf, mpsc::Receiver<String>) {
+    let socket_dir = std::env::temp_dir().join(format!(
+      "i3-util-{}-{}",
+      name,
+      std::process::id()
+    ));
+
+    let _ = std::fs::remove_dir_all(&socket_dir);
+    std::fs::create_dir_all(&socket_dir).unwrap();
+
+    let socket_path = socket_dir.join("ipc.sock");
+    let listener = UnixListener::bind(&socket_path).unwrap();
+    let (command_tx, command_rx) = mpsc::channel(10);
+
+    task::spawn(async move {
+      while let Ok((stream, _)) = listener.accept().await {
+        task::spawn(handle_connection(stream, command_tx.clone()));
+      }
+    });
+
+    (socket_path, command_rx)
+  }
+
+  async fn handle_connection(
+    mut stream: UnixStream,
+    command_tx: mpsc::Sender<String>,
+  ) {
+    while let Ok((message_type, payload)) =
+      ipc::read_message(&mut stream).await
+    {
+      let payload = String::from_utf8(payload).unwrap();
+
+      let reply = match message_type {
+        0 if payload == "invalid" => {
+          r#"[{"success":false,"parse_error":true,"error":"Unknown command"}]"#
+        }
+        0 => {
+          command_tx.send(payload).await.unwrap();
+          r#"[{"success":true}]"#
+        }
+        1 => WORKSPACES,
+        2 => r#"{"success":true}"#,
+        3 => OUTPUTS,
+        4 => TREE,
+        12 => r#"{"name":"resize"}"#,
+        _ => panic!("Unexpected message type: {}", message_type),
+      };
+
+      ipc::write_message(&mut stream, message_type, reply.as_bytes())
+        .await
+        .unwrap();
+
+      if message_type == 2 {
+        ipc::write_message(
+          &mut stream,
+          EventType::Workspace.message_type(),
+          WORKSPACE_EVENT.as_bytes(),
+        )
+        .await
+        .unwrap();
+      }
+    }
+  }
+
+  #[tokio::test]
+  async fn test_output() {
+    let (socket_path, _) = start_server("output");
+    let mut client = I3Client::with_socket_path(socket_path);
+
+    let output = client.output().await.unwrap();
+
+    let monitor_names = output
+      .monitors
+      .iter()
+      .map(|monitor| monitor.name.as_str())
+      .collect::<Vec<_>>();
+
+    assert_eq!(monitor_names, ["DP-1", "HDMI-A-1"]);
+    assert_eq!(output.workspaces.len(), 3);
+    assert!(output.workspaces[1].urgent);
+    assert_eq!(output.focused_window.unwrap().id, 7);
+    assert_eq!(output.binding_mode, "resize");
+
+    // Workspace event should trigger another output.
+    assert!(client.output().await.is_ok());
+  }
+
+  #[tokio::test]
+  async fn test_run_command() {
+    let (socket_path, mut command_rx) = start_server("run-command");
+    let client = I3Client::with_socket_path(socket_path);
+
+    client.run_command("workspace number 2").await.unwrap();
+    assert_eq!(command_rx.recv().await.unwrap(), "workspace number 2");
+
+    let res = client.run_command("invalid").await;
+    assert!(
+      matches!(res, Err(Error::Command(err)) if err == "Unknown command")
+    );
+  }
+
+  #[tokio::test]
+  async fn test_connection_error() {
+    let mut client =
+      I3Client::with_socket_path(PathBuf::from("/nonexistent/ipc.sock"));
+
+    assert!(matches!(client.output().await, Err(Error::Socket(_))));
+  }
+}
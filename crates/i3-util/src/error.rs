@@ -0,0 +1,45 @@
+use serde::{Serialize, Serializer};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+  #[error(
+    "Failed to get socket path. Neither `SWAYSOCK` nor `I3SOCK` is set."
+  )]
+  SocketPath,
+
+  #[error("Socket error: {0}")]
+  Socket(#[from] std::io::Error),
+
+  #[error("Received IPC message with invalid magic string.")]
+  InvalidMagic,
+
+  #[error("Received IPC message with too large payload ({0} bytes).")]
+  PayloadTooLarge(u32),
+
+  #[error("Failed to parse IPC reply: {0}")]
+  ReplyParse(#[from] serde_json::Error),
+
+  #[error("Failed to read next output from socket.")]
+  SocketRead,
+
+  #[error("Failed to subscribe to window manager events.")]
+  Subscribe,
+
+  #[error("Command failed: {0}")]
+  Command(String),
+
+  #[error("Window manager is shutting down.")]
+  Shutdown,
+}
+
+impl Serialize for Error {
+  fn serialize<S>(
+    &self,
+    serializer: S,
+  ) -> std::result::Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    serializer.serialize_str(&self.to_string())
+  }
+}
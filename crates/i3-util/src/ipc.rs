@@ -0,0 +1,189 @@
+use serde::de::DeserializeOwned;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::Error;
+
+/// Magic string that prefixes every i3 IPC message.
+pub const IPC_MAGIC: &[u8; 6] = b"i3-ipc";
+
+/// Length of a message header (magic string + payload length + type).
+const HEADER_LEN: usize = 14;
+
+/// Maximum payload length of a received message (64MB). Large enough for
+/// the `GET_TREE` reply of big layouts.
+const MAX_PAYLOAD_SIZE: u32 = 64 * 1024 * 1024;
+
+/// Bit that is set on the message type of events.
+const EVENT_BIT: u32 = 1 << 31;
+
+/// Type of a message sent to the window manager.
+///
+/// Only the message types used by this crate are included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum MessageType {
+  RunCommand = 0,
+  GetWorkspaces = 1,
+  Subscribe = 2,
+  GetOutputs = 3,
+  GetTree = 4,
+  GetBindingState = 12,
+}
+
+/// Type of an event sent by the window manager to subscribers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventType {
+  Workspace,
+  Output,
+  Mode,
+  Window,
+  BarconfigUpdate,
+  Binding,
+  Shutdown,
+  Tick,
+}
+
+impl EventType {
+  /// Gets the event type from a raw message type.
+  ///
+  /// Returns `None` if the message is a reply rather than an event, or if
+  /// the event type is unknown.
+  pub fn from_message_type(message_type: u32) -> Option<Self> {
+    if message_type & EVENT_BIT == 0 {
+      return None;
+    }
+
+    match message_type & !EVENT_BIT {
+      0 => Some(Self::Workspace),
+      1 => Some(Self::Output),
+      2 => Some(Self::Mode),
+      3 => Some(Self::Window),
+      4 => Some(Self::BarconfigUpdate),
+      5 => Some(Self::Binding),
+      6 => Some(Self::Shutdown),
+      7 => Some(Self::Tick),
+      _ => None,
+    }
+  }
+
+  /// Gets the raw message type of the event.
+  pub fn message_type(&self) -> u32 {
+    EVENT_BIT | *self as u32
+  }
+}
+
+/// Writes a message with the given type and payload.
+///
+/// Integers in the header are in native byte order, as required by the
+/// i3 IPC protocol.
+pub async fn write_message<W: AsyncWrite + Unpin>(
+  writer: &mut W,
+  message_type: u32,
+  payload: &[u8],
+) -> crate::Result<()> {
+  let mut message = Vec::with_capacity(HEADER_LEN + payload.len());
+  message.extend_from_slice(IPC_MAGIC);
+  message.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+  message.extend_from_slice(&message_type.to_ne_bytes());
+  message.extend_from_slice(payload);
+
+  writer.write_all(&message).await?;
+
+  Ok(())
+}
+
+/// Reads the next message and returns its raw type and payload.
+pub async fn read_message<R: AsyncRead + Unpin>(
+  reader: &mut R,
+) -> crate::Result<(u32, Vec<u8>)> {
+  let mut header = [0; HEADER_LEN];
+  reader.read_exact(&mut header).await?;
+
+  if &header[..6] != IPC_MAGIC {
+    return Err(Error::InvalidMagic);
+  }
+
+  let payload_len =
+    u32::from_ne_bytes([header[6], header[7], header[8], header[9]]);
+
+  let message_type =
+    u32::from_ne_bytes([header[10], header[11], header[12], header[13]]);
+
+  if payload_len > MAX_PAYLOAD_SIZE {
+    return Err(Error::PayloadTooLarge(payload_len));
+  }
+
+  let mut payload = vec![0; payload_len as usize];
+  reader.read_exact(&mut payload).await?;
+
+  Ok((message_type, payload))
+}
+
+/// Sends a message and parses the reply as JSON.
+///
+/// The stream must not be subscribed to events, since the next message
+/// read is assumed to be the reply.
+pub(crate) async fn query<S, T>(
+  stream: &mut S,
+  message_type: MessageType,
+  payload: &[u8],
+) -> crate::Result<T>
+where
+  S: AsyncRead + AsyncWrite + Unpin,
+  T: DeserializeOwned,
+{
+  write_message(stream, message_type as u32, payload).await?;
+  let (_, reply) = read_message(stream).await?;
+
+  Ok(serde_json::from_slice(&reply)?)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn test_message_roundtrip() {
+    let (mut client, mut server) = tokio::io::duplex(64);
+
+    write_message(&mut client, MessageType::RunCommand as u32, b"nop")
+      .await
+      .unwrap();
+
+    let (message_type, payload) = read_message(&mut server).await.unwrap();
+    assert_eq!(message_type, 0);
+    assert_eq!(payload, b"nop");
+  }
+
+  #[tokio::test]
+  async fn test_invalid_magic() {
+    let (mut client, mut server) = tokio::io::duplex(64);
+    client.write_all(b"i3-bad\0\0\0\0\0\0\0\0").await.unwrap();
+
+    let res = read_message(&mut server).await;
+    assert!(matches!(res, Err(Error::InvalidMagic)));
+  }
+
+  #[tokio::test]
+  async fn test_payload_too_large() {
+    let (mut client, mut server) = tokio::io::duplex(64);
+
+    let mut header = IPC_MAGIC.to_vec();
+    header.extend_from_slice(&u32::MAX.to_ne_bytes());
+    header.extend_from_slice(&0u32.to_ne_bytes());
+    client.write_all(&header).await.unwrap();
+
+    let res = read_message(&mut server).await;
+    assert!(matches!(res, Err(Error::PayloadTooLarge(u32::MAX))));
+  }
+
+  #[test]
+  fn test_event_type() {
+    assert_eq!(
+      EventType::from_message_type(0x8000_0002),
+      Some(EventType::Mode)
+    );
+    assert_eq!(EventType::from_message_type(2), None);
+    assert_eq!(EventType::Window.message_type(), 0x8000_0003);
+  }
+}
@@ -0,0 +1,211 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct I3Output {
+  /// Active monitors (called outputs by i3 and Sway).
+  pub monitors: Vec<I3Monitor>,
+
+  /// All workspaces across all monitors.
+  pub workspaces: Vec<I3Workspace>,
+
+  /// The focused window, if any.
+  ///
+  /// This is `None` when focus is on an empty workspace.
+  pub focused_window: Option<I3Window>,
+
+  /// Name of the current binding mode (e.g. `default` or `resize`).
+  pub binding_mode: String,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all(serialize = "camelCase"))]
+pub struct I3Monitor {
+  pub name: String,
+  #[serde(default)]
+  pub active: bool,
+  #[serde(default)]
+  pub primary: bool,
+
+  /// Sway only.
+  #[serde(default)]
+  pub focused: bool,
+
+  /// Name of the workspace that is visible on the monitor.
+  pub current_workspace: Option<String>,
+
+  pub rect: I3Rect,
+}
+
+#[derive(Debug, Clone, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all(serialize = "camelCase"))]
+pub struct I3Workspace {
+  pub id: i64,
+
+  /// Workspace number, or `-1` for workspaces with a non-numeric name.
+  pub num: i32,
+
+  pub name: String,
+  pub visible: bool,
+  pub focused: bool,
+  pub urgent: bool,
+
+  /// Name of the monitor that the workspace is on.
+  pub output: String,
+
+  pub rect: I3Rect,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct I3Window {
+  pub id: i64,
+  pub title: Option<String>,
+
+  /// Wayland app ID (Sway only).
+  pub app_id: Option<String>,
+
+  /// X11 window class (i3 and Xwayland windows).
+  pub class: Option<String>,
+
+  pub pid: Option<i32>,
+  pub floating: bool,
+  pub rect: I3Rect,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Serialize)]
+pub struct I3Rect {
+  pub x: i32,
+  pub y: i32,
+  pub width: i32,
+  pub height: i32,
+}
+
+/// Node in the layout tree returned by `GET_TREE`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct TreeNode {
+  id: i64,
+  name: Option<String>,
+  r#type: String,
+  #[serde(default)]
+  focused: bool,
+  rect: I3Rect,
+  #[serde(default)]
+  nodes: Vec<TreeNode>,
+  #[serde(default)]
+  floating_nodes: Vec<TreeNode>,
+  app_id: Option<String>,
+  pid: Option<i32>,
+  window: Option<i64>,
+  window_properties: Option<WindowProperties>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WindowProperties {
+  class: Option<String>,
+}
+
+impl TreeNode {
+  /// Finds the focused window within the tree.
+  pub(crate) fn focused_window(&self) -> Option<I3Window> {
+    self.find_focused_window(false)
+  }
+
+  fn find_focused_window(&self, is_floating: bool) -> Option<I3Window> {
+    if self.focused {
+      return self.to_window(is_floating);
+    }
+
+    // i3 wraps floating windows in a `floating_con`, whereas Sway uses
+    // the `floating_con` as the window itself.
+    let is_floating = is_floating || self.r#type == "floating_con";
+
+    self
+      .nodes
+      .iter()
+      .map(|node| node.find_focused_window(is_floating))
+      .chain(
+        self
+          .floating_nodes
+          .iter()
+          .map(|node| node.find_focused_window(true)),
+      )
+      .flatten()
+      .next()
+  }
+
+  /// Converts the node to a window if it holds one.
+  ///
+  /// Containers without a window (e.g. split containers and workspaces)
+  /// return `None`.
+  fn to_window(&self, is_floating: bool) -> Option<I3Window> {
+    let is_window = matches!(self.r#type.as_str(), "con" | "floating_con")
+      && (self.window.is_some() || self.app_id.is_some());
+
+    is_window.then(|| I3Window {
+      id: self.id,
+      title: self.name.clone(),
+      app_id: self.app_id.clone(),
+      class: self
+        .window_properties
+        .as_ref()
+        .and_then(|props| props.class.clone()),
+      pid: self.pid,
+      floating: is_floating || self.r#type == "floating_con",
+      rect: self.rect,
+    })
+  }
+}
+
+/// Reply to a `GET_BINDING_STATE` message.
+#[derive(Debug, Deserialize)]
+pub(crate) struct BindingState {
+  pub name: String,
+}
+
+/// Reply to a `SUBSCRIBE` message.
+#[derive(Debug, Deserialize)]
+pub(crate) struct SubscribeReply {
+  pub success: bool,
+}
+
+/// Result of a single command within a `RUN_COMMAND` message.
+#[derive(Debug, Deserialize)]
+pub(crate) struct CommandOutcome {
+  pub success: bool,
+  pub error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_focused_window() {
+    let tree: TreeNode =
+      serde_json::from_str(include_str!("../fixtures/tree.json")).unwrap();
+
+    let window = tree.focused_window().unwrap();
+    assert_eq!(window.id, 7);
+    assert_eq!(window.title.as_deref(), Some("~/zebar - Alacritty"));
+    assert_eq!(window.app_id.as_deref(), Some("Alacritty"));
+    assert!(!window.floating);
+  }
+
+  #[test]
+  fn test_no_focused_window() {
+    let tree: TreeNode = serde_json::from_str(
+      r#"{
+        "id": 1, "name": "root", "type": "root", "focused": false,
+        "rect": {"x": 0, "y": 0, "width": 1920, "height": 1080},
+        "nodes": [{
+          "id": 4, "name": "1", "type": "workspace", "focused": true,
+          "rect": {"x": 0, "y": 0, "width": 1920, "height": 1080}
+        }]
+      }"#,
+    )
+    .unwrap();
+
+    assert_eq!(tree.focused_window(), None);
+  }
+}
@@ -1,7 +1,8 @@
-#[cfg(target_os = "macos")]
+#[cfg(unix)]
 use std::os::unix::net::{UnixListener, UnixStream};
 use std::{
   io::{BufReader, Read, Write},
+  path::{Path, PathBuf},
   time::Duration,
 };
 
@@ -10,32 +11,68 @@ use tokio::sync::{mpsc, oneshot};
 #[cfg(windows)]
 use uds_windows::{UnixListener, UnixStream};
 
-use crate::{Error, KomorebiOutput};
+use crate::{
+  command::{send_command, COMMAND_SOCKET_NAME},
+  ClientOptions, Error, KomorebiCommand, KomorebiOutput, KomorebiStatus,
+  ReconnectBackoff,
+};
 
 /// A client that connects to and interacts with Komorebi via IPC on a Unix
 /// socket.
 pub struct KomorebiClient {
   output_rx: mpsc::Receiver<Result<KomorebiOutput, Error>>,
   shutdown_tx: Option<oneshot::Sender<()>>,
+
+  /// Directory containing Komorebi's sockets.
+  socket_dir: PathBuf,
 }
 
 impl KomorebiClient {
   /// Creates a `KomorebiClient` instance.
   ///
   /// The client will immediately begin listening for outputs on the
-  /// specified socket.
+  /// specified socket. Sockets are created within Komorebi's default
+  /// data directory.
   pub fn new(socket_name: &str) -> crate::Result<Self> {
+    Self::with_options(ClientOptions::new(socket_name))
+  }
+
+  /// Creates a `KomorebiClient` instance with the given options.
+  ///
+  /// The client will immediately begin listening for outputs on the
+  /// specified socket.
+  pub fn with_options(options: ClientOptions) -> crate::Result<Self> {
     let (output_tx, output_rx) = mpsc::channel(100);
     let (shutdown_tx, shutdown_rx) = oneshot::channel();
 
-    Self::subscribe(socket_name.to_string(), output_tx, shutdown_rx);
+    let socket_dir = match &options.socket_dir {
+      Some(socket_dir) => socket_dir.clone(),
+      None => default_socket_dir()?,
+    };
+
+    Self::subscribe(
+      options.socket_name,
+      socket_dir.clone(),
+      options.reconnect_backoff,
+      output_tx,
+      shutdown_rx,
+    );
 
     Ok(KomorebiClient {
       output_rx,
       shutdown_tx: Some(shutdown_tx),
+      socket_dir,
     })
   }
 
+  /// Sends a command to Komorebi (e.g. to focus a workspace).
+  pub fn send_command(
+    &self,
+    command: &KomorebiCommand,
+  ) -> crate::Result<()> {
+    send_command(&self.socket_dir, command)
+  }
+
   /// Returns the latest output from Komorebi socket.
   pub async fn output(&mut self) -> crate::Result<KomorebiOutput> {
     self.output_rx.recv().await.ok_or(Error::SocketRead)?
@@ -47,12 +84,11 @@ impl KomorebiClient {
   }
 
   /// Creates a socket and sends a message to Komorebi to subscribe to it.
-  fn create_socket(socket_name: &str) -> crate::Result<UnixListener> {
-    let data_dir = dirs::data_local_dir()
-      .map(|dir| dir.join("komorebi"))
-      .ok_or(Error::DataDir)?;
-
-    let listener_socket = data_dir.join(socket_name);
+  fn create_socket(
+    socket_name: &str,
+    socket_dir: &Path,
+  ) -> crate::Result<UnixListener> {
+    let listener_socket = socket_dir.join(socket_name);
 
     if let Err(err) = std::fs::remove_file(&listener_socket) {
       if err.kind() != std::io::ErrorKind::NotFound {
@@ -62,7 +98,7 @@ impl KomorebiClient {
 
     let listener = UnixListener::bind(&listener_socket)?;
 
-    let msg_socket = data_dir.join("komorebi.sock");
+    let msg_socket = socket_dir.join(COMMAND_SOCKET_NAME);
     let mut msg_stream = UnixStream::connect(msg_socket)?;
 
     let add_subscriber_msg = serde_json::to_string(&json!({
@@ -77,20 +113,31 @@ impl KomorebiClient {
   }
 
   /// Attempts to create a socket with retry logic.
+  ///
+  /// The delay between attempts grows according to the given backoff.
+  /// `on_failure` is called after every failed attempt.
   fn create_socket_with_retry(
     socket_name: &str,
+    socket_dir: &Path,
+    backoff: &ReconnectBackoff,
     shutdown_rx: &mut oneshot::Receiver<()>,
+    mut on_failure: impl FnMut(),
   ) -> Option<UnixListener> {
+    let mut delay = backoff.initial_delay;
+
     loop {
-      match Self::create_socket(socket_name) {
+      match Self::create_socket(socket_name, socket_dir) {
         Ok(socket) => return Some(socket),
         Err(err) => {
           tracing::debug!(
-            "Failed to connect to Komorebi: {}. Retrying in 15s...",
-            err
+            "Failed to connect to Komorebi: {}. Retrying in {:?}...",
+            err,
+            delay
           );
 
-          std::thread::sleep(Duration::from_secs(15));
+          on_failure();
+          std::thread::sleep(delay);
+          delay = backoff.next_delay(delay);
 
           // Check for shutdown signal during retry attempts.
           if shutdown_rx.try_recv().is_ok() {
@@ -104,15 +151,25 @@ impl KomorebiClient {
   /// Listens for socket messages on a separate thread.
   fn subscribe(
     socket_name: String,
+    socket_dir: PathBuf,
+    backoff: ReconnectBackoff,
     output_tx: mpsc::Sender<crate::Result<KomorebiOutput>>,
     mut shutdown_rx: oneshot::Receiver<()>,
   ) {
     std::thread::spawn(move || {
+      // Latest output that was emitted. Used to re-emit the last known
+      // state as disconnected when the connection is lost.
+      let mut latest_output: Option<KomorebiOutput> = None;
+
       loop {
         // Attempt to create or recreate socket
-        let Some(socket) =
-          Self::create_socket_with_retry(&socket_name, &mut shutdown_rx)
-        else {
+        let Some(socket) = Self::create_socket_with_retry(
+          &socket_name,
+          &socket_dir,
+          &backoff,
+          &mut shutdown_rx,
+          || Self::emit_disconnected(&output_tx, &mut latest_output),
+        ) else {
           // Shutdown signal received during connection attempt.
           return;
         };
@@ -131,6 +188,7 @@ impl KomorebiClient {
               // Shutdown signal has been received.
               if matches!(reader.read_to_end(&mut buffer), Ok(0)) {
                 tracing::debug!("Komorebi shutdown received.");
+                Self::emit_disconnected(&output_tx, &mut latest_output);
                 break;
               }
 
@@ -142,6 +200,10 @@ impl KomorebiClient {
                     .map_err(Error::OutputParse)
                 });
 
+              if let Ok(output) = &result {
+                latest_output = Some(output.clone());
+              }
+
               let _ = output_tx.blocking_send(result);
             }
             Err(_) => {
@@ -153,6 +215,29 @@ impl KomorebiClient {
     });
   }
 
+  /// Emits the latest output as disconnected.
+  ///
+  /// No-ops if the latest output is already marked as disconnected, such
+  /// that only the transition to disconnected is emitted.
+  fn emit_disconnected(
+    output_tx: &mpsc::Sender<crate::Result<KomorebiOutput>>,
+    latest_output: &mut Option<KomorebiOutput>,
+  ) {
+    if latest_output.as_ref().map(|output| output.status)
+      == Some(KomorebiStatus::Disconnected)
+    {
+      return;
+    }
+
+    let output = latest_output
+      .as_ref()
+      .map(KomorebiOutput::to_disconnected)
+      .unwrap_or_default();
+
+    *latest_output = Some(output.clone());
+    let _ = output_tx.blocking_send(Ok(output));
+  }
+
   /// Stops the client and its background listener thread.
   ///
   /// Returns `KomorebiError::AlreadyStopped` if the client was already
@@ -175,3 +260,88 @@ impl Drop for KomorebiClient {
     let _ = self.stop();
   }
 }
+
+/// Returns the directory where Komorebi creates its sockets by default.
+///
+/// This is `komorebi` within the local data directory (e.g.
+/// `%LOCALAPPDATA%/komorebi` on Windows).
+pub fn default_socket_dir() -> crate::Result<PathBuf> {
+  dirs::data_local_dir()
+    .map(|dir| dir.join("komorebi"))
+    .ok_or(Error::DataDir)
+}
+
+#[cfg(test)]
+mod tests {
+  #[cfg(unix)]
+  use std::os::unix::net::{UnixListener, UnixStream};
+  use std::{io::Read, time::Duration};
+
+  #[cfg(windows)]
+  use uds_windows::{UnixListener, UnixStream};
+
+  use super::*;
+
+  #[test]
+  fn test_connection_status() {
+    let socket_dir = std::env::temp_dir()
+      .join(format!("komorebi-util-client-{}", std::process::id()));
+
+    let _ = std::fs::remove_dir_all(&socket_dir);
+    std::fs::create_dir_all(&socket_dir).unwrap();
+
+    let mut client = KomorebiClient::with_options(ClientOptions {
+      socket_name: "test.sock".to_string(),
+      socket_dir: Some(socket_dir.clone()),
+      reconnect_backoff: ReconnectBackoff {
+        initial_delay: Duration::from_millis(10),
+        max_delay: Duration::from_millis(50),
+        multiplier: 2,
+      },
+    })
+    .unwrap();
+
+    // Komorebi is not running yet.
+    let output = client.output_blocking().unwrap();
+    assert_eq!(output.status, KomorebiStatus::Disconnected);
+    assert!(output.all_monitors.is_empty());
+
+    // Start a stand-in Komorebi and wait for the client to subscribe.
+    let komorebi =
+      UnixListener::bind(socket_dir.join(COMMAND_SOCKET_NAME)).unwrap();
+
+    let (mut stream, _) = komorebi.accept().unwrap();
+    let mut subscribe_msg = String::new();
+    stream.read_to_string(&mut subscribe_msg).unwrap();
+
+    assert_eq!(
+      serde_json::from_str::<serde_json::Value>(&subscribe_msg).unwrap(),
+      json!({ "type": "AddSubscriberSocket", "content": "test.sock" })
+    );
+
+    // Send a notification to the subscriber socket.
+    let mut subscriber =
+      UnixStream::connect(socket_dir.join("test.sock")).unwrap();
+
+    subscriber
+      .write_all(
+        br#"{
+          "event": { "type": "ToggleMonocle" },
+          "state": { "monitors": { "elements": [], "focused": 0 } }
+        }"#,
+      )
+      .unwrap();
+
+    drop(subscriber);
+
+    let output = client.output_blocking().unwrap();
+    assert_eq!(output.status, KomorebiStatus::Connected);
+    assert_eq!(
+      output.event.map(|event| event.r#type),
+      Some("ToggleMonocle".to_string())
+    );
+
+    client.stop().unwrap();
+    let _ = std::fs::remove_dir_all(&socket_dir);
+  }
+}
@@ -0,0 +1,178 @@
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::{io::Write, path::Path, time::Duration};
+
+use serde::Serialize;
+#[cfg(windows)]
+use uds_windows::UnixStream;
+
+use crate::Error;
+
+/// Name of the socket that Komorebi listens on for incoming commands.
+pub const COMMAND_SOCKET_NAME: &str = "komorebi.sock";
+
+/// A command that can be sent to Komorebi.
+///
+/// Serializes to the same JSON format as Komorebi's `SocketMessage` (e.g.
+/// `{"type":"FocusWorkspaceNumber","content":1}`).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", content = "content")]
+pub enum KomorebiCommand {
+  /// Focuses the workspace at the given index on the focused monitor.
+  FocusWorkspaceNumber(usize),
+
+  /// Focuses the workspace at the given index on the given monitor.
+  ///
+  /// Tuple of `(monitor_index, workspace_index)`.
+  FocusMonitorWorkspaceNumber(usize, usize),
+
+  /// Focuses the workspace with the given name.
+  FocusNamedWorkspace(String),
+
+  /// Focuses the previous or next workspace on the focused monitor.
+  CycleFocusWorkspace(CycleDirection),
+
+  /// Moves the focused window to the workspace at the given index on the
+  /// focused monitor.
+  MoveContainerToWorkspaceNumber(usize),
+
+  /// Moves the focused window to the workspace at the given index on the
+  /// given monitor.
+  ///
+  /// Tuple of `(monitor_index, workspace_index)`.
+  MoveContainerToMonitorWorkspaceNumber(usize, usize),
+
+  /// Changes focus to the window in the given direction.
+  FocusWindow(OperationDirection),
+
+  /// Moves the focused window in the given direction.
+  MoveWindow(OperationDirection),
+
+  /// Cycles the layout of the focused workspace.
+  CycleLayout(CycleDirection),
+
+  /// Toggles monocle mode for the focused window.
+  ToggleMonocle,
+
+  /// Toggles floating mode for the focused window.
+  ToggleFloat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum OperationDirection {
+  Left,
+  Right,
+  Up,
+  Down,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum CycleDirection {
+  Previous,
+  Next,
+}
+
+/// Sends a command to Komorebi via the `komorebi.sock` socket within the
+/// given directory.
+pub fn send_command(
+  socket_dir: &Path,
+  command: &KomorebiCommand,
+) -> crate::Result<()> {
+  let msg =
+    serde_json::to_string(command).map_err(Error::CommandSerialize)?;
+
+  let mut stream =
+    UnixStream::connect(socket_dir.join(COMMAND_SOCKET_NAME))
+      .map_err(Error::CommandSend)?;
+
+  stream
+    .set_write_timeout(Some(Duration::from_secs(1)))
+    .map_err(Error::CommandSend)?;
+
+  stream
+    .write_all(msg.as_bytes())
+    .map_err(Error::CommandSend)?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  #[cfg(unix)]
+  use std::os::unix::net::UnixListener;
+  use std::{io::Read, path::PathBuf};
+
+  #[cfg(windows)]
+  use uds_windows::UnixListener;
+
+  use super::*;
+
+  /// Creates an empty directory for the stand-in Komorebi socket.
+  fn temp_socket_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+      "komorebi-util-{}-{}",
+      name,
+      std::process::id()
+    ));
+
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+  }
+
+  #[test]
+  fn test_command_serialization() {
+    let cases = [
+      (
+        KomorebiCommand::FocusWorkspaceNumber(2),
+        r#"{"type":"FocusWorkspaceNumber","content":2}"#,
+      ),
+      (
+        KomorebiCommand::FocusMonitorWorkspaceNumber(1, 3),
+        r#"{"type":"FocusMonitorWorkspaceNumber","content":[1,3]}"#,
+      ),
+      (
+        KomorebiCommand::MoveWindow(OperationDirection::Left),
+        r#"{"type":"MoveWindow","content":"Left"}"#,
+      ),
+      (
+        KomorebiCommand::CycleLayout(CycleDirection::Next),
+        r#"{"type":"CycleLayout","content":"Next"}"#,
+      ),
+      (
+        KomorebiCommand::ToggleMonocle,
+        r#"{"type":"ToggleMonocle"}"#,
+      ),
+    ];
+
+    for (command, expected) in cases {
+      assert_eq!(serde_json::to_string(&command).unwrap(), expected);
+    }
+  }
+
+  #[test]
+  fn test_send_command() {
+    let socket_dir = temp_socket_dir("send-command");
+    let listener =
+      UnixListener::bind(socket_dir.join(COMMAND_SOCKET_NAME)).unwrap();
+
+    send_command(&socket_dir, &KomorebiCommand::ToggleMonocle).unwrap();
+
+    let (mut stream, _) = listener.accept().unwrap();
+    let mut received = String::new();
+    stream.read_to_string(&mut received).unwrap();
+
+    assert_eq!(received, r#"{"type":"ToggleMonocle"}"#);
+    let _ = std::fs::remove_dir_all(&socket_dir);
+  }
+
+  #[test]
+  fn test_send_command_without_server() {
+    let socket_dir = temp_socket_dir("no-server");
+
+    let res = send_command(&socket_dir, &KomorebiCommand::ToggleFloat);
+
+    assert!(matches!(res, Err(Error::CommandSend(_))));
+    let _ = std::fs::remove_dir_all(&socket_dir);
+  }
+}
@@ -19,6 +19,12 @@ pub enum Error {
 
   #[error("Failed to get data directory.")]
   DataDir,
+
+  #[error("Failed to serialize command: {0}")]
+  CommandSerialize(serde_json::Error),
+
+  #[error("Failed to send command to Komorebi: {0}")]
+  CommandSend(std::io::Error),
 }
 
 impl Serialize for Error {
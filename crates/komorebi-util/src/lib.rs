@@ -1,9 +1,13 @@
 mod client;
+mod command;
 mod error;
+mod options;
 mod types;
 
 pub use client::*;
+pub use command::*;
 pub use error::*;
+pub use options::*;
 pub use types::*;
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,56 @@
+use std::{path::PathBuf, time::Duration};
+
+/// Options for creating a `KomorebiClient`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientOptions {
+  /// Name of the socket to create for receiving notifications.
+  pub socket_name: String,
+
+  /// Directory containing Komorebi's sockets.
+  ///
+  /// Defaults to Komorebi's data directory if `None`.
+  pub socket_dir: Option<PathBuf>,
+
+  /// Delays between attempts to (re)connect to Komorebi.
+  pub reconnect_backoff: ReconnectBackoff,
+}
+
+impl ClientOptions {
+  pub fn new(socket_name: &str) -> Self {
+    Self {
+      socket_name: socket_name.to_string(),
+      socket_dir: None,
+      reconnect_backoff: ReconnectBackoff::default(),
+    }
+  }
+}
+
+/// Exponential backoff for reconnecting to Komorebi.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectBackoff {
+  /// Delay after the first failed connection attempt.
+  pub initial_delay: Duration,
+
+  /// Upper bound for the delay between attempts.
+  pub max_delay: Duration,
+
+  /// Factor to multiply the delay by after each failed attempt.
+  pub multiplier: u32,
+}
+
+impl ReconnectBackoff {
+  /// Returns the delay to use after the given delay.
+  pub fn next_delay(&self, delay: Duration) -> Duration {
+    delay.saturating_mul(self.multiplier).min(self.max_delay)
+  }
+}
+
+impl Default for ReconnectBackoff {
+  fn default() -> Self {
+    Self {
+      initial_delay: Duration::from_secs(1),
+      max_delay: Duration::from_secs(15),
+      multiplier: 2,
+    }
+  }
+}
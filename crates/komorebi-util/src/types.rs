@@ -3,10 +3,66 @@ use serde::{Deserialize, Deserializer, Serialize};
 #[derive(Debug, Clone, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct KomorebiOutput {
+  /// Whether the client is currently connected to Komorebi.
+  pub status: KomorebiStatus,
+
+  /// Event that triggered the notification.
+  ///
+  /// This is `None` for outputs that were not sent by Komorebi (e.g. when
+  /// the connection is lost).
+  pub event: Option<KomorebiEvent>,
+
   pub all_monitors: Vec<KomorebiMonitor>,
   pub focused_monitor_index: usize,
 }
 
+impl KomorebiOutput {
+  /// Returns a copy of the output marked as disconnected.
+  ///
+  /// The last known monitors are kept, but the triggering event is
+  /// cleared.
+  pub fn to_disconnected(&self) -> Self {
+    Self {
+      status: KomorebiStatus::Disconnected,
+      event: None,
+      ..self.clone()
+    }
+  }
+}
+
+impl Default for KomorebiOutput {
+  fn default() -> Self {
+    Self {
+      status: KomorebiStatus::Disconnected,
+      event: None,
+      all_monitors: Vec::new(),
+      focused_monitor_index: 0,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KomorebiStatus {
+  Connected,
+  Disconnected,
+}
+
+/// Event that caused Komorebi to send a notification.
+///
+/// This is either a window manager event (e.g. `FocusChange`, `Manage`) or
+/// a socket message (e.g. `FocusWorkspaceNumber`). The content varies by
+/// event type and is passed through as-is.
+#[derive(Debug, Clone, Deserialize, PartialEq, Serialize)]
+pub struct KomorebiEvent {
+  /// Name of the event (e.g. `FocusChange`).
+  pub r#type: String,
+
+  /// Payload of the event (if any).
+  #[serde(default)]
+  pub content: serde_json::Value,
+}
+
 impl<'de> Deserialize<'de> for KomorebiOutput {
   fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
   where
@@ -14,6 +70,7 @@ impl<'de> Deserialize<'de> for KomorebiOutput {
   {
     #[derive(Deserialize)]
     struct Notification {
+      event: Option<KomorebiEvent>,
       state: State,
     }
 
@@ -31,6 +88,8 @@ impl<'de> Deserialize<'de> for KomorebiOutput {
     let notification = Notification::deserialize(deserializer)?;
 
     Ok(KomorebiOutput {
+      status: KomorebiStatus::Connected,
+      event: notification.event,
       all_monitors: notification.state.monitors.elements,
       focused_monitor_index: notification.state.monitors.focused,
     })
@@ -96,6 +155,14 @@ pub struct KomorebiWorkspace {
   pub container_padding: Option<i32>,
   pub floating_windows: Vec<KomorebiWindow>,
   pub focused_container_index: usize,
+
+  /// Window that has focus within the workspace.
+  ///
+  /// This is the window of the monocle container if there is one,
+  /// followed by the maximized window, and lastly the focused window of
+  /// the focused tiling container.
+  pub focused_window: Option<KomorebiWindow>,
+
   pub latest_layout: Vec<Rect>,
   pub layout: KomorebiLayout,
   pub layout_flip: Option<KomorebiLayoutFlip>,
@@ -131,17 +198,38 @@ impl<'de> Deserialize<'de> for KomorebiWorkspace {
       focused: usize,
     }
 
+    /// Floating windows are a plain list in older Komorebi versions.
     #[derive(Deserialize)]
-    struct WindowElements {
-      elements: Vec<KomorebiWindow>,
+    #[serde(untagged)]
+    enum WindowElements {
+      Ring { elements: Vec<KomorebiWindow> },
+      List(Vec<KomorebiWindow>),
     }
 
     let workspace = Workspace::deserialize(deserializer)?;
 
+    let focused_window = workspace
+      .monocle_container
+      .as_ref()
+      .and_then(KomorebiContainer::focused_window)
+      .or(workspace.maximized_window.as_ref())
+      .or_else(|| {
+        workspace
+          .containers
+          .elements
+          .get(workspace.containers.focused)
+          .and_then(KomorebiContainer::focused_window)
+      })
+      .cloned();
+
     Ok(KomorebiWorkspace {
       container_padding: workspace.container_padding,
-      floating_windows: workspace.floating_windows.elements,
+      floating_windows: match workspace.floating_windows {
+        WindowElements::Ring { elements } => elements,
+        WindowElements::List(elements) => elements,
+      },
       focused_container_index: workspace.containers.focused,
+      focused_window,
       latest_layout: workspace.latest_layout,
       layout: workspace.layout,
       layout_flip: workspace.layout_flip,
@@ -159,6 +247,14 @@ impl<'de> Deserialize<'de> for KomorebiWorkspace {
 pub struct KomorebiContainer {
   pub id: String,
   pub windows: Vec<KomorebiWindow>,
+  pub focused_window_index: usize,
+}
+
+impl KomorebiContainer {
+  /// Returns the focused window within the container.
+  pub fn focused_window(&self) -> Option<&KomorebiWindow> {
+    self.windows.get(self.focused_window_index)
+  }
 }
 
 impl<'de> Deserialize<'de> for KomorebiContainer {
@@ -175,6 +271,8 @@ impl<'de> Deserialize<'de> for KomorebiContainer {
     #[derive(Deserialize)]
     struct WindowElements {
       elements: Vec<KomorebiWindow>,
+      #[serde(default)]
+      focused: usize,
     }
 
     let container = Container::deserialize(deserializer)?;
@@ -182,6 +280,7 @@ impl<'de> Deserialize<'de> for KomorebiContainer {
     Ok(KomorebiContainer {
       id: container.id,
       windows: container.windows.elements,
+      focused_window_index: container.windows.focused,
     })
   }
 }
@@ -318,3 +417,88 @@ pub struct Rect {
   pub right: i32,
   pub bottom: i32,
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Minimal notification with a single monitor and workspace.
+  const NOTIFICATION: &str = r#"{
+    "event": { "type": "FocusWorkspaceNumber", "content": 0 },
+    "state": {
+      "monitors": {
+        "elements": [{
+          "id": 1,
+          "device": null,
+          "device_id": null,
+          "name": "DISPLAY1",
+          "size": { "left": 0, "top": 0, "right": 1920, "bottom": 1080 },
+          "work_area_offset": null,
+          "work_area_size": { "left": 0, "top": 40, "right": 1920, "bottom": 1040 },
+          "workspaces": {
+            "elements": [{
+              "container_padding": 10,
+              "floating_windows": [{ "hwnd": 3, "title": "Floating" }],
+              "latest_layout": [],
+              "layout": { "Default": "BSP" },
+              "layout_flip": null,
+              "maximized_window": null,
+              "monocle_container": null,
+              "name": "1",
+              "containers": {
+                "elements": [{
+                  "id": "a",
+                  "windows": {
+                    "elements": [
+                      { "hwnd": 1, "title": "First" },
+                      { "hwnd": 2, "title": "Second" }
+                    ],
+                    "focused": 1
+                  }
+                }],
+                "focused": 0
+              },
+              "workspace_padding": 10
+            }],
+            "focused": 0
+          }
+        }],
+        "focused": 0
+      }
+    }
+  }"#;
+
+  #[test]
+  fn test_notification_deserialization() {
+    let output = serde_json::from_str::<KomorebiOutput>(NOTIFICATION)
+      .expect("Notification should deserialize.");
+
+    assert_eq!(output.status, KomorebiStatus::Connected);
+
+    let event = output.event.expect("Event should be present.");
+    assert_eq!(event.r#type, "FocusWorkspaceNumber");
+    assert_eq!(event.content, serde_json::json!(0));
+
+    let workspace = &output.all_monitors[0].workspaces[0];
+    assert_eq!(workspace.layout, KomorebiLayout::Bsp);
+    assert_eq!(workspace.floating_windows.len(), 1);
+    assert_eq!(
+      workspace
+        .focused_window
+        .as_ref()
+        .and_then(|window| window.title.as_deref()),
+      Some("Second")
+    );
+  }
+
+  #[test]
+  fn test_to_disconnected() {
+    let output =
+      serde_json::from_str::<KomorebiOutput>(NOTIFICATION).unwrap();
+    let disconnected = output.to_disconnected();
+
+    assert_eq!(disconnected.status, KomorebiStatus::Disconnected);
+    assert_eq!(disconnected.event, None);
+    assert_eq!(disconnected.all_monitors, output.all_monitors);
+  }
+}
@@ -25,10 +25,15 @@ const version = z
 
 const widget = z.object({
   name,
-  htmlPath: z.string().refine(path => path.endsWith('.html'), {
-    message:
-      'Must be a valid HTML file path (e.g. "path/to/widget.html").',
-  }),
+  htmlPath: z
+    .string()
+    .refine(
+      path => path.endsWith('.html') || path.endsWith('.html.tera'),
+      {
+        message:
+          'Must be a valid HTML file path (e.g. "path/to/widget.html").',
+      },
+    ),
   zOrder: z.enum(['normal', 'top_most', 'bottom_most']),
   shownInTaskbar: z.boolean(),
   focused: z.boolean(),
@@ -44,6 +49,9 @@ const widget = z.object({
       }),
     ),
   }),
+  templateVariables: z.record(z.unknown()).optional(),
+  contentSecurityPolicy: z.string().optional(),
+  responseHeaders: z.record(z.string()).optional(),
   privileges: z.object({
     shellCommands: z.array(
       z.object({
@@ -55,6 +63,8 @@ const widget = z.object({
   presets: z.array(
     z.object({
       name: z.string(),
+      keepAlive: z.boolean().optional(),
+      templateVariables: z.record(z.unknown()).optional(),
       anchor: z.enum([
         'top_left',
         'top_center',
@@ -93,7 +103,24 @@ const widget = z.object({
         enabled: z.boolean(),
         edge: z.enum(['top', 'right', 'bottom', 'left']).nullable(),
         windowMargin: z.string(),
+        order: z.number().optional(),
       }),
+      visibility: z
+        .union([
+          z.object({
+            type: z.literal('always'),
+          }),
+          z.object({
+            type: z.literal('auto_hide'),
+            hideDelay: z.number().optional(),
+            revealDelay: z.number().optional(),
+          }),
+          z.object({
+            type: z.literal('hotkey'),
+            hotkey: z.string(),
+          }),
+        ])
+        .optional(),
     }),
   ),
 });
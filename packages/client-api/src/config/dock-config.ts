@@ -2,4 +2,5 @@ export type DockConfig = {
   enabled: boolean;
   edge: 'top' | 'bottom' | 'left' | 'right' | null;
   windowMargin: string;
+  order?: number;
 };
@@ -7,3 +7,4 @@ export * from './widget-pack';
 export * from './widget-placement';
 export * from './widget-preset';
 export * from './widget-privileges';
+export * from './widget-visibility';
@@ -13,5 +13,22 @@ export type WidgetConfig = {
   includeFiles: string[];
   caching: WidgetCaching;
   privileges: WidgetPrivileges;
+
+  /**
+   * Variables available to `.html.tera` entry points under
+   * `variables`.
+   */
+  templateVariables?: Record<string, unknown>;
+
+  /**
+   * Content-Security-Policy for the widget's files. Defaults to a strict
+   * policy for marketplace packs.
+   */
+  contentSecurityPolicy?: string;
+
+  /**
+   * Additional headers for responses to the widget's file requests.
+   */
+  responseHeaders?: Record<string, string>;
   presets: WidgetPreset[];
 };
@@ -16,6 +16,9 @@ export type WidgetPack =
         packId: string;
         installedAt: number;
         version: string;
+        publisherKey?: string;
+        rolledBackFrom?: string;
+        source: 'marketplace' | 'archive';
       };
     }
   | {
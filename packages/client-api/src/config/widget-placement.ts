@@ -1,5 +1,6 @@
 import type { MonitorSelection } from './monitor-selection';
 import type { DockConfig } from './dock-config';
+import type { WidgetVisibility } from './widget-visibility';
 
 export type WidgetPlacement = {
   anchor:
@@ -18,4 +19,5 @@ export type WidgetPlacement = {
   height: string;
   monitorSelection: MonitorSelection;
   dockToEdge: DockConfig;
+  visibility?: WidgetVisibility;
 };
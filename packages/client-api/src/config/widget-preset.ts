@@ -2,4 +2,17 @@ import type { WidgetPlacement } from './widget-placement';
 
 export type WidgetPreset = {
   name: string;
+
+  /**
+   * Whether to hide the widget instead of closing it when the preset is
+   * stopped. Starting the preset again shows the existing window, which
+   * keeps its JS state.
+   */
+  keepAlive?: boolean;
+
+  /**
+   * Variables available to `.html.tera` entry points. Overrides the
+   * widget's variables of the same name.
+   */
+  templateVariables?: Record<string, unknown>;
 } & WidgetPlacement;
@@ -0,0 +1,13 @@
+export type WidgetVisibility =
+  | {
+      type: 'always';
+    }
+  | {
+      type: 'auto_hide';
+      hideDelay?: number;
+      revealDelay?: number;
+    }
+  | {
+      type: 'hotkey';
+      hotkey: string;
+    };
@@ -0,0 +1,13 @@
+/**
+ * Gets the path of an asset server endpoint for the current widget.
+ *
+ * Widgets are served under a path prefix holding their token (i.e.
+ * `/__zebar/t/<token>/`), which identifies the widget to the asset
+ * server.
+ */
+export function assetServerPath(endpoint: string): string {
+  const prefix =
+    window.location.pathname.match(/^\/__zebar\/t\/[^/]+\//)?.[0] ?? '/';
+
+  return `${prefix}__zebar/${endpoint}`;
+}
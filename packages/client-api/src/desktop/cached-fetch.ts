@@ -0,0 +1,31 @@
+import { assetServerPath } from './asset-server';
+
+/**
+ * Fetches a remote resource via Zebar's caching proxy.
+ *
+ * Responses are cached on disk for the durations in the widget's
+ * `caching` config. Expired responses are revalidated with the remote
+ * server, and served as-is if the network is unavailable. Only `GET`
+ * requests are supported.
+ *
+ * Only URLs that match one of the widget's `caching.rules` can be
+ * fetched (others are rejected with a 403). Requests to loopback or
+ * private network addresses are not allowed, and responses are limited
+ * to 20MB. Proxied resources can't be loaded as scripts or documents.
+ *
+ * The `X-Zebar-Cache` response header indicates whether the response was
+ * served from the cache (`hit`, `miss`, `revalidated`, or `stale`).
+ *
+ * @example
+ * ```ts
+ * const res = await zebar.cachedFetch('https://example.com/data.json');
+ * const data = await res.json();
+ * ```
+ */
+export function cachedFetch(
+  url: string | URL,
+  init?: Omit<RequestInit, 'method' | 'body'>,
+): Promise<Response> {
+  const params = new URLSearchParams({ url: url.toString() });
+  return fetch(`${assetServerPath('proxy')}?${params}`, init);
+}
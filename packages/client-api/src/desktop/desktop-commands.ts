@@ -1,4 +1,5 @@
 import {
+  type Channel,
   type InvokeArgs,
   invoke as tauriInvoke,
 } from '@tauri-apps/api/core';
@@ -6,12 +7,17 @@ import {
 import { createLogger } from '../utils';
 import type { ProviderConfig } from '~/providers';
 import type { WidgetPlacement } from '~/config';
+import type { AnchorTarget } from './widgets';
+import type { BusMessage } from './message-bus';
+import type { StorageChange } from './widget-storage';
 
 const logger = createLogger();
 
 export const desktopCommands = {
   startWidget,
   startWidgetPreset,
+  showWidget,
+  hideWidget,
   listenProvider,
   unlistenProvider,
   callProviderFunction,
@@ -21,10 +27,20 @@ export const desktopCommands = {
   shellSpawn,
   shellWrite,
   shellKill,
+  publish,
+  subscribe,
+  unsubscribe,
+  storageGet,
+  storageSet,
+  storageDelete,
+  storageWatch,
 };
 
 export type ProviderFunction =
   | AudioFunction
+  | HyprlandFunction
+  | I3Function
+  | KomorebiFunction
   | MediaFunction
   | SystrayFunction;
 
@@ -47,6 +63,73 @@ export interface AudioFunction {
       };
 }
 
+export interface HyprlandFunction {
+  type: 'hyprland';
+  function:
+    | {
+        name: 'focus_workspace';
+        args: {
+          workspace: string;
+        };
+      }
+    | {
+        name: 'move_to_workspace';
+        args: {
+          workspace: string;
+          silent?: boolean;
+        };
+      }
+    | {
+        name: 'move_window';
+        args: {
+          direction: 'left' | 'right' | 'up' | 'down';
+        };
+      };
+}
+
+export interface I3Function {
+  type: 'i3';
+  function: {
+    name: 'run_command';
+    args: {
+      command: string;
+    };
+  };
+}
+
+export interface KomorebiFunction {
+  type: 'komorebi';
+  function:
+    | {
+        name: 'focus_workspace' | 'move_to_workspace';
+        args: {
+          workspaceIndex: number;
+          monitorIndex?: number;
+        };
+      }
+    | {
+        name: 'focus_named_workspace';
+        args: {
+          workspaceName: string;
+        };
+      }
+    | {
+        name: 'focus_window' | 'move_window';
+        args: {
+          direction: 'left' | 'right' | 'up' | 'down';
+        };
+      }
+    | {
+        name: 'cycle_focus_workspace' | 'cycle_layout';
+        args: {
+          direction: 'previous' | 'next';
+        };
+      }
+    | {
+        name: 'toggle_monocle' | 'toggle_float';
+      };
+}
+
 export interface MediaFunction {
   type: 'media';
   function: {
@@ -77,12 +160,14 @@ function startWidget(
   packId: string,
   widgetName: string,
   placement: WidgetPlacement,
+  anchorTarget: Required<AnchorTarget> | null,
   isPreview: boolean,
 ): Promise<void> {
   return invoke<void>('start_widget', {
     packId,
     widgetName,
     placement,
+    anchorTarget,
     isPreview,
   });
 }
@@ -101,6 +186,14 @@ function startWidgetPreset(
   });
 }
 
+function showWidget(widgetId: string): Promise<void> {
+  return invoke<void>('show_widget', { widgetId });
+}
+
+function hideWidget(widgetId: string): Promise<void> {
+  return invoke<void>('hide_widget', { widgetId });
+}
+
 function listenProvider(args: {
   configHash: string;
   config: ProviderConfig;
@@ -122,6 +215,41 @@ function callProviderFunction(
   });
 }
 
+function publish(
+  topic: string,
+  payload: unknown,
+  retain: boolean,
+): Promise<void> {
+  return invoke<void>('publish', { topic, payload, retain });
+}
+
+function subscribe<T>(
+  topic: string,
+  onMessage: Channel<BusMessage<T>>,
+): Promise<void> {
+  return invoke<void>('subscribe', { topic, onMessage });
+}
+
+function unsubscribe(topic: string, channelId: number): Promise<void> {
+  return invoke<void>('unsubscribe', { topic, channelId });
+}
+
+function storageGet<T>(key: string): Promise<T | null> {
+  return invoke<T | null>('storage_get', { key });
+}
+
+function storageSet(key: string, value: unknown): Promise<void> {
+  return invoke<void>('storage_set', { key, value });
+}
+
+function storageDelete(key: string): Promise<void> {
+  return invoke<void>('storage_delete', { key });
+}
+
+function storageWatch(onChange: Channel<StorageChange>): Promise<void> {
+  return invoke<void>('storage_watch', { onChange });
+}
+
 function setAlwaysOnTop(): Promise<void> {
   return invoke<void>('set_always_on_top');
 }
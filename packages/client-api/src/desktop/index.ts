@@ -1,6 +1,10 @@
+export * from './cached-fetch';
 export * from './desktop-commands';
 export * from './dialogs';
+export * from './message-bus';
 export * from './monitors';
 export * from './provider-emit';
 export * from './shell';
+export * from './widget-storage';
 export * from './widgets';
+export * from './whoami';
@@ -0,0 +1,79 @@
+import { Channel } from '@tauri-apps/api/core';
+
+import { desktopCommands } from './desktop-commands';
+
+export interface BusMessage<T = unknown> {
+  /**
+   * Topic that the message was published to.
+   */
+  topic: string;
+
+  /**
+   * Payload of the message.
+   */
+  payload: T;
+
+  /**
+   * ID of the widget that published the message.
+   */
+  senderId: string;
+}
+
+export interface PublishOptions {
+  /**
+   * Whether to keep the message as the topic's last message, such that
+   * it's sent to widgets that subscribe afterwards. Publishing a
+   * retained `null` payload clears the retained message.
+   *
+   * Defaults to `false`.
+   */
+  retain?: boolean;
+}
+
+/**
+ * Publishes a message to widgets that are subscribed to the topic.
+ *
+ * Topics are scoped to the widget pack, so only widgets from the same
+ * pack receive the message.
+ *
+ * @example
+ * ```ts
+ * await zebar.publish('calendar/tab', 'month');
+ * ```
+ */
+export async function publish<T = unknown>(
+  topic: string,
+  payload: T,
+  options?: PublishOptions,
+): Promise<void> {
+  return desktopCommands.publish(topic, payload, options?.retain ?? false);
+}
+
+/**
+ * Listens for messages published to the topic by widgets from the same
+ * pack. If the topic has a retained message, the callback is invoked
+ * with it right away.
+ *
+ * @example
+ * ```ts
+ * const unsubscribe = await zebar.subscribe<string>(
+ *   'calendar/tab',
+ *   message => setTab(message.payload),
+ * );
+ * ```
+ *
+ * @returns Function to stop listening for messages.
+ */
+export async function subscribe<T = unknown>(
+  topic: string,
+  callback: (message: BusMessage<T>) => void,
+): Promise<() => Promise<void>> {
+  // Messages are sent over a channel owned by this subscription, so that
+  // other widgets can't listen in on them.
+  const channel = new Channel<BusMessage<T>>();
+  channel.onmessage = callback;
+
+  await desktopCommands.subscribe(topic, channel);
+
+  return () => desktopCommands.unsubscribe(topic, channel.id);
+}
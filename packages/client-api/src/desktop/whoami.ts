@@ -0,0 +1,20 @@
+import { assetServerPath } from './asset-server';
+
+export type WidgetIdentity = {
+  widgetId: string;
+  packId: string;
+  widgetName: string;
+};
+
+/**
+ * Gets the identity of the current widget from the asset server, based
+ * on the token that the page was served with.
+ *
+ * Resolves to `null` if the widget's token has been revoked (e.g. after
+ * the widget was relaunched).
+ */
+export async function whoami(): Promise<WidgetIdentity | null> {
+  const res = await fetch(assetServerPath('whoami'));
+
+  return res.ok ? res.json() : null;
+}
@@ -0,0 +1,101 @@
+import { Channel } from '@tauri-apps/api/core';
+
+import { desktopCommands } from './desktop-commands';
+
+export interface StorageChange<T = unknown> {
+  /**
+   * Key that was changed.
+   */
+  key: string;
+
+  /**
+   * New value of the key, or `null` if the key was deleted.
+   */
+  value: T | null;
+
+  /**
+   * ID of the widget instance that made the change.
+   */
+  senderId: string;
+}
+
+/**
+ * Persistent key-value storage for the current widget.
+ *
+ * Values are shared between all instances of the widget (e.g. the same
+ * widget open on multiple monitors), and unlike `localStorage`, they're
+ * kept when the cache is emptied. Values must be JSON-serializable, and
+ * each widget's storage is limited to 1MB.
+ *
+ * @example
+ * ```ts
+ * await zebar.storage.set('theme', { accent: 'blue' });
+ * const theme = await zebar.storage.get<{ accent: string }>('theme');
+ * ```
+ */
+export const storage = {
+  get,
+  set,
+  delete: remove,
+  onChange,
+};
+
+/**
+ * Callbacks for changes made by other instances of the widget.
+ */
+const changeCallbacks = new Set<(change: StorageChange) => void>();
+
+let watchPromise: Promise<void> | null = null;
+
+/**
+ * Gets the value of a key, or `null` if the key doesn't exist.
+ */
+function get<T = unknown>(key: string): Promise<T | null> {
+  return desktopCommands.storageGet<T>(key);
+}
+
+/**
+ * Sets the value of a key.
+ *
+ * @throws - If the storage limit would be exceeded.
+ */
+function set<T = unknown>(key: string, value: T): Promise<void> {
+  return desktopCommands.storageSet(key, value);
+}
+
+/**
+ * Deletes a key.
+ */
+function remove(key: string): Promise<void> {
+  return desktopCommands.storageDelete(key);
+}
+
+/**
+ * Listens for changes made by other instances of the widget.
+ *
+ * @returns Function to stop listening for changes.
+ */
+async function onChange<T = unknown>(
+  callback: (change: StorageChange<T>) => void,
+): Promise<() => void> {
+  const changeCallback = callback as (change: StorageChange) => void;
+  changeCallbacks.add(changeCallback);
+
+  // Changes are sent over a channel owned by this widget, so that other
+  // widgets can't listen in on them.
+  await (watchPromise ??= watch());
+
+  return () => {
+    changeCallbacks.delete(changeCallback);
+  };
+}
+
+function watch(): Promise<void> {
+  const channel = new Channel<StorageChange>();
+
+  channel.onmessage = change => {
+    changeCallbacks.forEach(callback => callback(change));
+  };
+
+  return desktopCommands.storageWatch(channel);
+}
@@ -64,6 +64,20 @@ export interface Widget {
    * Same as calling `tauriWindow.close()`.
    */
   close(): Promise<void>;
+
+  /**
+   * Hides the widget's window without closing it, such that its state is
+   * kept until it's shown again.
+   *
+   * Widgets that stay hidden for a long time may get closed to free up
+   * memory.
+   */
+  hide(): Promise<void>;
+
+  /**
+   * Shows the widget's window after being hidden via {@link hide}.
+   */
+  show(): Promise<void>;
 }
 
 function getWidgetState(): Widget {
@@ -100,6 +114,8 @@ export function currentWidget(): Widget {
     isPreview: state.isPreview,
     setZOrder: (zOrder: ZOrder) => setZOrder(tauriWindow, zOrder),
     close: () => close(tauriWindow),
+    hide: () => desktopCommands.hideWidget(state.id),
+    show: () => desktopCommands.showWidget(state.id),
   };
 }
 
@@ -122,8 +138,43 @@ async function close(window: ReturnType<typeof getCurrentWindow>) {
   await window.close();
 }
 
+/**
+ * What a popup widget is placed next to.
+ */
+export type AnchorTarget =
+  | {
+      type: 'cursor';
+    }
+  | {
+      /**
+       * Rect within a widget's viewport in logical pixels (e.g. from
+       * `element.getBoundingClientRect()`).
+       */
+      type: 'rect';
+
+      /**
+       * ID of the widget that the rect is in. Defaults to the current
+       * widget.
+       */
+      widgetId?: string;
+      x: number;
+      y: number;
+      width: number;
+      height: number;
+    };
+
 export interface StartWidgetArgs {
   packId?: string;
+
+  /**
+   * Opens the widget as a popup next to the given target, rather than
+   * relative to the monitor. The placement's anchor point determines
+   * which side of the target the popup opens towards, and the popup
+   * flips to the other side if it doesn't fit on the monitor.
+   *
+   * Popups are closed when they lose focus.
+   */
+  anchorTarget?: AnchorTarget;
 }
 
 /**
@@ -134,10 +185,18 @@ export async function startWidget(
   placement: WidgetPlacement,
   args?: StartWidgetArgs,
 ) {
+  const anchorTarget = args?.anchorTarget;
+
   return desktopCommands.startWidget(
     args?.packId ?? currentWidget().packId,
     widgetName,
     placement,
+    anchorTarget?.type === 'rect'
+      ? {
+          ...anchorTarget,
+          widgetId: anchorTarget.widgetId ?? currentWidget().id,
+        }
+      : anchorTarget ?? null,
     getWidgetState().isPreview,
   );
 }
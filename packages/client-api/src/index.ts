@@ -1,10 +1,16 @@
 export * from './config';
 export {
+  cachedFetch,
   currentWidget,
   startWidget,
   startWidgetPreset,
+  publish,
+  subscribe,
+  storage,
   shellExec,
   shellSpawn,
+  whoami,
   type Widget,
+  type WidgetIdentity,
 } from './desktop';
 export * from './providers';
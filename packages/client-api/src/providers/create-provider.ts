@@ -28,6 +28,13 @@ import type {
   HostProviderConfig,
   HostProvider,
 } from './host/host-provider-types';
+import { createHyprlandProvider } from './hyprland/create-hyprland-provider';
+import type {
+  HyprlandProviderConfig,
+  HyprlandProvider,
+} from './hyprland/hyprland-provider-types';
+import { createI3Provider } from './i3/create-i3-provider';
+import type { I3ProviderConfig, I3Provider } from './i3/i3-provider-types';
 import { createIpProvider } from './ip/create-ip-provider';
 import type { IpProviderConfig, IpProvider } from './ip/ip-provider-types';
 import { createKeyboardProvider } from './keyboard/create-keyboard-provider';
@@ -78,6 +85,8 @@ export interface ProviderConfigMap {
   date: DateProviderConfig;
   glazewm: GlazeWmProviderConfig;
   host: HostProviderConfig;
+  hyprland: HyprlandProviderConfig;
+  i3: I3ProviderConfig;
   ip: IpProviderConfig;
   komorebi: KomorebiProviderConfig;
   media: MediaProviderConfig;
@@ -96,6 +105,8 @@ export interface ProviderMap {
   date: DateProvider;
   glazewm: GlazeWmProvider;
   host: HostProvider;
+  hyprland: HyprlandProvider;
+  i3: I3Provider;
   ip: IpProvider;
   komorebi: KomorebiProvider;
   media: MediaProvider;
@@ -140,6 +151,10 @@ export function createProvider<T extends ProviderConfig>(
       return createGlazeWmProvider(config) as any;
     case 'host':
       return createHostProvider(config) as any;
+    case 'hyprland':
+      return createHyprlandProvider(config) as any;
+    case 'i3':
+      return createI3Provider(config) as any;
     case 'ip':
       return createIpProvider(config) as any;
     case 'komorebi':
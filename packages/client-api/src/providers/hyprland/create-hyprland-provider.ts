@@ -0,0 +1,118 @@
+import { z } from 'zod';
+
+import {
+  desktopCommands,
+  getMonitors,
+  onProviderEmit,
+  type HyprlandFunction,
+} from '~/desktop';
+import { getCoordinateDistance } from '~/utils';
+import { createBaseProvider } from '../create-base-provider';
+import type {
+  HyprlandDirection,
+  HyprlandMonitor,
+  HyprlandMoveOptions,
+  HyprlandOutput,
+  HyprlandProvider,
+  HyprlandProviderConfig,
+  HyprlandResponse,
+} from './hyprland-provider-types';
+
+const hyprlandProviderConfigSchema = z.object({
+  type: z.literal('hyprland'),
+});
+
+export function createHyprlandProvider(
+  config: HyprlandProviderConfig,
+): HyprlandProvider {
+  const mergedConfig = hyprlandProviderConfigSchema.parse(config);
+
+  // TODO: Update state when monitors change.
+  return createBaseProvider(mergedConfig, async queue => {
+    const monitors = await getMonitors();
+
+    function getUpdatedState(
+      res: HyprlandResponse,
+      configHash: string,
+    ): HyprlandOutput {
+      const currentPosition = {
+        x: monitors.currentMonitor!.x,
+        y: monitors.currentMonitor!.y,
+      };
+
+      // Get Hyprland monitor that corresponds to the Zebar window's
+      // monitor.
+      const currentMonitor = res.monitors.reduce<HyprlandMonitor | null>(
+        (a, b) =>
+          a &&
+          getCoordinateDistance(currentPosition, a) <
+            getCoordinateDistance(currentPosition, b)
+            ? a
+            : b,
+        null,
+      );
+
+      const focusedMonitor = res.monitors.find(monitor => monitor.focused);
+
+      return {
+        ...res,
+        displayedWorkspace:
+          res.workspaces.find(
+            workspace =>
+              workspace.id === currentMonitor?.activeWorkspace.id,
+          ) ?? null,
+        focusedWorkspace:
+          res.workspaces.find(
+            workspace =>
+              workspace.id === focusedMonitor?.activeWorkspace.id,
+          ) ?? null,
+        currentWorkspaces: res.workspaces.filter(
+          workspace => workspace.monitorId === currentMonitor?.id,
+        ),
+        currentMonitor,
+        ...getFunctions(configHash),
+      };
+    }
+
+    function getFunctions(configHash: string) {
+      function callFunction(fn: HyprlandFunction['function']) {
+        return desktopCommands.callProviderFunction(configHash, {
+          type: 'hyprland',
+          function: fn,
+        });
+      }
+
+      return {
+        focusWorkspace: (workspace: string | number) =>
+          callFunction({
+            name: 'focus_workspace',
+            args: { workspace: String(workspace) },
+          }),
+        moveToWorkspace: (
+          workspace: string | number,
+          options?: HyprlandMoveOptions,
+        ) =>
+          callFunction({
+            name: 'move_to_workspace',
+            args: {
+              workspace: String(workspace),
+              silent: options?.silent,
+            },
+          }),
+        moveWindow: (direction: HyprlandDirection) =>
+          callFunction({ name: 'move_window', args: { direction } }),
+      };
+    }
+
+    return onProviderEmit<HyprlandResponse>(
+      mergedConfig,
+      ({ configHash, result }) => {
+        if ('error' in result) {
+          queue.error(result.error);
+        } else {
+          queue.output(getUpdatedState(result.output, configHash));
+        }
+      },
+    );
+  });
+}
@@ -0,0 +1,111 @@
+import type { Provider } from '../create-base-provider';
+
+export interface HyprlandProviderConfig {
+  type: 'hyprland';
+}
+
+export type HyprlandProvider = Provider<
+  HyprlandProviderConfig,
+  HyprlandOutput
+>;
+
+export interface HyprlandResponse {
+  monitors: HyprlandMonitor[];
+  workspaces: HyprlandWorkspace[];
+  activeWindow: HyprlandWindow | null;
+  submap: string;
+}
+
+export interface HyprlandOutput extends HyprlandResponse {
+  /**
+   * Workspace that is displayed on the monitor of the widget.
+   */
+  displayedWorkspace: HyprlandWorkspace | null;
+
+  /**
+   * Workspace that currently has focus (on any monitor).
+   */
+  focusedWorkspace: HyprlandWorkspace | null;
+
+  /**
+   * Workspaces on the monitor of the widget.
+   */
+  currentWorkspaces: HyprlandWorkspace[];
+
+  /**
+   * Monitor that the widget is on.
+   */
+  currentMonitor: HyprlandMonitor | null;
+
+  /**
+   * Focuses the given workspace.
+   *
+   * @param workspace Workspace ID, name (e.g. `name:web`), or relative
+   * selector (e.g. `e+1`).
+   */
+  focusWorkspace(workspace: string | number): Promise<void>;
+
+  /**
+   * Moves the active window to the given workspace.
+   */
+  moveToWorkspace(
+    workspace: string | number,
+    options?: HyprlandMoveOptions,
+  ): Promise<void>;
+
+  /**
+   * Moves the active window in the given direction.
+   */
+  moveWindow(direction: HyprlandDirection): Promise<void>;
+}
+
+export interface HyprlandMoveOptions {
+  /**
+   * Whether to move the window without following it.
+   */
+  silent?: boolean;
+}
+
+export type HyprlandDirection = 'left' | 'right' | 'up' | 'down';
+
+export interface HyprlandMonitor {
+  id: number;
+  name: string;
+  description: string;
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+  scale: number;
+  activeWorkspace: HyprlandWorkspaceRef;
+  focused: boolean;
+}
+
+export interface HyprlandWorkspace {
+  id: number;
+  name: string;
+  monitor: string;
+  monitorId: number;
+  windows: number;
+  hasFullscreen: boolean;
+  lastWindowTitle: string;
+}
+
+export interface HyprlandWorkspaceRef {
+  id: number;
+  name: string;
+}
+
+export interface HyprlandWindow {
+  address: string;
+  class: string;
+  title: string;
+  initialClass: string;
+  initialTitle: string;
+  pid: number;
+  floating: boolean;
+  workspace: HyprlandWorkspaceRef;
+  monitor: number;
+  at: [number, number];
+  size: [number, number];
+}
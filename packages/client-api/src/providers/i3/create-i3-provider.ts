@@ -0,0 +1,80 @@
+import { z } from 'zod';
+
+import { desktopCommands, getMonitors, onProviderEmit } from '~/desktop';
+import { getCoordinateDistance } from '~/utils';
+import { createBaseProvider } from '../create-base-provider';
+import type {
+  I3Monitor,
+  I3Output,
+  I3Provider,
+  I3ProviderConfig,
+  I3Response,
+} from './i3-provider-types';
+
+const i3ProviderConfigSchema = z.object({
+  type: z.literal('i3'),
+});
+
+export function createI3Provider(config: I3ProviderConfig): I3Provider {
+  const mergedConfig = i3ProviderConfigSchema.parse(config);
+
+  // TODO: Update state when monitors change.
+  return createBaseProvider(mergedConfig, async queue => {
+    const monitors = await getMonitors();
+
+    function getUpdatedState(
+      res: I3Response,
+      configHash: string,
+    ): I3Output {
+      const currentPosition = {
+        x: monitors.currentMonitor!.x,
+        y: monitors.currentMonitor!.y,
+      };
+
+      // Get i3 monitor that corresponds to the Zebar window's monitor.
+      const currentMonitor = res.monitors.reduce<I3Monitor | null>(
+        (a, b) =>
+          a &&
+          getCoordinateDistance(currentPosition, a.rect) <
+            getCoordinateDistance(currentPosition, b.rect)
+            ? a
+            : b,
+        null,
+      );
+
+      return {
+        ...res,
+        displayedWorkspace:
+          res.workspaces.find(
+            workspace =>
+              workspace.name === currentMonitor?.currentWorkspace,
+          ) ?? null,
+        focusedWorkspace:
+          res.workspaces.find(workspace => workspace.focused) ?? null,
+        currentWorkspaces: res.workspaces.filter(
+          workspace => workspace.output === currentMonitor?.name,
+        ),
+        currentMonitor,
+        runCommand: (command: string) =>
+          desktopCommands.callProviderFunction(configHash, {
+            type: 'i3',
+            function: {
+              name: 'run_command',
+              args: { command },
+            },
+          }),
+      };
+    }
+
+    return onProviderEmit<I3Response>(
+      mergedConfig,
+      ({ configHash, result }) => {
+        if ('error' in result) {
+          queue.error(result.error);
+        } else {
+          queue.output(getUpdatedState(result.output, configHash));
+        }
+      },
+    );
+  });
+}
@@ -0,0 +1,81 @@
+import type { Provider } from '../create-base-provider';
+
+export interface I3ProviderConfig {
+  type: 'i3';
+}
+
+export type I3Provider = Provider<I3ProviderConfig, I3Output>;
+
+export interface I3Response {
+  monitors: I3Monitor[];
+  workspaces: I3Workspace[];
+  focusedWindow: I3Window | null;
+  bindingMode: string;
+}
+
+export interface I3Output extends I3Response {
+  /**
+   * Workspace that is displayed on the monitor of the widget.
+   */
+  displayedWorkspace: I3Workspace | null;
+
+  /**
+   * Workspace that currently has focus (on any monitor).
+   */
+  focusedWorkspace: I3Workspace | null;
+
+  /**
+   * Workspaces on the monitor of the widget.
+   */
+  currentWorkspaces: I3Workspace[];
+
+  /**
+   * Monitor that the widget is on.
+   */
+  currentMonitor: I3Monitor | null;
+
+  /**
+   * Runs an i3 or Sway command (e.g. `workspace number 2`).
+   *
+   * Commands that run programs (e.g. `exec`) are refused. Use the
+   * shell commands API instead.
+   */
+  runCommand(command: string): Promise<void>;
+}
+
+export interface I3Monitor {
+  name: string;
+  active: boolean;
+  primary: boolean;
+  focused: boolean;
+  currentWorkspace: string | null;
+  rect: I3Rect;
+}
+
+export interface I3Workspace {
+  id: number;
+  num: number;
+  name: string;
+  visible: boolean;
+  focused: boolean;
+  urgent: boolean;
+  output: string;
+  rect: I3Rect;
+}
+
+export interface I3Window {
+  id: number;
+  title: string | null;
+  appId: string | null;
+  class: string | null;
+  pid: number | null;
+  floating: boolean;
+  rect: I3Rect;
+}
+
+export interface I3Rect {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
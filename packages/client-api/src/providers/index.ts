@@ -4,6 +4,8 @@ export * from './cpu/cpu-provider-types';
 export * from './date/date-provider-types';
 export * from './glazewm/glazewm-provider-types';
 export * from './host/host-provider-types';
+export * from './hyprland/hyprland-provider-types';
+export * from './i3/i3-provider-types';
 export * from './ip/ip-provider-types';
 export * from './keyboard/keyboard-provider-types';
 export * from './disk/disk-provider-types';
@@ -1,9 +1,17 @@
 import { z } from 'zod';
 
-import { getMonitors, onProviderEmit } from '~/desktop';
+import {
+  desktopCommands,
+  getMonitors,
+  onProviderEmit,
+  type KomorebiFunction,
+} from '~/desktop';
 import { getCoordinateDistance } from '~/utils';
 import { createBaseProvider } from '../create-base-provider';
 import type {
+  KomorebiCycleDirection,
+  KomorebiDirection,
+  KomorebiMonitorOptions,
   KomorebiProvider,
   KomorebiProviderConfig,
   KomorebiResponse,
@@ -11,6 +19,13 @@ import type {
 
 const komorebiProviderConfigSchema = z.object({
   type: z.literal('komorebi'),
+  reconnectBackoff: z
+    .object({
+      initialDelay: z.coerce.number().int().positive().optional(),
+      maxDelay: z.coerce.number().int().positive().optional(),
+      multiplier: z.coerce.number().int().min(1).optional(),
+    })
+    .optional(),
 });
 
 export function createKomorebiProvider(
@@ -22,7 +37,10 @@ export function createKomorebiProvider(
   return createBaseProvider(mergedConfig, async queue => {
     const monitors = await getMonitors();
 
-    async function getUpdatedState(res: KomorebiResponse) {
+    async function getUpdatedState(
+      res: KomorebiResponse,
+      configHash: string,
+    ) {
       const currentPosition = {
         x: monitors.currentMonitor!.x,
         y: monitors.currentMonitor!.y,
@@ -60,6 +78,8 @@ export function createKomorebiProvider(
         ]!;
 
       return {
+        status: res.status,
+        event: res.event,
         displayedWorkspace: displayedKomorebiWorkspace,
         focusedWorkspace: focusedKomorebiWorkspace,
         currentWorkspaces: currentKomorebiMonitor.workspaces,
@@ -67,16 +87,69 @@ export function createKomorebiProvider(
         focusedMonitor: focusedKomorebiMonitor,
         currentMonitor: currentKomorebiMonitor,
         allMonitors: res.allMonitors,
+        ...getFunctions(configHash),
+      };
+    }
+
+    function getFunctions(configHash: string) {
+      function callFunction(fn: KomorebiFunction['function']) {
+        return desktopCommands.callProviderFunction(configHash, {
+          type: 'komorebi',
+          function: fn,
+        });
+      }
+
+      return {
+        focusWorkspace: (
+          workspaceIndex: number,
+          options?: KomorebiMonitorOptions,
+        ) =>
+          callFunction({
+            name: 'focus_workspace',
+            args: { workspaceIndex, monitorIndex: options?.monitorIndex },
+          }),
+        focusNamedWorkspace: (workspaceName: string) =>
+          callFunction({
+            name: 'focus_named_workspace',
+            args: { workspaceName },
+          }),
+        cycleFocusWorkspace: (direction: KomorebiCycleDirection) =>
+          callFunction({
+            name: 'cycle_focus_workspace',
+            args: { direction },
+          }),
+        moveToWorkspace: (
+          workspaceIndex: number,
+          options?: KomorebiMonitorOptions,
+        ) =>
+          callFunction({
+            name: 'move_to_workspace',
+            args: { workspaceIndex, monitorIndex: options?.monitorIndex },
+          }),
+        focusWindow: (direction: KomorebiDirection) =>
+          callFunction({ name: 'focus_window', args: { direction } }),
+        moveWindow: (direction: KomorebiDirection) =>
+          callFunction({ name: 'move_window', args: { direction } }),
+        cycleLayout: (direction: KomorebiCycleDirection) =>
+          callFunction({ name: 'cycle_layout', args: { direction } }),
+        toggleMonocle: () => callFunction({ name: 'toggle_monocle' }),
+        toggleFloat: () => callFunction({ name: 'toggle_float' }),
       };
     }
 
     return onProviderEmit<KomorebiResponse>(
       mergedConfig,
-      async ({ result }) => {
+      async ({ configHash, result }) => {
         if ('error' in result) {
           queue.error(result.error);
+        } else if (result.output.allMonitors.length === 0) {
+          // No state is known if Komorebi has never been reachable.
+          queue.error('Komorebi is not running.');
         } else {
-          const updatedState = await getUpdatedState(result.output);
+          const updatedState = await getUpdatedState(
+            result.output,
+            configHash,
+          );
           queue.output(updatedState);
         }
       },
@@ -2,6 +2,29 @@ import type { Provider } from '../create-base-provider';
 
 export interface KomorebiProviderConfig {
   type: 'komorebi';
+
+  /**
+   * Delays between attempts to reconnect to Komorebi.
+   */
+  reconnectBackoff?: {
+    /**
+     * Delay in milliseconds after the first failed connection attempt.
+     * Defaults to 1000.
+     */
+    initialDelay?: number;
+
+    /**
+     * Upper bound in milliseconds for the delay between attempts.
+     * Defaults to 15000.
+     */
+    maxDelay?: number;
+
+    /**
+     * Factor to multiply the delay by after each failed attempt.
+     * Defaults to 2.
+     */
+    multiplier?: number;
+  };
 }
 
 export type KomorebiProvider = Provider<
@@ -10,6 +33,18 @@ export type KomorebiProvider = Provider<
 >;
 
 export interface KomorebiOutput {
+  /**
+   * Whether Zebar is currently connected to Komorebi. When disconnected,
+   * the remaining fields hold the last known state.
+   */
+  status: KomorebiStatus;
+
+  /**
+   * Event that triggered the latest update (e.g. `FocusChange`). This is
+   * `null` when the connection to Komorebi is lost.
+   */
+  event: KomorebiEvent | null;
+
   /**
    * Workspace displayed on the current monitor.
    */
@@ -44,13 +79,91 @@ export interface KomorebiOutput {
    * Monitor that is nearest to this Zebar widget.
    */
   currentMonitor: KomorebiMonitor;
+
+  /**
+   * Focuses the workspace at the given index. Targets the focused monitor
+   * unless a monitor index is given.
+   */
+  focusWorkspace(
+    workspaceIndex: number,
+    options?: KomorebiMonitorOptions,
+  ): Promise<void>;
+
+  /**
+   * Focuses the workspace with the given name.
+   */
+  focusNamedWorkspace(workspaceName: string): Promise<void>;
+
+  /**
+   * Focuses the previous or next workspace on the focused monitor.
+   */
+  cycleFocusWorkspace(direction: KomorebiCycleDirection): Promise<void>;
+
+  /**
+   * Moves the focused window to the workspace at the given index.
+   * Targets the focused monitor unless a monitor index is given.
+   */
+  moveToWorkspace(
+    workspaceIndex: number,
+    options?: KomorebiMonitorOptions,
+  ): Promise<void>;
+
+  /**
+   * Changes focus to the window in the given direction.
+   */
+  focusWindow(direction: KomorebiDirection): Promise<void>;
+
+  /**
+   * Moves the focused window in the given direction.
+   */
+  moveWindow(direction: KomorebiDirection): Promise<void>;
+
+  /**
+   * Cycles the layout of the focused workspace.
+   */
+  cycleLayout(direction: KomorebiCycleDirection): Promise<void>;
+
+  /**
+   * Toggles monocle mode for the focused window.
+   */
+  toggleMonocle(): Promise<void>;
+
+  /**
+   * Toggles floating mode for the focused window.
+   */
+  toggleFloat(): Promise<void>;
 }
 
+export interface KomorebiMonitorOptions {
+  monitorIndex?: number;
+}
+
+export type KomorebiDirection = 'left' | 'right' | 'up' | 'down';
+
+export type KomorebiCycleDirection = 'previous' | 'next';
+
 export interface KomorebiResponse {
+  status: KomorebiStatus;
+  event: KomorebiEvent | null;
   allMonitors: KomorebiMonitor[];
   focusedMonitorIndex: number;
 }
 
+export type KomorebiStatus = 'connected' | 'disconnected';
+
+export interface KomorebiEvent {
+  /**
+   * Name of the event (e.g. `FocusChange`, `Manage`,
+   * `FocusWorkspaceNumber`).
+   */
+  type: string;
+
+  /**
+   * Payload of the event. Varies by event type.
+   */
+  content: unknown;
+}
+
 export interface KomorebiMonitor {
   id: number;
   device: string | null;
@@ -67,6 +180,7 @@ export interface KomorebiWorkspace {
   containerPadding: number | null;
   floatingWindows: KomorebiWindow[];
   focusedContainerIndex: number;
+  focusedWindow: KomorebiWindow | null;
   latestLayout: KomorebiRect[];
   layout: KomorebiLayout;
   layoutFlip: KomorebiLayoutFlip | null;
@@ -80,6 +194,7 @@ export interface KomorebiWorkspace {
 export interface KomorebiContainer {
   id: string;
   windows: KomorebiWindow[];
+  focusedWindowIndex: number;
 }
 
 export interface KomorebiWindow {
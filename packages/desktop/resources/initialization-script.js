@@ -11,7 +11,7 @@ setInterval(
   1000 * 60 * 15,
 );
 
-if (window.location.host === '127.0.0.1:6124') {
+if (window.location.origin === window.__ZEBAR_ASSET_SERVER_ORIGIN) {
   if ('serviceWorker' in navigator) {
     navigator.serviceWorker
       .register('/__zebar/sw.js', { scope: '/' })
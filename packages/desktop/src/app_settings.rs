@@ -1,4 +1,5 @@
 use std::{
+  collections::BTreeMap,
   fs::{self},
   path::{Path, PathBuf},
   sync::Arc,
@@ -13,6 +14,8 @@ use crate::{
   common::{copy_dir_all, read_and_parse_json, visit_deep, PathExt},
   config_migration::apply_config_migrations,
   marketplace_installer::STARTER_PACK_ID,
+  monitor_state::Monitor,
+  startup_condition::{StartupCondition, StartupEnvironment},
 };
 
 pub const VERSION_NUMBER: &str = env!("VERSION_NUMBER");
@@ -26,6 +29,43 @@ pub struct AppSettingsValue {
 
   /// Widget configs to be launched on startup.
   pub startup_configs: Vec<StartupConfig>,
+
+  /// Whether to reopen the widgets that were open when Zebar was last
+  /// closed.
+  #[serde(default)]
+  pub restore_session: bool,
+
+  /// Preferred port for the localhost asset server. Falls back to a free
+  /// port if it's already in use.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub asset_server_port: Option<u16>,
+
+  /// How updates to installed marketplace packs are handled.
+  #[serde(default)]
+  pub update_policy: UpdatePolicy,
+
+  /// Content-Security-Policy overrides by widget pack ID. Replaces the
+  /// pack's policy, including the default for marketplace packs. An
+  /// empty policy disables it.
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub content_security_policies: BTreeMap<String, String>,
+}
+
+/// How updates to installed marketplace packs are handled.
+#[derive(
+  Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum UpdatePolicy {
+  /// Never check for updates automatically.
+  Manual,
+
+  /// Periodically check for updates and notify when some are available.
+  #[default]
+  Notify,
+
+  /// Periodically check for updates and install them.
+  Auto,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -39,6 +79,26 @@ pub struct StartupConfig {
 
   /// Preset name within the widget config.
   pub preset: String,
+
+  /// Conditions for launching the widget. Launched unconditionally if
+  /// not set.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub condition: Option<StartupCondition>,
+}
+
+impl StartupConfig {
+  /// Whether the config refers to the given widget preset, regardless of
+  /// its condition.
+  pub fn is_preset(
+    &self,
+    pack_id: &str,
+    widget_name: &str,
+    preset_name: &str,
+  ) -> bool {
+    self.pack == pack_id
+      && self.widget == widget_name
+      && self.preset == preset_name
+  }
 }
 
 #[derive(Debug)]
@@ -55,12 +115,26 @@ pub struct AppSettings {
   /// Directory where webview cache files are stored.
   pub webview_cache_dir: PathBuf,
 
+  /// Directory where remote resources fetched via the asset server's
+  /// caching proxy are stored.
+  pub proxy_cache_dir: PathBuf,
+
   /// Directory where marketplace metadata files are stored.
   pub marketplace_meta_dir: PathBuf,
 
   /// Directory where downloaded marketplace widget packs are stored.
   pub marketplace_download_dir: PathBuf,
 
+  /// Directory where persistent widget storage files are stored.
+  pub widget_storage_dir: PathBuf,
+
+  /// Path to the file where open widgets are saved for session restore.
+  pub session_file: PathBuf,
+
+  /// Path to the file where the port of the running asset server is
+  /// saved (for `zebar query asset-server`).
+  pub asset_server_file: PathBuf,
+
   /// Path to the config migration file.
   pub migration_file: PathBuf,
 
@@ -83,7 +157,13 @@ impl AppSettings {
       .resolve("zebar/webview-cache", BaseDirectory::Data)
       .context("Unable to resolve app data directory.")?;
 
+    let proxy_cache_dir = app_handle
+      .path()
+      .resolve("zebar/proxy-cache", BaseDirectory::Data)
+      .context("Unable to resolve app data directory.")?;
+
     let marketplace_meta_dir = config_dir.join(".marketplace");
+    let widget_storage_dir = config_dir.join(".storage");
 
     let marketplace_download_dir = app_handle
       .path()
@@ -98,8 +178,10 @@ impl AppSettings {
     for dir in [
       &config_dir,
       &webview_cache_dir,
+      &proxy_cache_dir,
       &marketplace_meta_dir,
       &marketplace_download_dir,
+      &widget_storage_dir,
     ] {
       fs::create_dir_all(dir)?;
     }
@@ -114,9 +196,17 @@ impl AppSettings {
       is_first_run,
       config_dir: config_dir.canonicalize_pretty()?,
       webview_cache_dir: webview_cache_dir.canonicalize_pretty()?,
+      proxy_cache_dir: proxy_cache_dir.canonicalize_pretty()?,
       marketplace_meta_dir: marketplace_meta_dir.canonicalize_pretty()?,
       marketplace_download_dir: marketplace_download_dir
         .canonicalize_pretty()?,
+      widget_storage_dir: widget_storage_dir.canonicalize_pretty()?,
+      session_file: config_dir
+        .canonicalize_pretty()?
+        .join(".session.json"),
+      asset_server_file: Self::asset_server_file(
+        &config_dir.canonicalize_pretty()?,
+      ),
       migration_file,
       value: Arc::new(Mutex::new(settings)),
       _settings_change_rx,
@@ -203,7 +293,12 @@ impl AppSettings {
           false => "vanilla".into(),
         },
         preset: "default".into(),
+        condition: None,
       }],
+      restore_session: false,
+      asset_server_port: None,
+      update_policy: UpdatePolicy::default(),
+      content_security_policies: BTreeMap::new(),
     };
 
     let settings_path = config_dir.join("settings.json");
@@ -216,11 +311,81 @@ impl AppSettings {
     Ok(())
   }
 
-  /// Returns the widget configs to open on startup.
+  /// Returns all widget configs to open on startup, including ones
+  /// whose conditions aren't met.
   pub async fn startup_configs(&self) -> Vec<StartupConfig> {
     self.value.lock().await.startup_configs.clone()
   }
 
+  /// Returns the widget configs to open on startup whose conditions are
+  /// met with the given monitors connected.
+  pub async fn matching_startup_configs(
+    &self,
+    monitors: &[Monitor],
+  ) -> Vec<StartupConfig> {
+    let env = StartupEnvironment::current(monitors);
+
+    self
+      .startup_configs()
+      .await
+      .into_iter()
+      .filter(|config| {
+        config
+          .condition
+          .as_ref()
+          .is_none_or(|condition| condition.is_met(&env))
+      })
+      .collect()
+  }
+
+  /// Returns the preferred port for the asset server.
+  pub async fn asset_server_port(&self) -> Option<u16> {
+    self.value.lock().await.asset_server_port
+  }
+
+  /// Returns how updates to marketplace packs are handled.
+  pub async fn update_policy(&self) -> UpdatePolicy {
+    self.value.lock().await.update_policy
+  }
+
+  /// Returns the user's Content-Security-Policy override for the widget
+  /// pack, if any.
+  pub async fn content_security_policy(
+    &self,
+    pack_id: &str,
+  ) -> Option<String> {
+    self
+      .value
+      .lock()
+      .await
+      .content_security_policies
+      .get(pack_id)
+      .cloned()
+  }
+
+  /// Gets the path to the asset server file within the config directory.
+  pub fn asset_server_file(config_dir: &Path) -> PathBuf {
+    config_dir.join(".asset-server.json")
+  }
+
+  /// Returns whether to restore open widgets from the last session.
+  pub async fn restore_session(&self) -> bool {
+    self.value.lock().await.restore_session
+  }
+
+  /// Enables or disables restoring open widgets from the last session.
+  pub async fn set_restore_session(
+    &self,
+    restore_session: bool,
+  ) -> anyhow::Result<()> {
+    let new_settings = AppSettingsValue {
+      restore_session,
+      ..self.value.lock().await.clone()
+    };
+
+    self.write_settings(new_settings).await
+  }
+
   /// Adds the given config to be launched on startup.
   pub async fn add_startup_config(
     &self,
@@ -230,17 +395,20 @@ impl AppSettings {
   ) -> anyhow::Result<()> {
     let mut new_settings = { self.value.lock().await.clone() };
 
-    let startup_config = StartupConfig {
-      pack: pack_id.to_string(),
-      widget: widget_name.to_string(),
-      preset: preset_name.to_string(),
-    };
-
-    if new_settings.startup_configs.contains(&startup_config) {
+    if new_settings
+      .startup_configs
+      .iter()
+      .any(|config| config.is_preset(pack_id, widget_name, preset_name))
+    {
       return Ok(());
     }
 
-    new_settings.startup_configs.push(startup_config);
+    new_settings.startup_configs.push(StartupConfig {
+      pack: pack_id.to_string(),
+      widget: widget_name.to_string(),
+      preset: preset_name.to_string(),
+      condition: None,
+    });
     self.write_settings(new_settings).await
   }
 
@@ -0,0 +1,572 @@
+use std::{
+  fs,
+  net::{IpAddr, Ipv4Addr, Ipv6Addr},
+  path::{Path, PathBuf},
+  sync::Arc,
+  time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context};
+use reqwest::{
+  dns::{Addrs, Name, Resolve, Resolving},
+  header::{
+    CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+  },
+  redirect, Client, StatusCode, Url,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::common::{read_and_parse_json, write_atomic};
+
+/// Timeout for requests to the remote server.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Maximum size of a response body (20MB).
+const MAX_RESPONSE_SIZE: u64 = 20 * 1024 * 1024;
+
+/// Maximum number of redirects to follow.
+const MAX_REDIRECTS: usize = 10;
+
+/// How a proxied response was served.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CacheStatus {
+  /// Served from the cache without contacting the remote server.
+  Hit,
+
+  /// Fetched from the remote server.
+  Miss,
+
+  /// Served from the cache after the remote server confirmed that it's
+  /// unchanged.
+  Revalidated,
+
+  /// Served from an expired cache entry because the remote server
+  /// couldn't be reached.
+  Stale,
+}
+
+impl CacheStatus {
+  /// Value for the `X-Zebar-Cache` response header.
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      CacheStatus::Hit => "hit",
+      CacheStatus::Miss => "miss",
+      CacheStatus::Revalidated => "revalidated",
+      CacheStatus::Stale => "stale",
+    }
+  }
+}
+
+/// Response returned by the caching proxy.
+#[derive(Clone, Debug)]
+pub struct CachedResponse {
+  pub status: u16,
+  pub content_type: Option<String>,
+  pub body: Vec<u8>,
+  pub cache_status: CacheStatus,
+}
+
+/// Metadata of a cached response. Stored alongside the response body.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CacheEntry {
+  url: String,
+  status: u16,
+  content_type: Option<String>,
+  etag: Option<String>,
+  last_modified: Option<String>,
+
+  /// When the response was last fetched or revalidated (in seconds since
+  /// the Unix epoch).
+  fetched_at: u64,
+
+  /// How long the response is fresh for (in seconds).
+  duration: u64,
+}
+
+impl CacheEntry {
+  fn is_fresh(&self, now: u64) -> bool {
+    now < self.fetched_at.saturating_add(self.duration)
+  }
+}
+
+/// Disk cache for remote resources fetched on behalf of widgets.
+///
+/// Unlike the service worker cache, entries are kept when the webview
+/// cache is cleared, and expired entries are served if the remote server
+/// is unreachable.
+///
+/// Only resources on public addresses can be fetched, such that widgets
+/// can't use the cache to reach services on the local machine or
+/// network.
+#[derive(Debug)]
+pub struct AssetCache {
+  cache_dir: PathBuf,
+  client: Client,
+
+  /// Whether loopback and private addresses can be fetched.
+  allow_private: bool,
+}
+
+impl AssetCache {
+  /// Creates a new `AssetCache` instance.
+  pub fn new(cache_dir: PathBuf) -> anyhow::Result<Self> {
+    Self::build(cache_dir, false)
+  }
+
+  fn build(
+    cache_dir: PathBuf,
+    allow_private: bool,
+  ) -> anyhow::Result<Self> {
+    fs::create_dir_all(&cache_dir)?;
+
+    let mut builder = Client::builder().timeout(REQUEST_TIMEOUT);
+
+    if !allow_private {
+      // Resolved addresses are checked by the resolver, and addresses in
+      // redirect URLs are checked before following them.
+      builder = builder.dns_resolver(Arc::new(PublicResolver)).redirect(
+        redirect::Policy::custom(|attempt| {
+          if attempt.previous().len() >= MAX_REDIRECTS {
+            attempt.error("Too many redirects.")
+          } else if has_private_ip_host(attempt.url()) {
+            attempt.error("Redirect to a private address.")
+          } else {
+            attempt.follow()
+          }
+        }),
+      );
+    }
+
+    Ok(Self {
+      cache_dir,
+      client: builder.build()?,
+      allow_private,
+    })
+  }
+
+  /// Fetches a remote resource, serving it from the cache if it was
+  /// fetched within `duration`.
+  ///
+  /// Expired entries are revalidated with the remote server using their
+  /// `ETag` or `Last-Modified` headers.
+  pub async fn fetch(
+    &self,
+    url: &str,
+    duration: Duration,
+  ) -> anyhow::Result<CachedResponse> {
+    if !self.allow_private && has_private_ip_host(&Url::parse(url)?) {
+      bail!("URL {} has a private address.", url);
+    }
+
+    let (meta_path, body_path) = self.entry_paths(url);
+    let now = unix_now();
+
+    let cached = Self::read_entry(&meta_path, &body_path)
+      .filter(|(entry, _)| entry.url == url);
+
+    if let Some((entry, body)) = &cached {
+      if entry.is_fresh(now) {
+        return Ok(Self::cached_response(entry, body, CacheStatus::Hit));
+      }
+    }
+
+    let mut request = self.client.get(url);
+
+    if let Some((entry, _)) = &cached {
+      if let Some(etag) = &entry.etag {
+        request = request.header(IF_NONE_MATCH, etag);
+      }
+
+      if let Some(last_modified) = &entry.last_modified {
+        request = request.header(IF_MODIFIED_SINCE, last_modified);
+      }
+    }
+
+    let mut response = match request.send().await {
+      Ok(response) => response,
+      Err(err) => {
+        return match cached {
+          Some((entry, body)) => {
+            warn!("Serving stale response for {}: {:?}", url, err);
+            Ok(Self::cached_response(&entry, &body, CacheStatus::Stale))
+          }
+          None => Err(err).context(format!("Failed to fetch {}.", url)),
+        };
+      }
+    };
+
+    let status = response.status();
+
+    match cached {
+      // Remote resource is unchanged, so extend the cached entry.
+      Some((mut entry, body)) if status == StatusCode::NOT_MODIFIED => {
+        entry.fetched_at = now;
+        entry.duration = duration.as_secs();
+        write_atomic(&meta_path, &serde_json::to_vec(&entry)?)?;
+
+        Ok(Self::cached_response(
+          &entry,
+          &body,
+          CacheStatus::Revalidated,
+        ))
+      }
+      Some((entry, body)) if status.is_server_error() => {
+        warn!("Serving stale response for {}: {}", url, status);
+        Ok(Self::cached_response(&entry, &body, CacheStatus::Stale))
+      }
+      _ => {
+        let header = |name| {
+          response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from)
+        };
+
+        let entry = CacheEntry {
+          url: url.to_string(),
+          status: status.as_u16(),
+          content_type: header(CONTENT_TYPE),
+          etag: header(ETAG),
+          last_modified: header(LAST_MODIFIED),
+          fetched_at: now,
+          duration: duration.as_secs(),
+        };
+
+        let body = Self::read_body(&mut response).await?;
+
+        // Only successful responses are cached.
+        if status.is_success() {
+          write_atomic(&body_path, &body)?;
+          write_atomic(&meta_path, &serde_json::to_vec(&entry)?)?;
+        }
+
+        Ok(Self::cached_response(&entry, &body, CacheStatus::Miss))
+      }
+    }
+  }
+
+  /// Reads a response body, failing if it exceeds `MAX_RESPONSE_SIZE`.
+  async fn read_body(
+    response: &mut reqwest::Response,
+  ) -> anyhow::Result<Vec<u8>> {
+    let size_error = || {
+      anyhow::anyhow!(
+        "Response exceeds maximum size of {} bytes.",
+        MAX_RESPONSE_SIZE
+      )
+    };
+
+    if response
+      .content_length()
+      .is_some_and(|len| len > MAX_RESPONSE_SIZE)
+    {
+      return Err(size_error());
+    }
+
+    let mut body = Vec::new();
+
+    while let Some(chunk) = response.chunk().await? {
+      if (body.len() + chunk.len()) as u64 > MAX_RESPONSE_SIZE {
+        return Err(size_error());
+      }
+
+      body.extend_from_slice(&chunk);
+    }
+
+    Ok(body)
+  }
+
+  /// Gets the paths to the metadata and body files of a URL's entry.
+  fn entry_paths(&self, url: &str) -> (PathBuf, PathBuf) {
+    let key = Sha256::digest(url.as_bytes())
+      .iter()
+      .map(|byte| format!("{:02x}", byte))
+      .collect::<String>();
+
+    (
+      self.cache_dir.join(format!("{key}.json")),
+      self.cache_dir.join(format!("{key}.body")),
+    )
+  }
+
+  /// Reads a cache entry and its body, if both exist.
+  fn read_entry(
+    meta_path: &Path,
+    body_path: &Path,
+  ) -> Option<(CacheEntry, Vec<u8>)> {
+    let entry = read_and_parse_json::<CacheEntry>(meta_path).ok()?;
+    let body = fs::read(body_path).ok()?;
+
+    Some((entry, body))
+  }
+
+  fn cached_response(
+    entry: &CacheEntry,
+    body: &[u8],
+    cache_status: CacheStatus,
+  ) -> CachedResponse {
+    CachedResponse {
+      status: entry.status,
+      content_type: entry.content_type.clone(),
+      body: body.to_vec(),
+      cache_status,
+    }
+  }
+}
+
+/// DNS resolver that fails for hosts that resolve to a loopback or
+/// private address.
+///
+/// Checking the resolved addresses (rather than resolving the host
+/// separately) means a host can't resolve to a public address for the
+/// check and a private one for the request.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+  fn resolve(&self, name: Name) -> Resolving {
+    Box::pin(async move {
+      let addrs = tokio::net::lookup_host((name.as_str(), 0))
+        .await?
+        .collect::<Vec<_>>();
+
+      if addrs.iter().any(|addr| !is_public_ip(addr.ip())) {
+        return Err(
+          format!("Host {} has a private address.", name.as_str()).into(),
+        );
+      }
+
+      Ok(Box::new(addrs.into_iter()) as Addrs)
+    })
+  }
+}
+
+/// Whether the URL's host is an IP address that isn't public.
+///
+/// IP addresses in URLs are connected to as-is without being resolved,
+/// so they need to be checked separately.
+fn has_private_ip_host(url: &Url) -> bool {
+  url
+    .host_str()
+    .map(|host| host.trim_start_matches('[').trim_end_matches(']'))
+    .and_then(|host| host.parse::<IpAddr>().ok())
+    .is_some_and(|ip| !is_public_ip(ip))
+}
+
+/// Whether the IP address is publicly routable (i.e. not a loopback,
+/// private, link-local, or otherwise reserved address).
+fn is_public_ip(ip: IpAddr) -> bool {
+  match ip {
+    IpAddr::V4(ip) => is_public_ipv4(ip),
+    IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+      Some(ip) => is_public_ipv4(ip),
+      None => is_public_ipv6(ip),
+    },
+  }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+  let [a, b, ..] = ip.octets();
+
+  !(ip.is_unspecified()
+    || ip.is_loopback()
+    || ip.is_private()
+    || ip.is_link_local()
+    || ip.is_broadcast()
+    || ip.is_documentation()
+    // "This network" (0.0.0.0/8).
+    || a == 0
+    // Shared address space (100.64.0.0/10).
+    || (a == 100 && (b & 0xc0) == 64)
+    // Reserved and multicast (224.0.0.0/3).
+    || a >= 224)
+}
+
+fn is_public_ipv6(ip: Ipv6Addr) -> bool {
+  let first = ip.segments()[0];
+
+  !(ip.is_unspecified()
+    || ip.is_loopback()
+    || ip.is_multicast()
+    // Unique local (fc00::/7).
+    || (first & 0xfe00) == 0xfc00
+    // Link-local (fe80::/10).
+    || (first & 0xffc0) == 0xfe80)
+}
+
+fn unix_now() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|duration| duration.as_secs())
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{
+    net::TcpListener,
+    sync::{
+      atomic::{AtomicUsize, Ordering},
+      Arc,
+    },
+  };
+
+  use super::*;
+  use crate::common::test_util::{
+    http_response, spawn_http_server, temp_dir,
+  };
+
+  /// Local HTTP server that responds with an `ETag`, and with
+  /// `304 Not Modified` if the request's `If-None-Match` matches.
+  ///
+  /// Returns the server's URL and a counter of received requests.
+  fn spawn_server(etag: &'static str) -> (String, Arc<AtomicUsize>) {
+    let request_count = Arc::new(AtomicUsize::new(0));
+    let counter = request_count.clone();
+
+    let url = spawn_http_server(move |request| {
+      counter.fetch_add(1, Ordering::SeqCst);
+
+      let is_not_modified = request
+        .to_lowercase()
+        .contains(&format!("if-none-match: {}", etag.to_lowercase()));
+
+      match is_not_modified {
+        true => http_response("304 Not Modified", &[], ""),
+        false => http_response(
+          "200 OK",
+          &[("Content-Type", "application/json"), ("ETag", etag)],
+          r#"{"temp":21}"#,
+        ),
+      }
+    });
+
+    (format!("{url}/data.json"), request_count)
+  }
+
+  fn temp_cache(name: &str) -> (AssetCache, PathBuf) {
+    let dir = temp_dir(&format!("asset-cache-{name}"));
+
+    // The test server is on a loopback address.
+    (AssetCache::build(dir.clone(), true).unwrap(), dir)
+  }
+
+  #[tokio::test]
+  async fn test_fresh_entry_is_hit() {
+    let (url, request_count) = spawn_server("\"v1\"");
+    let (cache, dir) = temp_cache("hit");
+    let duration = Duration::from_secs(60);
+
+    let response = cache.fetch(&url, duration).await.unwrap();
+    assert_eq!(response.cache_status, CacheStatus::Miss);
+    assert_eq!(response.body, br#"{"temp":21}"#);
+    assert_eq!(response.content_type.as_deref(), Some("application/json"));
+
+    let response = cache.fetch(&url, duration).await.unwrap();
+    assert_eq!(response.cache_status, CacheStatus::Hit);
+    assert_eq!(response.body, br#"{"temp":21}"#);
+    assert_eq!(request_count.load(Ordering::SeqCst), 1);
+
+    fs::remove_dir_all(dir).unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_expired_entry_is_revalidated() {
+    let (url, request_count) = spawn_server("\"v1\"");
+    let (cache, dir) = temp_cache("revalidate");
+
+    cache.fetch(&url, Duration::ZERO).await.unwrap();
+
+    let response = cache.fetch(&url, Duration::ZERO).await.unwrap();
+    assert_eq!(response.cache_status, CacheStatus::Revalidated);
+    assert_eq!(response.status, 200);
+    assert_eq!(response.body, br#"{"temp":21}"#);
+    assert_eq!(request_count.load(Ordering::SeqCst), 2);
+
+    fs::remove_dir_all(dir).unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_stale_entry_served_offline() {
+    let (cache, dir) = temp_cache("offline");
+
+    // Reserve a port and close it again, such that requests fail.
+    let url = {
+      let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+      format!("http://{}/data.json", listener.local_addr().unwrap())
+    };
+
+    assert!(cache.fetch(&url, Duration::ZERO).await.is_err());
+
+    // Seed the cache as if the resource was fetched while online.
+    let (meta_path, body_path) = cache.entry_paths(&url);
+    let entry = CacheEntry {
+      url: url.clone(),
+      status: 200,
+      content_type: None,
+      etag: None,
+      last_modified: None,
+      fetched_at: 0,
+      duration: 60,
+    };
+
+    fs::write(&meta_path, serde_json::to_vec(&entry).unwrap()).unwrap();
+    fs::write(&body_path, "cached").unwrap();
+
+    let response = cache.fetch(&url, Duration::ZERO).await.unwrap();
+    assert_eq!(response.cache_status, CacheStatus::Stale);
+    assert_eq!(response.body, b"cached");
+
+    fs::remove_dir_all(dir).unwrap();
+  }
+
+  #[test]
+  fn test_private_addresses() {
+    for ip in [
+      "127.0.0.1",
+      "10.0.0.1",
+      "172.16.0.1",
+      "192.168.1.1",
+      "169.254.169.254",
+      "100.64.0.1",
+      "0.0.0.0",
+      "::1",
+      "::",
+      "fd00::1",
+      "fe80::1",
+      "::ffff:127.0.0.1",
+    ] {
+      assert!(!is_public_ip(ip.parse().unwrap()), "{ip}");
+    }
+
+    for ip in ["1.1.1.1", "93.184.216.34", "2606:4700:4700::1111"] {
+      assert!(is_public_ip(ip.parse().unwrap()), "{ip}");
+    }
+
+    let private_host =
+      |url: &str| has_private_ip_host(&url.parse().unwrap());
+    assert!(private_host("http://127.0.0.1:8080/data.json"));
+    assert!(private_host("http://[::1]/data.json"));
+    assert!(private_host("http://0x7f000001/data.json"));
+    assert!(!private_host("https://example.com/data.json"));
+  }
+
+  #[tokio::test]
+  async fn test_private_address_rejected() {
+    let (url, request_count) = spawn_server("\"v1\"");
+    let dir = temp_dir("asset-cache-private");
+    let cache = AssetCache::new(dir.clone()).unwrap();
+
+    assert!(cache.fetch(&url, Duration::ZERO).await.is_err());
+    assert!(cache
+      .fetch(&url.replace("127.0.0.1", "localhost"), Duration::ZERO)
+      .await
+      .is_err());
+    assert_eq!(request_count.load(Ordering::SeqCst), 0);
+
+    fs::remove_dir_all(dir).unwrap();
+  }
+}
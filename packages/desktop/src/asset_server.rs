@@ -1,45 +1,179 @@
 use std::{
   collections::HashMap,
-  io::Cursor,
+  io::{Cursor, SeekFrom},
+  net::{Ipv4Addr, TcpStream},
   path::{Path, PathBuf},
-  sync::LazyLock,
+  sync::{
+    atomic::{AtomicU16, Ordering},
+    Arc, LazyLock,
+  },
+  time::Duration,
 };
 
+use anyhow::Context;
+use globset::GlobSet;
 use rocket::{
-  fs::NamedFile,
-  http::{ContentType, Cookie, CookieJar, Header, SameSite, Status},
+  http::{ContentType, Header, Status},
   request::{FromRequest, Outcome},
-  response::{self, Redirect, Responder, Response},
-  Request,
+  response::{self, Responder, Response},
+  serde::json::Json,
+  Request, State,
+};
+use serde::{Deserialize, Serialize};
+use tauri::{ipc::CapabilityBuilder, Manager};
+use tokio::{
+  io::{AsyncReadExt, AsyncSeekExt},
+  sync::Mutex,
+  task,
 };
-use tokio::{sync::Mutex, task};
 use uuid::Uuid;
 
-use crate::common::{glob_util, PathExt};
+use crate::{
+  asset_cache::{AssetCache, CachedResponse},
+  common::{
+    glob_util,
+    http_util::{self, ByteRange, Encoding},
+    PathExt,
+  },
+  widget_headers,
+  widget_pack::{CompiledCachingRules, WidgetConfig, WidgetPack},
+  widget_template,
+};
+
+/// Default port for the localhost asset server.
+pub const DEFAULT_ASSET_SERVER_PORT: u16 = 6124;
+
+/// Port that the asset server is bound to.
+static ASSET_SERVER_PORT: AtomicU16 =
+  AtomicU16::new(DEFAULT_ASSET_SERVER_PORT);
+
+/// Maximum number of bytes to serve for a range request. Longer ranges
+/// (e.g. `bytes=0-` for streaming media) are served in chunks.
+const MAX_RANGE_LENGTH: u64 = 8 * 1024 * 1024;
 
-/// Port for the localhost asset server.
-const ASSET_SERVER_PORT: u16 = 6124;
+/// Maximum size of files to compress (in bytes). Larger files are served
+/// uncompressed.
+const MAX_COMPRESS_LENGTH: u64 = 4 * 1024 * 1024;
 
-/// Map of tokens to their corresponding path and file patterns.
+/// Path prefix under which widgets are served. Followed by the widget's
+/// token (i.e. `/__zebar/t/<token>/`).
+const TOKEN_PATH_PREFIX: &str = "/__zebar/t";
+
+/// Map of tokens to the widget they were issued to.
 static ASSET_SERVER_TOKENS: LazyLock<Mutex<HashMap<String, TokenAccess>>> =
   LazyLock::new(|| Mutex::new(HashMap::new()));
 
 /// Access information for a given token.
 #[derive(Clone, Debug)]
 struct TokenAccess {
+  /// ID of the widget that the token was issued to.
+  widget_id: String,
+
+  /// ID of the widget's pack.
+  pack_id: String,
+
+  /// Name of the widget within its pack.
+  widget_name: String,
+
   /// Base directory for the token.
   base_dir: PathBuf,
 
   /// File patterns for accessible files.
   file_patterns: Vec<String>,
+
+  /// Compiled glob set of `file_patterns`.
+  file_glob_set: Arc<GlobSet>,
+
+  /// Caching rules for requests made via the caching proxy. Only URLs
+  /// that match a rule can be fetched.
+  caching_rules: CompiledCachingRules,
+
+  /// Context that `.html.tera` entry points are rendered with.
+  template_context: tera::Context,
+
+  /// Headers to add to every response (e.g. the widget's CSP).
+  response_headers: Vec<(String, String)>,
 }
 
-pub async fn setup_asset_server() -> anyhow::Result<()> {
+/// Grants widgets served by the asset server the permissions of the
+/// `widget` capability.
+///
+/// The asset server's port is only known once it's bound, so its origin
+/// is added at runtime rather than allowing every localhost port in the
+/// static capability.
+pub fn add_widget_capability(
+  app_handle: &tauri::AppHandle,
+  port: u16,
+) -> anyhow::Result<()> {
+  let capability = widget_permissions()?.into_iter().fold(
+    CapabilityBuilder::new("widget-asset-server")
+      .remote(format!("http://127.0.0.1:{}", port))
+      .window("widget-*")
+      .local(false),
+    |capability, permission| capability.permission(permission),
+  );
+
+  app_handle.add_capability(capability)?;
+
+  Ok(())
+}
+
+/// Gets the permissions of the static `widget` capability.
+fn widget_permissions() -> anyhow::Result<Vec<String>> {
+  let capability = serde_json::from_str::<serde_json::Value>(
+    include_str!("../capabilities/widget.json"),
+  )?;
+
+  capability["permissions"]
+    .as_array()
+    .context("Widget capability has no permissions.")?
+    .iter()
+    .map(|permission| {
+      permission
+        .as_str()
+        .map(String::from)
+        .context("Invalid widget permission.")
+    })
+    .collect()
+}
+
+/// Starts the asset server on the preferred port, or on a free port if
+/// it's already in use.
+///
+/// Returns the port that the server is bound to.
+pub async fn setup_asset_server(
+  asset_cache: AssetCache,
+  preferred_port: u16,
+) -> anyhow::Result<u16> {
+  let port = http_util::available_port(preferred_port)
+    .context("No port available for the asset server.")?;
+
+  if port != preferred_port {
+    tracing::warn!(
+      "Asset server port {} is in use. Falling back to port {}.",
+      preferred_port,
+      port
+    );
+  }
+
+  ASSET_SERVER_PORT.store(port, Ordering::Relaxed);
+
   let rocket = rocket::build()
-    .configure(
-      rocket::Config::figment().merge(("port", ASSET_SERVER_PORT)),
+    .configure(rocket::Config::figment().merge(("port", port)))
+    .manage(asset_cache)
+    .mount(
+      "/",
+      routes![
+        sw_js,
+        normalize_css,
+        whoami,
+        csp_report,
+        proxy,
+        serve,
+        serve_root_relative
+      ],
     )
-    .mount("/", routes![sw_js, normalize_css, init, serve]);
+    .register("/", catchers![default_catcher]);
 
   // Test if the server can start (this doesn't block).
   let rocket = rocket.ignite().await.map_err(|err| {
@@ -53,84 +187,233 @@ pub async fn setup_asset_server() -> anyhow::Result<()> {
     }
   });
 
-  Ok(())
+  Ok(port)
 }
 
-pub async fn create_init_url(
-  parent_dir: &Path,
-  html_path: &Path,
-  file_patterns: Vec<String>,
+/// Details of a running asset server. Saved to a file, such that it can
+/// be queried from another process.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetServerInfo {
+  pub port: u16,
+  pub origin: String,
+}
+
+impl AssetServerInfo {
+  /// Gets the details of the asset server in this process.
+  pub fn current() -> Self {
+    Self {
+      port: ASSET_SERVER_PORT.load(Ordering::Relaxed),
+      origin: origin(),
+    }
+  }
+
+  /// Whether the asset server accepts connections.
+  pub fn is_running(&self) -> bool {
+    TcpStream::connect_timeout(
+      &(Ipv4Addr::LOCALHOST, self.port).into(),
+      Duration::from_secs(1),
+    )
+    .is_ok()
+  }
+}
+
+/// Gets the origin of the asset server (e.g. `http://127.0.0.1:6124`).
+pub fn origin() -> String {
+  format!(
+    "http://127.0.0.1:{}",
+    ASSET_SERVER_PORT.load(Ordering::Relaxed)
+  )
+}
+
+/// Issues a token for a widget and returns the URL of its entry point.
+///
+/// The token is part of the URL path, such that every request from the
+/// widget is tied to it (e.g. relative URLs resolve to
+/// `/__zebar/t/<token>/...`). Any previous token of the widget is
+/// revoked.
+pub async fn create_widget_url(
+  widget_id: &str,
+  widget_pack: &WidgetPack,
+  widget_config: &WidgetConfig,
+  template_context: tera::Context,
+  csp_override: Option<&str>,
 ) -> anyhow::Result<tauri::Url> {
+  let parent_dir = &widget_pack.directory_path;
+  let html_path = parent_dir.join(&widget_config.html_path);
+
+  let file_patterns = widget_config.include_files.clone();
+
+  let file_glob_set = Arc::new(
+    glob_util::create_glob_set(&file_patterns).unwrap_or_else(|err| {
+      tracing::warn!(
+        "Invalid file patterns {:?}: {:?}",
+        file_patterns,
+        err
+      );
+      GlobSet::empty()
+    }),
+  );
+
   // Generate a unique token to identify requests from the widget to the
   // asset server.
-  let token = upsert_or_get_token(parent_dir, file_patterns).await;
+  let token = issue_token(TokenAccess {
+    widget_id: widget_id.to_string(),
+    pack_id: widget_pack.id.clone(),
+    widget_name: widget_config.name.clone(),
+    base_dir: parent_dir.clone(),
+    file_patterns,
+    file_glob_set,
+    caching_rules: widget_config.caching.compile_rules(),
+    template_context,
+    response_headers: widget_headers::response_headers(
+      &widget_pack.id,
+      &widget_pack.r#type,
+      widget_config,
+      csp_override,
+    ),
+  })
+  .await;
 
-  let redirect = format!(
-    "/{}",
-    html_path.strip_prefix(parent_dir)?.to_unicode_string()
-  );
+  let mut url = tauri::Url::parse(&origin())?;
 
-  let url = tauri::Url::parse_with_params(
-    &format!("http://127.0.0.1:{}/__zebar/init", ASSET_SERVER_PORT),
-    &[("token", &token), ("redirect", &redirect)],
-  )?;
+  url
+    .path_segments_mut()
+    .map_err(|_| anyhow::anyhow!("Invalid asset server origin."))?
+    .pop_if_empty()
+    .extend(
+      TOKEN_PATH_PREFIX
+        .split('/')
+        .filter(|segment| !segment.is_empty()),
+    )
+    .push(&token)
+    .extend(
+      html_path
+        .strip_prefix(parent_dir)?
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy()),
+    );
 
   Ok(url)
 }
 
-/// Returns an asset server token for a given directory.
-///
-/// If the directory does not have an existing token, a new one is
-/// generated and inserted.
-async fn upsert_or_get_token(
-  directory: &Path,
-  file_patterns: Vec<String>,
-) -> String {
+/// Generates a new token for a widget, replacing any existing token of
+/// the same widget.
+async fn issue_token(token_access: TokenAccess) -> String {
   let mut asset_server_tokens = ASSET_SERVER_TOKENS.lock().await;
 
-  // Find existing token for this path.
-  let found_token = asset_server_tokens
-    .iter()
-    .find(|(_, token)| token.base_dir == directory)
-    .map(|(token, _)| token.clone());
+  asset_server_tokens
+    .retain(|_, access| access.widget_id != token_access.widget_id);
 
-  if let Some(token) = found_token {
-    // Update the file patterns for the existing token.
-    if let Some(access) = asset_server_tokens.get_mut(&token) {
-      access.file_patterns = file_patterns;
-    }
+  let token = Uuid::new_v4().to_string();
+  asset_server_tokens.insert(token.clone(), token_access);
 
-    token
-  } else {
-    let new_token = Uuid::new_v4().to_string();
+  token
+}
 
-    asset_server_tokens.insert(
-      new_token.clone(),
-      TokenAccess {
-        base_dir: directory.to_path_buf(),
-        file_patterns,
-      },
-    );
+/// Revokes the token of a widget (e.g. when it's closed).
+pub async fn revoke_token(widget_id: &str) {
+  ASSET_SERVER_TOKENS
+    .lock()
+    .await
+    .retain(|_, access| access.widget_id != widget_id);
+}
 
-    new_token
-  }
+/// Gets the access information of a token, unless it has been revoked.
+async fn token_access(token: &str) -> Result<TokenAccess, Status> {
+  ASSET_SERVER_TOKENS
+    .lock()
+    .await
+    .get(token)
+    .cloned()
+    .ok_or(Status::Unauthorized)
 }
 
-#[get("/__zebar/init?<token>&<redirect>")]
-pub fn init(
-  token: String,
-  redirect: String,
-  cookies: &CookieJar<'_>,
-) -> Redirect {
-  // Create a http-only cookie with the widget's token.
-  cookies.add(
-    Cookie::build(("ZEBAR_TOKEN", token))
-      .http_only(true)
-      .same_site(SameSite::Strict)
-      .path("/"),
-  );
+/// Gets the token from the URL of a widget page, if it's served by the
+/// asset server.
+fn token_from_url(url: &str) -> Option<&str> {
+  let path = url
+    .split_once(TOKEN_PATH_PREFIX)
+    .map(|(_, path)| path)?
+    .strip_prefix('/')?;
 
-  Redirect::to(redirect)
+  path.split('/').next().filter(|token| !token.is_empty())
+}
+
+/// Returns the identity of the widget that the token in the path was
+/// issued to.
+#[get("/__zebar/t/<token>/__zebar/whoami")]
+pub async fn whoami(token: &str) -> Result<Json<WidgetIdentity>, Status> {
+  let access = token_access(token).await?;
+
+  Ok(Json(WidgetIdentity {
+    widget_id: access.widget_id,
+    pack_id: access.pack_id,
+    widget_name: access.widget_name,
+  }))
+}
+
+/// Response body of the `whoami` endpoint.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WidgetIdentity {
+  widget_id: String,
+  pack_id: String,
+  widget_name: String,
+}
+
+/// Logs Content-Security-Policy violations reported by widgets.
+///
+/// Accepts both `report-uri` (`{"csp-report": {...}}`) and Reporting API
+/// (`[{"body": {...}}]`) formats.
+#[post("/__zebar/csp-report", data = "<report>")]
+pub async fn csp_report(report: String) -> Status {
+  let reports = match serde_json::from_str::<serde_json::Value>(&report) {
+    Ok(serde_json::Value::Array(reports)) => reports
+      .into_iter()
+      .filter_map(|report| report.get("body").cloned())
+      .collect(),
+    Ok(report) => report.get("csp-report").cloned().into_iter().collect(),
+    Err(_) => Vec::new(),
+  };
+
+  if reports.is_empty() {
+    tracing::warn!("Invalid CSP report: {}", report);
+  }
+
+  for report in reports {
+    let field = |names: &[&str]| {
+      names
+        .iter()
+        .find_map(|name| {
+          report.get(*name).and_then(|value| value.as_str())
+        })
+        .unwrap_or("unknown")
+        .to_string()
+    };
+
+    let document_url = field(&["document-uri", "documentURL"]);
+
+    // Reports are only logged, so the widget is looked up from the
+    // reported document rather than requiring a token.
+    let widget_id = match token_from_url(&document_url) {
+      Some(token) => token_access(token)
+        .await
+        .map(|access| access.widget_id)
+        .unwrap_or_else(|_| "unknown".to_string()),
+      None => "unknown".to_string(),
+    };
+
+    tracing::warn!(
+      "CSP violation in widget {}: '{}' blocked {} on {}.",
+      widget_id,
+      field(&["violated-directive", "effectiveDirective"]),
+      field(&["blocked-uri", "blockedURL"]),
+      document_url,
+    );
+  }
+
+  Status::NoContent
 }
 
 #[get("/__zebar/sw.js")]
@@ -157,64 +440,697 @@ pub fn normalize_css() -> (ContentType, &'static str) {
   (ContentType::CSS, include_str!("../resources/normalize.css"))
 }
 
-#[rocket::get("/<path..>", rank = 100)]
+/// Fetches a remote resource on behalf of a widget, caching it on disk
+/// according to the widget's caching rules.
+///
+/// Only URLs that match one of the widget's caching rules can be
+/// fetched, and requests to loopback or private addresses are rejected by
+/// the cache.
+///
+/// Proxied resources are served from the asset server's origin, so they
+/// can't be loaded as scripts or documents. Otherwise, they'd count as
+/// `'self'` in the widget's CSP.
+///
+/// The response includes an `X-Zebar-Cache` header indicating whether it
+/// was served from the cache (`hit`, `miss`, `revalidated`, or `stale`).
+#[get("/__zebar/t/<token>/__zebar/proxy?<url>")]
+pub async fn proxy(
+  token: &str,
+  url: String,
+  fetch_dest: FetchDest,
+  asset_cache: &State<AssetCache>,
+) -> Result<WithHeaders<ProxyResponse>, Status> {
+  let token_access = token_access(token).await?;
+
+  if !is_proxy_dest_allowed(fetch_dest.0.as_deref()) {
+    return Err(Status::Forbidden);
+  }
+
+  let is_remote_url = tauri::Url::parse(&url)
+    .is_ok_and(|url| matches!(url.scheme(), "http" | "https"));
+
+  if !is_remote_url {
+    return Err(Status::BadRequest);
+  }
+
+  let Some(duration) = token_access.caching_rules.duration_for(&url)
+  else {
+    return Err(Status::Forbidden);
+  };
+
+  asset_cache
+    .fetch(&url, duration)
+    .await
+    .map(|response| {
+      WithHeaders(
+        ProxyResponse(response),
+        proxy_headers(&token_access.response_headers),
+      )
+    })
+    .map_err(|err| {
+      tracing::warn!("Caching proxy failed to fetch {}: {:?}", url, err);
+      Status::BadGateway
+    })
+}
+
+/// Whether a proxied resource can be loaded for the given
+/// `Sec-Fetch-Dest` (e.g. `empty` for `fetch` calls).
+///
+/// Only passive content is allowed. Requests without the header (i.e.
+/// from older webviews) are allowed as well.
+fn is_proxy_dest_allowed(fetch_dest: Option<&str>) -> bool {
+  matches!(
+    fetch_dest,
+    None | Some("empty" | "image" | "audio" | "video" | "track" | "font")
+  )
+}
+
+/// Gets the headers for a proxied response.
+///
+/// Uses the widget's response headers, but with a CSP that prevents the
+/// response from running scripts if it's opened as a document.
+fn proxy_headers(
+  response_headers: &[(String, String)],
+) -> Vec<(String, String)> {
+  let mut headers = response_headers
+    .iter()
+    .filter(|(name, _)| {
+      !name.eq_ignore_ascii_case("Content-Security-Policy")
+        && !name.eq_ignore_ascii_case("X-Content-Type-Options")
+    })
+    .cloned()
+    .collect::<Vec<_>>();
+
+  headers.extend([
+    ("X-Content-Type-Options".to_string(), "nosniff".to_string()),
+    (
+      "Content-Security-Policy".to_string(),
+      "sandbox; default-src 'none'".to_string(),
+    ),
+  ]);
+
+  headers
+}
+
+#[derive(Debug)]
+pub struct ProxyResponse(CachedResponse);
+
+#[rocket::async_trait]
+impl<'r> Responder<'r, 'static> for ProxyResponse {
+  fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
+    let mut response = Response::build();
+
+    response
+      .status(Status::from_code(self.0.status).unwrap_or(Status::Ok))
+      .header(Header::new("X-Zebar-Cache", self.0.cache_status.as_str()))
+      .sized_body(self.0.body.len(), Cursor::new(self.0.body));
+
+    if let Some(content_type) = self.0.content_type {
+      response.header(Header::new("Content-Type", content_type));
+    }
+
+    response.ok()
+  }
+}
+
+#[rocket::get("/__zebar/t/<token>/<path..>", rank = 100)]
 pub async fn serve(
+  token: &str,
   path: Option<PathBuf>,
-  token: ServerToken,
-) -> Option<NamedFile> {
-  // Retrieve access information for the corresponding token.
-  let token_access =
-    { ASSET_SERVER_TOKENS.lock().await.get(&token.0).cloned() }?;
+  headers: AssetRequestHeaders,
+) -> WithHeaders<AssetResponse> {
+  let Ok(token_access) = token_access(token).await else {
+    return WithHeaders(
+      AssetResponse::error(Status::Unauthorized, "Invalid token."),
+      Vec::new(),
+    );
+  };
 
   let relative_path = path.unwrap_or("index.html".into());
-  let absolute_path = token_access
+
+  // Widget's response headers (e.g. its CSP) apply to every response,
+  // including errors.
+  let response_headers = token_access.response_headers.clone();
+
+  // Deny access to files that don't match the widget's file patterns.
+  if !token_access.file_glob_set.is_match(&relative_path) {
+    tracing::warn!(
+      "Asset path {} is inaccessible for widget {}.",
+      relative_path.display(),
+      token_access.widget_id
+    );
+
+    return WithHeaders(
+      AssetResponse::error(Status::Forbidden, "Asset is not accessible."),
+      response_headers,
+    );
+  }
+
+  WithHeaders(
+    serve_path(&token_access, relative_path, &headers).await,
+    response_headers,
+  )
+}
+
+/// Serves root-relative asset URLs (e.g. `/main.js` from a Vite build
+/// with `base: '/'`), using the token of the widget page that requested
+/// them.
+///
+/// Requests without a widget page as their `Referer` fall through to the
+/// catcher.
+#[rocket::get("/<path..>", rank = 101)]
+pub async fn serve_root_relative(
+  path: PathBuf,
+  referer_token: RefererToken,
+  headers: AssetRequestHeaders,
+) -> WithHeaders<AssetResponse> {
+  serve(&referer_token.0, Some(path), headers).await
+}
+
+/// Serves a file that matched the file patterns of the given widget.
+async fn serve_path(
+  token_access: &TokenAccess,
+  relative_path: PathBuf,
+  headers: &AssetRequestHeaders,
+) -> AssetResponse {
+  let Ok(absolute_path) = token_access
     .base_dir
-    .join(relative_path.clone())
+    .join(&relative_path)
     .canonicalize_pretty()
-    .ok()?;
-
-  // Allow access if:
-  // - The asset path is within the base directory.
-  // - The asset path matches any of the file patterns of the widget pack.
-  if !absolute_path.starts_with(&token_access.base_dir)
-    || !glob_util::is_match(&relative_path, &token_access.file_patterns)
-      .ok()?
-  {
+  else {
+    return AssetResponse::error(Status::NotFound, "Asset not found.");
+  };
+
+  // Deny access to files outside of the base directory (e.g. via
+  // symlinks).
+  if !absolute_path.starts_with(&token_access.base_dir) {
     tracing::warn!(
-      "Asset path {} is inaccessable with token {:?}.",
+      "Asset path {} is outside of base directory {}.",
       absolute_path.display(),
-      token_access
+      token_access.base_dir.display()
     );
 
-    return None;
+    return AssetResponse::error(
+      Status::Forbidden,
+      "Asset is not accessible.",
+    );
   }
 
-  // Attempt to open and serve the requested file. Currently returns HTML
-  // `Content-Type` if not found.
-  NamedFile::open(absolute_path).await.ok()
+  if widget_template::is_template(&absolute_path) {
+    return serve_template(
+      absolute_path,
+      token_access.template_context.clone(),
+    )
+    .await;
+  }
+
+  match serve_file(&absolute_path, headers).await {
+    Ok(response) => response,
+    Err(err) => {
+      tracing::warn!(
+        "Failed to serve asset {}: {:?}",
+        absolute_path.display(),
+        err
+      );
+
+      AssetResponse::error(Status::NotFound, "Asset not found.")
+    }
+  }
+}
+
+/// Renders a `.html.tera` entry point with the widget's context.
+async fn serve_template(
+  path: PathBuf,
+  context: tera::Context,
+) -> AssetResponse {
+  let rendered =
+    task::spawn_blocking(move || widget_template::render(&path, &context))
+      .await;
+
+  match rendered {
+    Ok(Ok(html)) => AssetResponse::Template(html),
+    Ok(Err(err)) => {
+      tracing::warn!("{:?}", err);
+
+      AssetResponse::error(
+        Status::InternalServerError,
+        "Failed to render template.",
+      )
+    }
+    Err(err) => {
+      tracing::warn!("Template rendering task failed: {:?}", err);
+
+      AssetResponse::error(
+        Status::InternalServerError,
+        "Failed to render template.",
+      )
+    }
+  }
+}
+
+/// Builds the response for a file, taking conditional, range, and
+/// compression headers of the request into account.
+async fn serve_file(
+  path: &Path,
+  headers: &AssetRequestHeaders,
+) -> anyhow::Result<AssetResponse> {
+  let metadata = tokio::fs::metadata(path).await?;
+
+  if !metadata.is_file() {
+    return Ok(AssetResponse::error(Status::NotFound, "Asset not found."));
+  }
+
+  let length = metadata.len();
+  let modified = metadata.modified()?;
+  let etag = http_util::file_etag(length, modified);
+  let last_modified = httpdate::fmt_http_date(modified);
+
+  if http_util::is_not_modified(
+    headers.if_none_match.as_deref(),
+    headers.if_modified_since.as_deref(),
+    &etag,
+    modified,
+  ) {
+    return Ok(AssetResponse::NotModified {
+      etag,
+      last_modified,
+    });
+  }
+
+  let content_type = path
+    .extension()
+    .and_then(|ext| ext.to_str())
+    .and_then(ContentType::from_extension);
+
+  let mut file = tokio::fs::File::open(path).await?;
+
+  let range = http_util::parse_range(
+    headers.range.as_deref(),
+    length,
+    MAX_RANGE_LENGTH,
+  );
+
+  let (status, content_range, encoding, body) = match range {
+    ByteRange::Unsatisfiable => {
+      return Ok(AssetResponse::RangeNotSatisfiable { length });
+    }
+    ByteRange::Partial { start, end } => {
+      let mut bytes = vec![0; (end - start + 1) as usize];
+      file.seek(SeekFrom::Start(start)).await?;
+      file.read_exact(&mut bytes).await?;
+
+      (
+        Status::PartialContent,
+        Some(format!("bytes {}-{}/{}", start, end, length)),
+        None,
+        FileBody::Bytes(bytes),
+      )
+    }
+    ByteRange::Full => {
+      let encoding = content_type
+        .as_ref()
+        .filter(|content_type| is_compressible(content_type))
+        .filter(|_| length <= MAX_COMPRESS_LENGTH)
+        .and_then(|_| {
+          http_util::negotiate_encoding(headers.accept_encoding.as_deref())
+        });
+
+      match encoding {
+        Some(encoding) => {
+          let mut bytes = Vec::with_capacity(length as usize);
+          file.read_to_end(&mut bytes).await?;
+
+          let compressed =
+            task::spawn_blocking(move || encoding.compress(&bytes))
+              .await??;
+
+          (
+            Status::Ok,
+            None,
+            Some(encoding),
+            FileBody::Bytes(compressed),
+          )
+        }
+        None => (Status::Ok, None, None, FileBody::File(file)),
+      }
+    }
+  };
+
+  Ok(AssetResponse::File(Box::new(FileResponse {
+    status,
+    content_type,
+    etag,
+    last_modified,
+    content_range,
+    encoding,
+    body,
+  })))
+}
+
+/// Whether files of the given content type benefit from compression.
+fn is_compressible(content_type: &ContentType) -> bool {
+  content_type.top() == "text"
+    || [
+      "javascript",
+      "json",
+      "xml",
+      "wasm",
+      "svg+xml",
+      "manifest+json",
+    ]
+    .iter()
+    .any(|sub| content_type.sub() == *sub)
+}
+
+/// Response for an asset request.
+#[derive(Debug)]
+pub enum AssetResponse {
+  /// Error with a JSON body.
+  Error {
+    status: Status,
+    message: &'static str,
+  },
+
+  /// Client's cached copy is still valid.
+  NotModified { etag: String, last_modified: String },
+
+  /// Requested range lies outside of the file.
+  RangeNotSatisfiable { length: u64 },
+
+  /// Entire file or a range of it.
+  File(Box<FileResponse>),
+
+  /// Rendered `.html.tera` entry point.
+  Template(String),
+}
+
+#[derive(Debug)]
+pub struct FileResponse {
+  status: Status,
+  content_type: Option<ContentType>,
+  etag: String,
+  last_modified: String,
+  content_range: Option<String>,
+  encoding: Option<Encoding>,
+  body: FileBody,
+}
+
+impl AssetResponse {
+  fn error(status: Status, message: &'static str) -> Self {
+    Self::Error { status, message }
+  }
+}
+
+#[derive(Debug)]
+pub enum FileBody {
+  Bytes(Vec<u8>),
+  File(tokio::fs::File),
+}
+
+#[rocket::async_trait]
+impl<'r> Responder<'r, 'static> for AssetResponse {
+  fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
+    let mut response = Response::build();
+
+    match self {
+      AssetResponse::Error { status, message } => {
+        let body = error_body(status, message);
+
+        response
+          .status(status)
+          .header(ContentType::JSON)
+          .sized_body(body.len(), Cursor::new(body));
+      }
+      AssetResponse::NotModified {
+        etag,
+        last_modified,
+      } => {
+        response
+          .status(Status::NotModified)
+          .header(Header::new("ETag", etag))
+          .header(Header::new("Last-Modified", last_modified));
+      }
+      AssetResponse::RangeNotSatisfiable { length } => {
+        let body = error_body(
+          Status::RangeNotSatisfiable,
+          "Requested range is not satisfiable.",
+        );
+
+        response
+          .status(Status::RangeNotSatisfiable)
+          .header(ContentType::JSON)
+          .header(Header::new(
+            "Content-Range",
+            format!("bytes */{}", length),
+          ))
+          .sized_body(body.len(), Cursor::new(body));
+      }
+      AssetResponse::Template(html) => {
+        response
+          .status(Status::Ok)
+          .header(ContentType::HTML)
+          // Rendered output depends on the widget's placement, so it
+          // shouldn't be reused by other widgets.
+          .header(Header::new("Cache-Control", "no-store"))
+          .sized_body(html.len(), Cursor::new(html));
+      }
+      AssetResponse::File(file_response) => {
+        let FileResponse {
+          status,
+          content_type,
+          etag,
+          last_modified,
+          content_range,
+          encoding,
+          body,
+        } = *file_response;
+
+        response
+          .status(status)
+          .header(Header::new("ETag", etag))
+          .header(Header::new("Last-Modified", last_modified))
+          .header(Header::new("Accept-Ranges", "bytes"))
+          .header(Header::new("Vary", "Accept-Encoding"))
+          // Always revalidate, such that changes to widget files show up
+          // right away.
+          .header(Header::new("Cache-Control", "no-cache"));
+
+        if let Some(content_type) = content_type {
+          response.header(content_type);
+        }
+
+        if let Some(content_range) = content_range {
+          response.header(Header::new("Content-Range", content_range));
+        }
+
+        if let Some(encoding) = encoding {
+          response
+            .header(Header::new("Content-Encoding", encoding.as_str()));
+        }
+
+        match body {
+          FileBody::Bytes(bytes) => {
+            response.sized_body(bytes.len(), Cursor::new(bytes))
+          }
+          FileBody::File(file) => response.sized_body(None, file),
+        };
+      }
+    }
+
+    response.ok()
+  }
 }
 
-/// Token for identifying which directory is being accessed.
+/// Response with additional headers.
 #[derive(Debug)]
-pub struct ServerToken(pub String);
+pub struct WithHeaders<R>(R, Vec<(String, String)>);
 
 #[rocket::async_trait]
-impl<'r> FromRequest<'r> for ServerToken {
-  type Error = anyhow::Error;
+impl<'r, R: Responder<'r, 'static>> Responder<'r, 'static>
+  for WithHeaders<R>
+{
+  fn respond_to(
+    self,
+    request: &'r Request<'_>,
+  ) -> response::Result<'static> {
+    let mut response = self.0.respond_to(request)?;
+
+    for (name, value) in self.1 {
+      response.set_raw_header(name, value);
+    }
+
+    Ok(response)
+  }
+}
+
+/// Catches errors that aren't handled by a route (e.g. a missing token)
+/// and responds with a JSON body.
+#[catch(default)]
+pub fn default_catcher(status: Status, _: &Request) -> AssetResponse {
+  AssetResponse::error(status, status.reason().unwrap_or("Unknown error."))
+}
+
+/// Gets the JSON body for an error response.
+fn error_body(status: Status, message: &str) -> String {
+  serde_json::json!({ "status": status.code, "error": message })
+    .to_string()
+}
+
+/// Request headers that affect how assets are served.
+#[derive(Debug)]
+pub struct AssetRequestHeaders {
+  if_none_match: Option<String>,
+  if_modified_since: Option<String>,
+  range: Option<String>,
+  accept_encoding: Option<String>,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AssetRequestHeaders {
+  type Error = ();
 
   async fn from_request(
     request: &'r Request<'_>,
   ) -> Outcome<Self, Self::Error> {
-    let token = request.cookies().get("ZEBAR_TOKEN");
+    let header = |name| request.headers().get_one(name).map(String::from);
 
-    match token {
-      Some(token) => {
-        Outcome::Success(ServerToken(token.value_trimmed().to_string()))
-      }
-      None => Outcome::Error((
-        Status::Unauthorized,
-        anyhow::anyhow!("Missing token for accessing directory."),
-      )),
+    Outcome::Success(AssetRequestHeaders {
+      if_none_match: header("If-None-Match"),
+      if_modified_since: header("If-Modified-Since"),
+      range: header("Range"),
+      accept_encoding: header("Accept-Encoding"),
+    })
+  }
+}
+
+/// Value of the `Sec-Fetch-Dest` request header, i.e. how the requested
+/// resource is going to be used (e.g. `script` or `image`).
+#[derive(Debug)]
+pub struct FetchDest(Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for FetchDest {
+  type Error = ();
+
+  async fn from_request(
+    request: &'r Request<'_>,
+  ) -> Outcome<Self, Self::Error> {
+    Outcome::Success(FetchDest(
+      request
+        .headers()
+        .get_one("Sec-Fetch-Dest")
+        .map(|dest| dest.to_ascii_lowercase()),
+    ))
+  }
+}
+
+/// Token of the widget page that a request was made from, based on the
+/// `Referer` request header.
+#[derive(Debug)]
+pub struct RefererToken(String);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for RefererToken {
+  type Error = ();
+
+  async fn from_request(
+    request: &'r Request<'_>,
+  ) -> Outcome<Self, Self::Error> {
+    match request
+      .headers()
+      .get_one("Referer")
+      .and_then(token_from_url)
+    {
+      Some(token) => Outcome::Success(RefererToken(token.to_string())),
+      None => Outcome::Forward(Status::NotFound),
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::widget_pack::{WidgetCaching, WidgetCachingRule};
+
+  #[test]
+  fn test_token_from_url() {
+    assert_eq!(
+      token_from_url("http://127.0.0.1:6124/__zebar/t/abc/index.html"),
+      Some("abc")
+    );
+    assert_eq!(
+      token_from_url("http://127.0.0.1:6124/__zebar/t/abc"),
+      Some("abc")
+    );
+    assert_eq!(token_from_url("http://127.0.0.1:6124/__zebar/t/"), None);
+    assert_eq!(token_from_url("http://127.0.0.1:6124/index.html"), None);
+  }
+
+  #[test]
+  fn test_caching_rules() {
+    let caching = WidgetCaching {
+      default_duration: 60,
+      rules: vec![
+        WidgetCachingRule {
+          url_regex: "^https://api\\.example\\.com/".into(),
+          duration: 300,
+        },
+        WidgetCachingRule {
+          url_regex: "(invalid".into(),
+          duration: 600,
+        },
+      ],
+    };
+
+    let rules = caching.compile_rules();
+
+    assert_eq!(
+      rules.duration_for("https://api.example.com/weather"),
+      Some(Duration::from_secs(300))
+    );
+    assert_eq!(rules.duration_for("https://example.com/weather"), None);
+    assert_eq!(rules.duration_for("http://127.0.0.1/"), None);
+  }
+
+  #[test]
+  fn test_proxy_restrictions() {
+    assert!(is_proxy_dest_allowed(None));
+    assert!(is_proxy_dest_allowed(Some("empty")));
+    assert!(is_proxy_dest_allowed(Some("image")));
+    assert!(!is_proxy_dest_allowed(Some("script")));
+    assert!(!is_proxy_dest_allowed(Some("document")));
+    assert!(!is_proxy_dest_allowed(Some("iframe")));
+    assert!(!is_proxy_dest_allowed(Some("worker")));
+
+    let headers = proxy_headers(&[
+      (
+        "Content-Security-Policy".to_string(),
+        "script-src 'self'".to_string(),
+      ),
+      ("Permissions-Policy".to_string(), "camera=()".to_string()),
+    ]);
+
+    assert_eq!(
+      headers,
+      [
+        ("Permissions-Policy".to_string(), "camera=()".to_string()),
+        ("X-Content-Type-Options".to_string(), "nosniff".to_string()),
+        (
+          "Content-Security-Policy".to_string(),
+          "sandbox; default-src 'none'".to_string()
+        ),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_widget_permissions() {
+    let permissions = widget_permissions().unwrap();
+    assert!(permissions.contains(&"core:event:default".to_string()));
+
+    // The asset server's origin is only allowed at runtime.
+    assert!(
+      !include_str!("../capabilities/widget.json").contains("127.0.0.1")
+    );
+  }
+}
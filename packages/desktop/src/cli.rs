@@ -1,11 +1,13 @@
 use std::{path::PathBuf, process};
 
-use clap::{Args, Parser, Subcommand, ValueEnum};
+use clap::{
+  builder::TypedValueParser, Args, Parser, Subcommand, ValueEnum,
+};
 use tracing::Level;
 
 use crate::{
   app_settings::VERSION_NUMBER, common::LengthValue,
-  widget_pack::AnchorPoint,
+  marketplace_installer::DEFAULT_KEEP_VERSIONS, widget_pack::AnchorPoint,
 };
 
 #[derive(Clone, Debug, Parser)]
@@ -13,12 +15,23 @@ use crate::{
 pub struct Cli {
   #[command(subcommand)]
   command: Option<CliCommand>,
+
+  /// Preferred port for the localhost asset server.
+  ///
+  /// Overrides `assetServerPort` in the settings file. Falls back to a
+  /// free port if it's already in use.
+  #[clap(long, global = true, env = "ZEBAR_ASSET_SERVER_PORT")]
+  asset_server_port: Option<u16>,
 }
 
 impl Cli {
   pub fn command(&self) -> CliCommand {
     self.command.clone().unwrap_or(CliCommand::Empty)
   }
+
+  pub fn asset_server_port(&self) -> Option<u16> {
+    self.asset_server_port
+  }
 }
 
 #[derive(Clone, Debug, PartialEq, Subcommand)]
@@ -47,6 +60,18 @@ pub enum CliCommand {
   /// Publishes a widget pack to the Zebar marketplace.
   Publish(PublishArgs),
 
+  /// Installs a widget pack from a tarball at a local path or URL.
+  ///
+  /// Tarballs can be created with `zebar export`.
+  Install(InstallArgs),
+
+  /// Exports an installed or local widget pack to a tarball.
+  Export(ExportArgs),
+
+  /// Manages downloaded versions of marketplace widget packs.
+  #[clap(subcommand)]
+  Marketplace(MarketplaceArgs),
+
   /// Used when Zebar is launched with no arguments.
   ///
   /// If Zebar is already running, this command will no-op, otherwise it
@@ -187,6 +212,15 @@ impl From<LogLevel> for Level {
 pub enum QueryArgs {
   /// Outputs available monitors.
   Monitors,
+
+  /// Outputs the port and origin of the running asset server.
+  AssetServer {
+    /// Config directory of the running Zebar instance.
+    ///
+    /// The default path is `%userprofile%/.glzr/zebar/`
+    #[clap(long, value_hint = clap::ValueHint::FilePath)]
+    config_dir: Option<PathBuf>,
+  },
 }
 
 #[derive(Args, Clone, Debug, PartialEq)]
@@ -236,6 +270,93 @@ pub struct PublishArgs {
   pub api_url: String,
 }
 
+#[derive(Args, Clone, Debug, PartialEq)]
+pub struct InstallArgs {
+  /// Path or URL of the tarball (`.tar.gz`).
+  pub source: String,
+
+  /// ID to install the widget pack as (optional).
+  ///
+  /// Defaults to the name in the pack's `zpack.json`.
+  #[clap(long = "pack")]
+  pub pack_id: Option<String>,
+
+  /// Expected SHA-256 digest of the tarball (optional).
+  ///
+  /// The digest is output by `zebar export`.
+  #[clap(long)]
+  pub sha256: Option<String>,
+
+  /// Absolute or relative path to the Zebar config directory.
+  ///
+  /// The default path is `%userprofile%/.glzr/zebar/`
+  #[clap(long, value_hint = clap::ValueHint::FilePath)]
+  pub config_dir: Option<PathBuf>,
+}
+
+#[derive(Args, Clone, Debug, PartialEq)]
+pub struct ExportArgs {
+  /// ID of the widget pack to export.
+  #[clap(long = "pack")]
+  pub pack_id: String,
+
+  /// Path to write the tarball to (e.g. `./my-pack.tar.gz`).
+  #[clap(value_hint = clap::ValueHint::FilePath)]
+  pub output: PathBuf,
+
+  /// Absolute or relative path to the Zebar config directory.
+  ///
+  /// The default path is `%userprofile%/.glzr/zebar/`
+  #[clap(long, value_hint = clap::ValueHint::FilePath)]
+  pub config_dir: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Parser, PartialEq)]
+pub enum MarketplaceArgs {
+  /// Switches an installed pack back to a previously downloaded version.
+  Rollback {
+    /// ID of the widget pack to roll back.
+    #[clap(long = "pack")]
+    pack_id: String,
+
+    /// Version to roll back to (optional).
+    ///
+    /// Defaults to the newest downloaded version that's older than the
+    /// installed one.
+    #[clap(long)]
+    version: Option<String>,
+
+    /// Absolute or relative path to the Zebar config directory.
+    ///
+    /// The default path is `%userprofile%/.glzr/zebar/`
+    #[clap(long, value_hint = clap::ValueHint::FilePath)]
+    config_dir: Option<PathBuf>,
+  },
+
+  /// Removes old downloaded versions of widget packs.
+  ///
+  /// The installed version of each pack is always kept.
+  Gc {
+    /// Number of versions to keep per pack.
+    #[clap(
+      long,
+      default_value_t = DEFAULT_KEEP_VERSIONS,
+      value_parser = clap::value_parser!(u32).range(1..).map(|keep| keep as usize),
+    )]
+    keep: usize,
+
+    /// Lists the downloads that would be removed without removing them.
+    #[clap(long)]
+    dry_run: bool,
+
+    /// Absolute or relative path to the Zebar config directory.
+    ///
+    /// The default path is `%userprofile%/.glzr/zebar/`
+    #[clap(long, value_hint = clap::ValueHint::FilePath)]
+    config_dir: Option<PathBuf>,
+  },
+}
+
 /// Prints to stdout/stderror and exits the process.
 pub fn print_and_exit(output: anyhow::Result<String>) {
   match output {
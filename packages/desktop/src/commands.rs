@@ -1,13 +1,19 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
-use tauri::{State, Window};
+use tauri::{ipc::Channel, State, Window};
 
 #[cfg(target_os = "macos")]
 use crate::common::macos::WindowExtMacOs;
 #[cfg(target_os = "windows")]
 use crate::common::windows::WindowExtWindows;
 use crate::{
-  marketplace_installer::MarketplaceInstaller,
+  marketplace_installer::{
+    GcReport, MarketplaceInstaller, DEFAULT_KEEP_VERSIONS,
+  },
+  marketplace_updater::{MarketplaceUpdater, PackUpdate},
+  message_bus::{BusMessage, MessageBus},
+  pack_archive::PackIntegrity,
+  popup_placement::AnchorTarget,
   providers::{
     ProviderConfig, ProviderFunction, ProviderFunctionResponse,
     ProviderManager,
@@ -18,6 +24,7 @@ use crate::{
     CreateWidgetConfigArgs, CreateWidgetPackArgs, UpdateWidgetPackArgs,
     WidgetConfig, WidgetPack, WidgetPackManager, WidgetPlacement,
   },
+  widget_storage::{StorageChange, WidgetStorage},
 };
 
 #[tauri::command]
@@ -46,16 +53,20 @@ pub async fn start_widget(
   pack_id: String,
   widget_name: String,
   placement: WidgetPlacement,
+  anchor_target: Option<AnchorTarget>,
   is_preview: bool,
   widget_factory: State<'_, Arc<WidgetFactory>>,
 ) -> anyhow::Result<(), String> {
+  let open_options = match anchor_target {
+    Some(anchor_target) => WidgetOpenOptions::Popup {
+      placement,
+      anchor_target,
+    },
+    None => WidgetOpenOptions::Standalone(placement),
+  };
+
   widget_factory
-    .start_widget_by_id(
-      &pack_id,
-      &widget_name,
-      &WidgetOpenOptions::Standalone(placement),
-      is_preview,
-    )
+    .start_widget_by_id(&pack_id, &widget_name, &open_options, is_preview)
     .await
     .map_err(|err| err.to_string())
 }
@@ -92,6 +103,28 @@ pub async fn stop_widget_preset(
     .map_err(|err| err.to_string())
 }
 
+#[tauri::command]
+pub async fn show_widget(
+  widget_id: String,
+  widget_factory: State<'_, Arc<WidgetFactory>>,
+) -> anyhow::Result<(), String> {
+  widget_factory
+    .show_by_id(&widget_id)
+    .await
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn hide_widget(
+  widget_id: String,
+  widget_factory: State<'_, Arc<WidgetFactory>>,
+) -> anyhow::Result<(), String> {
+  widget_factory
+    .hide_by_id(&widget_id)
+    .await
+    .map_err(|err| err.to_string())
+}
+
 #[tauri::command]
 pub async fn create_widget_pack(
   args: CreateWidgetPackArgs,
@@ -202,11 +235,130 @@ pub async fn install_widget_pack(
   pack_id: String,
   version: String,
   tarball_url: String,
+  integrity: Option<PackIntegrity>,
   is_preview: bool,
   marketplace_manager: State<'_, Arc<MarketplaceInstaller>>,
 ) -> anyhow::Result<WidgetPack, String> {
   marketplace_manager
-    .install(&pack_id, &version, &tarball_url, is_preview)
+    .install(
+      &pack_id,
+      &version,
+      &tarball_url,
+      &integrity.unwrap_or_default(),
+      is_preview,
+    )
+    .await
+    .map_err(|err| err.to_string())
+}
+
+/// Installs a widget pack from a local or remote tarball.
+///
+/// Only available to the settings window, since the archive is not
+/// verified against the marketplace.
+#[tauri::command]
+pub async fn install_widget_pack_archive(
+  source: String,
+  pack_id: Option<String>,
+  sha256: Option<String>,
+  window: Window,
+  marketplace_manager: State<'_, Arc<MarketplaceInstaller>>,
+) -> anyhow::Result<WidgetPack, String> {
+  ensure_settings_window(&window)?;
+
+  marketplace_manager
+    .install_from_archive(
+      &source,
+      pack_id.as_deref(),
+      &PackIntegrity {
+        sha256,
+        ..Default::default()
+      },
+    )
+    .await
+    .map_err(|err| err.to_string())
+}
+
+/// Exports a widget pack to a tarball at the given path.
+///
+/// Only available to the settings window, since it writes to an arbitrary
+/// path.
+#[tauri::command]
+pub async fn export_widget_pack(
+  pack_id: String,
+  output_path: PathBuf,
+  window: Window,
+  widget_pack_manager: State<'_, Arc<WidgetPackManager>>,
+) -> anyhow::Result<String, String> {
+  ensure_settings_window(&window)?;
+
+  let pack = widget_pack_manager
+    .widget_pack_by_id(&pack_id)
+    .await
+    .ok_or(format!("No widget pack found for '{}'.", pack_id))?;
+
+  crate::publish::export_widget_pack(&pack, &output_path)
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn rollback_widget_pack(
+  pack_id: String,
+  version: Option<String>,
+  window: Window,
+  marketplace_manager: State<'_, Arc<MarketplaceInstaller>>,
+) -> anyhow::Result<WidgetPack, String> {
+  ensure_settings_window(&window)?;
+
+  marketplace_manager
+    .rollback(&pack_id, version.as_deref())
+    .await
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn collect_pack_garbage(
+  keep: Option<usize>,
+  dry_run: bool,
+  window: Window,
+  marketplace_manager: State<'_, Arc<MarketplaceInstaller>>,
+) -> anyhow::Result<GcReport, String> {
+  ensure_settings_window(&window)?;
+
+  marketplace_manager
+    .collect_garbage(keep.unwrap_or(DEFAULT_KEEP_VERSIONS).max(1), dry_run)
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn pack_updates(
+  marketplace_updater: State<'_, Arc<MarketplaceUpdater>>,
+) -> anyhow::Result<Vec<PackUpdate>, String> {
+  Ok(marketplace_updater.available_updates().await)
+}
+
+#[tauri::command]
+pub async fn check_pack_updates(
+  window: Window,
+  marketplace_updater: State<'_, Arc<MarketplaceUpdater>>,
+) -> anyhow::Result<Vec<PackUpdate>, String> {
+  ensure_settings_window(&window)?;
+
+  marketplace_updater
+    .check()
+    .await
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn install_pack_update(
+  pack_id: String,
+  window: Window,
+  marketplace_updater: State<'_, Arc<MarketplaceUpdater>>,
+) -> anyhow::Result<WidgetPack, String> {
+  ensure_settings_window(&window)?;
+
+  marketplace_updater
+    .install(&pack_id)
     .await
     .map_err(|err| err.to_string())
 }
@@ -318,3 +470,107 @@ pub async fn shell_kill(
 ) -> anyhow::Result<(), String> {
   shell_state.kill(pid).map_err(|err| err.to_string())
 }
+
+#[tauri::command]
+pub async fn publish(
+  topic: String,
+  payload: serde_json::Value,
+  retain: bool,
+  window: Window,
+  message_bus: State<'_, Arc<MessageBus>>,
+) -> anyhow::Result<(), String> {
+  message_bus
+    .publish(window.label(), &topic, payload, retain)
+    .await
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn subscribe(
+  topic: String,
+  on_message: Channel<BusMessage>,
+  window: Window,
+  message_bus: State<'_, Arc<MessageBus>>,
+) -> anyhow::Result<(), String> {
+  message_bus
+    .subscribe(window.label(), &topic, on_message)
+    .await
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn unsubscribe(
+  topic: String,
+  channel_id: u32,
+  window: Window,
+  message_bus: State<'_, Arc<MessageBus>>,
+) -> anyhow::Result<(), String> {
+  message_bus
+    .unsubscribe(window.label(), &topic, channel_id)
+    .await
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn storage_get(
+  key: String,
+  window: Window,
+  widget_storage: State<'_, Arc<WidgetStorage>>,
+) -> anyhow::Result<Option<serde_json::Value>, String> {
+  widget_storage
+    .get(window.label(), &key)
+    .await
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn storage_set(
+  key: String,
+  value: serde_json::Value,
+  window: Window,
+  widget_storage: State<'_, Arc<WidgetStorage>>,
+) -> anyhow::Result<(), String> {
+  widget_storage
+    .update(window.label(), &key, Some(value))
+    .await
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn storage_delete(
+  key: String,
+  window: Window,
+  widget_storage: State<'_, Arc<WidgetStorage>>,
+) -> anyhow::Result<(), String> {
+  widget_storage
+    .update(window.label(), &key, None)
+    .await
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn storage_watch(
+  on_change: Channel<StorageChange>,
+  window: Window,
+  widget_storage: State<'_, Arc<WidgetStorage>>,
+) -> anyhow::Result<(), String> {
+  widget_storage
+    .watch(window.label(), on_change)
+    .await
+    .map_err(|err| err.to_string())
+}
+
+/// Errors unless the command was invoked from the settings window.
+///
+/// Used for commands that manage installed widget packs, which widgets
+/// shouldn't have access to.
+fn ensure_settings_window(window: &Window) -> Result<(), String> {
+  match window.label() {
+    "settings" => Ok(()),
+    label => Err(format!(
+      "Command is only available to the settings window (called from \
+       '{}').",
+      label
+    )),
+  }
+}
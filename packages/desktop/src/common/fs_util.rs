@@ -20,6 +20,28 @@ pub fn read_and_parse_json<T: DeserializeOwned>(
   })
 }
 
+/// Writes to a file atomically, such that the file either contains the
+/// previous or the new contents if the write is interrupted.
+///
+/// Contents are written to a temporary file in the same directory, which
+/// then replaces the destination file.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+  let mut temp_name = path
+    .file_name()
+    .context("Path has no file name.")?
+    .to_os_string();
+
+  temp_name.push(".tmp");
+  let temp_path = path.with_file_name(temp_name);
+
+  fs::write(&temp_path, contents).with_context(|| {
+    format!("Failed to write file: {}", temp_path.display())
+  })?;
+
+  fs::rename(&temp_path, path)
+    .with_context(|| format!("Failed to replace file: {}", path.display()))
+}
+
 /// Returns whether the path has the given extension.
 pub fn has_extension(path: &Path, extension: &str) -> bool {
   path
@@ -7,7 +7,7 @@ use anyhow::Result;
 use globset::{Glob, GlobSet, GlobSetBuilder};
 
 /// Creates a `GlobSet` from a collection of glob patterns.
-fn create_glob_set(patterns: &[String]) -> Result<GlobSet> {
+pub fn create_glob_set(patterns: &[String]) -> Result<GlobSet> {
   let mut builder = GlobSetBuilder::new();
 
   for pattern in patterns {
@@ -17,19 +17,6 @@ fn create_glob_set(patterns: &[String]) -> Result<GlobSet> {
   Ok(builder.build()?)
 }
 
-/// Checks if a path matches any of the provided glob patterns.
-///
-/// Returns `true` if the path matches any of the patterns, otherwise
-/// returns `false`.
-pub fn is_match(path: &Path, patterns: &[String]) -> Result<bool> {
-  if patterns.is_empty() {
-    return Ok(false);
-  }
-
-  let glob_set = create_glob_set(patterns)?;
-  Ok(glob_set.is_match(path))
-}
-
 /// Collects all file paths in a directory that match the given glob
 /// patterns.
 ///
@@ -0,0 +1,326 @@
+use std::{
+  io::Write,
+  net::{Ipv4Addr, TcpListener},
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Byte range of a file to serve.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ByteRange {
+  /// The entire file.
+  Full,
+
+  /// Inclusive range of bytes.
+  Partial { start: u64, end: u64 },
+
+  /// Range that lies outside of the file.
+  Unsatisfiable,
+}
+
+/// Parses a `Range` header for a file of the given length.
+///
+/// Only single ranges are supported. Requests for multiple ranges, or
+/// with an invalid header, are served the entire file. Ranges are capped
+/// to `max_length` bytes, which results in a shorter partial response.
+pub fn parse_range(
+  header: Option<&str>,
+  file_length: u64,
+  max_length: u64,
+) -> ByteRange {
+  let Some(spec) = header.and_then(|header| header.strip_prefix("bytes="))
+  else {
+    return ByteRange::Full;
+  };
+
+  if spec.contains(',') {
+    return ByteRange::Full;
+  }
+
+  let Some((start, end)) = spec.trim().split_once('-') else {
+    return ByteRange::Full;
+  };
+
+  let (start, end) = match (start.parse::<u64>(), end.parse::<u64>()) {
+    // E.g. `bytes=100-199`.
+    (Ok(start), Ok(end)) if start <= end => (start, end),
+    // E.g. `bytes=100-`.
+    (Ok(start), Err(_)) if end.is_empty() => {
+      (start, file_length.saturating_sub(1))
+    }
+    // E.g. `bytes=-100` for the last 100 bytes.
+    (Err(_), Ok(suffix)) if start.is_empty() => {
+      if suffix == 0 {
+        return ByteRange::Unsatisfiable;
+      }
+
+      (
+        file_length.saturating_sub(suffix),
+        file_length.saturating_sub(1),
+      )
+    }
+    _ => return ByteRange::Full,
+  };
+
+  if start >= file_length {
+    return ByteRange::Unsatisfiable;
+  }
+
+  let end = end
+    .min(file_length - 1)
+    .min(start.saturating_add(max_length.max(1) - 1));
+
+  ByteRange::Partial { start, end }
+}
+
+/// Content encoding to compress responses with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Encoding {
+  Brotli,
+  Gzip,
+}
+
+impl Encoding {
+  /// Value for the `Content-Encoding` header.
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Encoding::Brotli => "br",
+      Encoding::Gzip => "gzip",
+    }
+  }
+
+  /// Compresses the given bytes.
+  pub fn compress(&self, bytes: &[u8]) -> anyhow::Result<Vec<u8>> {
+    match self {
+      Encoding::Brotli => {
+        let mut output = Vec::new();
+
+        {
+          let mut writer =
+            brotli::CompressorWriter::new(&mut output, 4096, 5, 22);
+          writer.write_all(bytes)?;
+        }
+
+        Ok(output)
+      }
+      Encoding::Gzip => {
+        let mut encoder = flate2::write::GzEncoder::new(
+          Vec::new(),
+          flate2::Compression::default(),
+        );
+
+        encoder.write_all(bytes)?;
+        Ok(encoder.finish()?)
+      }
+    }
+  }
+}
+
+/// Picks an encoding based on the `Accept-Encoding` header.
+///
+/// Brotli is preferred over gzip, unless gzip has a higher quality
+/// value. Encodings with `q=0` are never picked.
+pub fn negotiate_encoding(header: Option<&str>) -> Option<Encoding> {
+  let mut best: Option<(Encoding, f32)> = None;
+
+  for item in header?.split(',') {
+    let mut parts = item.split(';').map(str::trim);
+    let name = parts.next().unwrap_or_default();
+
+    let quality = parts
+      .find_map(|param| param.strip_prefix("q="))
+      .and_then(|q| q.parse::<f32>().ok())
+      .unwrap_or(1.0);
+
+    let encoding = match name.to_ascii_lowercase().as_str() {
+      "br" => Encoding::Brotli,
+      "gzip" => Encoding::Gzip,
+      _ => continue,
+    };
+
+    let is_better = best.is_none_or(|(best_encoding, best_quality)| {
+      quality > best_quality
+        || (quality == best_quality
+          && encoding == Encoding::Brotli
+          && best_encoding == Encoding::Gzip)
+    });
+
+    if quality > 0.0 && is_better {
+      best = Some((encoding, quality));
+    }
+  }
+
+  best.map(|(encoding, _)| encoding)
+}
+
+/// Gets a weak ETag for a file from its length and modification time.
+pub fn file_etag(length: u64, modified: SystemTime) -> String {
+  let modified = modified
+    .duration_since(UNIX_EPOCH)
+    .map(|duration| duration.as_nanos())
+    .unwrap_or_default();
+
+  format!("W/\"{:x}-{:x}\"", length, modified)
+}
+
+/// Whether the client's cached copy is still valid, based on the
+/// `If-None-Match` and `If-Modified-Since` headers.
+///
+/// `If-Modified-Since` is ignored if `If-None-Match` is present.
+pub fn is_not_modified(
+  if_none_match: Option<&str>,
+  if_modified_since: Option<&str>,
+  etag: &str,
+  modified: SystemTime,
+) -> bool {
+  if let Some(if_none_match) = if_none_match {
+    let etag = etag.trim_start_matches("W/");
+
+    return if_none_match
+      .split(',')
+      .map(str::trim)
+      .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag);
+  }
+
+  let Some(since) = if_modified_since
+    .and_then(|date| httpdate::parse_http_date(date).ok())
+  else {
+    return false;
+  };
+
+  // HTTP dates only have second precision.
+  let modified_secs = modified
+    .duration_since(UNIX_EPOCH)
+    .map(|duration| duration.as_secs())
+    .unwrap_or_default();
+
+  let since_secs = since
+    .duration_since(UNIX_EPOCH)
+    .map(|duration| duration.as_secs())
+    .unwrap_or_default();
+
+  modified_secs <= since_secs
+}
+
+/// Gets a port on localhost that can be bound to, preferring the given
+/// port and otherwise falling back to one picked by the OS.
+///
+/// The port is only checked, not reserved, so another process could
+/// still bind to it in the meantime.
+pub fn available_port(preferred_port: u16) -> std::io::Result<u16> {
+  let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, preferred_port))
+    .or_else(|_| TcpListener::bind((Ipv4Addr::LOCALHOST, 0)))?;
+
+  Ok(listener.local_addr()?.port())
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use super::*;
+
+  #[test]
+  fn test_parse_range() {
+    let range = |header| parse_range(Some(header), 1000, 500);
+
+    assert_eq!(
+      range("bytes=0-99"),
+      ByteRange::Partial { start: 0, end: 99 }
+    );
+    assert_eq!(
+      range("bytes=900-"),
+      ByteRange::Partial {
+        start: 900,
+        end: 999
+      }
+    );
+    assert_eq!(
+      range("bytes=-100"),
+      ByteRange::Partial {
+        start: 900,
+        end: 999
+      }
+    );
+
+    // Capped to the end of the file and to the max length.
+    assert_eq!(
+      range("bytes=950-2000"),
+      ByteRange::Partial {
+        start: 950,
+        end: 999
+      }
+    );
+    assert_eq!(
+      range("bytes=0-"),
+      ByteRange::Partial { start: 0, end: 499 }
+    );
+
+    assert_eq!(range("bytes=1000-"), ByteRange::Unsatisfiable);
+    assert_eq!(range("bytes=0-10,20-30"), ByteRange::Full);
+    assert_eq!(range("items=0-10"), ByteRange::Full);
+    assert_eq!(parse_range(None, 1000, 500), ByteRange::Full);
+  }
+
+  #[test]
+  fn test_negotiate_encoding() {
+    assert_eq!(
+      negotiate_encoding(Some("gzip, deflate, br")),
+      Some(Encoding::Brotli)
+    );
+    assert_eq!(
+      negotiate_encoding(Some("br;q=0.5, gzip")),
+      Some(Encoding::Gzip)
+    );
+    assert_eq!(negotiate_encoding(Some("br;q=0, deflate")), None);
+    assert_eq!(negotiate_encoding(None), None);
+  }
+
+  #[test]
+  fn test_compress() {
+    let input = "body { margin: 0; }\n".repeat(100);
+
+    for encoding in [Encoding::Brotli, Encoding::Gzip] {
+      let output = encoding.compress(input.as_bytes()).unwrap();
+      assert!(output.len() < input.len());
+    }
+  }
+
+  #[test]
+  fn test_is_not_modified() {
+    let modified = UNIX_EPOCH + Duration::from_millis(1_700_000_000_500);
+    let etag = file_etag(42, modified);
+    let date = httpdate::fmt_http_date(modified);
+
+    assert!(is_not_modified(Some(&etag), None, &etag, modified));
+    assert!(is_not_modified(Some("\"a\", *"), None, &etag, modified));
+    assert!(!is_not_modified(
+      Some("\"a\""),
+      Some(&date),
+      &etag,
+      modified
+    ));
+
+    // Sub-second precision of the modification time is ignored.
+    assert!(is_not_modified(None, Some(&date), &etag, modified));
+    assert!(!is_not_modified(
+      None,
+      Some(&date),
+      &etag,
+      modified + Duration::from_secs(1)
+    ));
+    assert!(!is_not_modified(None, None, &etag, modified));
+  }
+
+  #[test]
+  fn test_available_port() {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let taken_port = listener.local_addr().unwrap().port();
+
+    let port = available_port(taken_port).unwrap();
+    assert_ne!(port, taken_port);
+
+    // Port is free again once the listener is dropped.
+    drop(listener);
+    assert_eq!(available_port(taken_port).unwrap(), taken_port);
+  }
+}
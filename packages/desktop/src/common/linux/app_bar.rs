@@ -0,0 +1,171 @@
+use std::ffi::c_ulong;
+
+use anyhow::{bail, Context};
+use gtk::{gdk, prelude::*};
+use gtk_layer_shell::{Edge, Layer, LayerShell};
+use tauri::{PhysicalPosition, PhysicalSize};
+use tracing::info;
+
+use super::strut;
+use crate::widget_pack::DockEdge;
+
+/// Prepares the window for reserving screen space.
+///
+/// On Wayland, this turns the window into a wlr-layer-shell surface,
+/// which is only possible before the window is first realized (i.e.
+/// shown). No-op on X11.
+///
+/// Must be called from the main thread.
+pub fn prepare_app_bar(
+  window: &gtk::ApplicationWindow,
+) -> anyhow::Result<()> {
+  let is_x11 = gdk::Display::default()
+    .context("No default GDK display.")?
+    .is::<gdkx11::X11Display>();
+
+  if is_x11 || window.is_layer_window() {
+    return Ok(());
+  }
+
+  if window.is_realized() {
+    bail!("Window must be prepared as an app bar before it's shown.");
+  }
+
+  anyhow::ensure!(
+    gtk_layer_shell::is_supported(),
+    "Compositor does not support wlr-layer-shell."
+  );
+
+  window.init_layer_shell();
+  window.set_layer(Layer::Top);
+  window.set_namespace("zebar");
+
+  Ok(())
+}
+
+/// Reserves screen space for the given window.
+///
+/// Uses EWMH struts on X11 and a wlr-layer-shell exclusive zone on
+/// Wayland. Unlike the Windows app bar API, neither adjusts the rect for
+/// space already reserved by other windows, so the rect is returned as-is.
+///
+/// On Wayland, the window needs to have been set up via
+/// `prepare_app_bar`.
+///
+/// Must be called from the main thread.
+pub fn create_app_bar(
+  window: &gtk::ApplicationWindow,
+  size: PhysicalSize<i32>,
+  position: PhysicalPosition<i32>,
+  edge: DockEdge,
+  scale_factor: f64,
+) -> anyhow::Result<(PhysicalSize<i32>, PhysicalPosition<i32>)> {
+  info!(
+    "Creating app bar with size {:?} and position {:?}.",
+    size, position
+  );
+
+  match x11_window_id(window) {
+    Some(xid) => strut::set_strut(xid, size, position, edge)?,
+    None => {
+      set_exclusive_zone(window, size, position, edge, scale_factor)?
+    }
+  }
+
+  Ok((size, position))
+}
+
+/// Removes space reserved by `create_app_bar`.
+///
+/// Must be called from the main thread.
+pub fn remove_app_bar(
+  window: &gtk::ApplicationWindow,
+) -> anyhow::Result<()> {
+  info!("Removing app bar.");
+
+  match x11_window_id(window) {
+    Some(xid) => strut::remove_strut(xid),
+    None => {
+      if window.is_layer_window() {
+        window.set_exclusive_zone(0);
+      }
+
+      Ok(())
+    }
+  }
+}
+
+/// Gets the X11 window ID, or `None` if not running on X11.
+fn x11_window_id(window: &gtk::ApplicationWindow) -> Option<c_ulong> {
+  window
+    .window()
+    .and_then(|window| window.downcast::<gdkx11::X11Window>().ok())
+    .map(|window| window.xid())
+}
+
+/// Anchors the layer surface to the given edge and reserves an exclusive
+/// zone for the given rect.
+fn set_exclusive_zone(
+  window: &gtk::ApplicationWindow,
+  size: PhysicalSize<i32>,
+  position: PhysicalPosition<i32>,
+  edge: DockEdge,
+  scale_factor: f64,
+) -> anyhow::Result<()> {
+  if !window.is_layer_window() {
+    bail!("Window was not prepared as an app bar.");
+  }
+
+  // Layer surfaces are positioned by the compositor, relative to the
+  // monitor they're on.
+  let monitor = gdk::Display::default()
+    .context("No default GDK display.")?
+    .monitor_at_point(
+      ((position.x + size.width / 2) as f64 / scale_factor) as i32,
+      ((position.y + size.height / 2) as f64 / scale_factor) as i32,
+    )
+    .context("No monitor found for app bar.")?;
+
+  window.set_monitor(&monitor);
+
+  let anchored_edge = match edge {
+    DockEdge::Top => Edge::Top,
+    DockEdge::Bottom => Edge::Bottom,
+    DockEdge::Left => Edge::Left,
+    DockEdge::Right => Edge::Right,
+  };
+
+  // Only anchor to the docked edge, so that the surface keeps its own
+  // size along the edge instead of being stretched across it.
+  for layer_edge in [Edge::Top, Edge::Bottom, Edge::Left, Edge::Right] {
+    window.set_anchor(layer_edge, layer_edge == anchored_edge);
+  }
+
+  // Margins and the exclusive zone are in logical pixels, relative to
+  // the monitor's logical geometry.
+  let geometry = monitor.geometry();
+  let x = (position.x as f64 / scale_factor) as i32;
+  let y = (position.y as f64 / scale_factor) as i32;
+  let width = (size.width as f64 / scale_factor) as i32;
+  let height = (size.height as f64 / scale_factor) as i32;
+
+  // Distance from the monitor edge to the reserved rect (e.g. when
+  // stacked after other widgets docked to the same edge).
+  let margin = match edge {
+    DockEdge::Top => y - geometry.y(),
+    DockEdge::Bottom => geometry.y() + geometry.height() - (y + height),
+    DockEdge::Left => x - geometry.x(),
+    DockEdge::Right => geometry.x() + geometry.width() - (x + width),
+  };
+
+  window.set_layer_shell_margin(anchored_edge, margin.max(0));
+
+  // The compositor reserves the exclusive zone in addition to the
+  // margin.
+  window.set_exclusive_zone(match edge.is_horizontal() {
+    true => height,
+    false => width,
+  });
+
+  Ok(())
+}
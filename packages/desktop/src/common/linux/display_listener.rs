@@ -0,0 +1,62 @@
+use anyhow::{bail, Context};
+use gtk::gdk::{self, prelude::MonitorExt};
+use tauri::AppHandle;
+use tokio::sync::mpsc;
+
+/// Listens for display changes via GDK.
+///
+/// GDK receives these as RandR events on X11 and as output events on
+/// Wayland. Monitor connections, geometry, scale factor, and work area
+/// changes are all forwarded.
+///
+/// Must be called from the main thread.
+pub fn listen_display_changes(
+  _app_handle: &AppHandle,
+  change_tx: mpsc::UnboundedSender<()>,
+) -> anyhow::Result<()> {
+  if !gtk::is_initialized_main_thread() {
+    bail!("GDK can only be accessed from the main thread.");
+  }
+
+  let display =
+    gdk::Display::default().context("No default GDK display.")?;
+
+  for index in 0..display.n_monitors() {
+    if let Some(monitor) = display.monitor(index) {
+      watch_monitor(&monitor, &change_tx);
+    }
+  }
+
+  let added_tx = change_tx.clone();
+  display.connect_monitor_added(move |_, monitor| {
+    watch_monitor(monitor, &added_tx);
+    let _ = added_tx.send(());
+  });
+
+  display.connect_monitor_removed(move |_, _| {
+    let _ = change_tx.send(());
+  });
+
+  Ok(())
+}
+
+/// Forwards geometry, scale factor, and work area changes of a monitor.
+fn watch_monitor(
+  monitor: &gdk::Monitor,
+  change_tx: &mpsc::UnboundedSender<()>,
+) {
+  let tx = change_tx.clone();
+  monitor.connect_geometry_notify(move |_| {
+    let _ = tx.send(());
+  });
+
+  let tx = change_tx.clone();
+  monitor.connect_scale_factor_notify(move |_| {
+    let _ = tx.send(());
+  });
+
+  let tx = change_tx.clone();
+  monitor.connect_workarea_notify(move |_| {
+    let _ = tx.send(());
+  });
+}
@@ -0,0 +1,8 @@
+mod app_bar;
+mod display_listener;
+mod strut;
+mod window_ext_linux;
+
+pub use app_bar::*;
+pub use display_listener::*;
+pub use window_ext_linux::*;
@@ -0,0 +1,240 @@
+use std::{
+  ffi::{c_long, c_ulong},
+  ptr,
+};
+
+use anyhow::bail;
+use tauri::{PhysicalPosition, PhysicalSize};
+use x11_dl::xlib::{self, Xlib};
+
+use crate::widget_pack::DockEdge;
+
+/// Reserves screen space for an X11 window via the EWMH
+/// `_NET_WM_STRUT_PARTIAL` and `_NET_WM_STRUT` properties.
+///
+/// `size` and `position` are the rect to reserve in physical pixels.
+pub fn set_strut(
+  xid: c_ulong,
+  size: PhysicalSize<i32>,
+  position: PhysicalPosition<i32>,
+  edge: DockEdge,
+) -> anyhow::Result<()> {
+  with_display(|xlib, display| unsafe {
+    let screen = (xlib.XDefaultScreen)(display);
+
+    let screen_size = PhysicalSize::new(
+      (xlib.XDisplayWidth)(display, screen),
+      (xlib.XDisplayHeight)(display, screen),
+    );
+
+    let strut = strut_partial(size, position, edge, screen_size);
+
+    // `_NET_WM_STRUT` is the older variant that only holds the first 4
+    // values. Some window managers only support this one.
+    for (name, len) in
+      [(c"_NET_WM_STRUT_PARTIAL", 12), (c"_NET_WM_STRUT", 4)]
+    {
+      let atom = (xlib.XInternAtom)(display, name.as_ptr(), 0);
+
+      // Properties with a format of 32 are passed as `c_long` values.
+      (xlib.XChangeProperty)(
+        display,
+        xid,
+        atom,
+        xlib::XA_CARDINAL,
+        32,
+        xlib::PropModeReplace,
+        strut.as_ptr() as *const _,
+        len,
+      );
+    }
+  })
+}
+
+/// Removes space reserved via `set_strut`.
+pub fn remove_strut(xid: c_ulong) -> anyhow::Result<()> {
+  with_display(|xlib, display| unsafe {
+    for name in [c"_NET_WM_STRUT_PARTIAL", c"_NET_WM_STRUT"] {
+      let atom = (xlib.XInternAtom)(display, name.as_ptr(), 0);
+
+      (xlib.XDeleteProperty)(display, xid, atom);
+    }
+  })
+}
+
+/// Runs the callback with a new connection to the X server.
+///
+/// A separate connection is used rather than GDK's, since properties can
+/// be changed on any window regardless of which client created it.
+fn with_display(
+  callback: impl FnOnce(&Xlib, *mut xlib::Display),
+) -> anyhow::Result<()> {
+  let xlib = Xlib::open()?;
+  let display = unsafe { (xlib.XOpenDisplay)(ptr::null()) };
+
+  if display.is_null() {
+    bail!("Failed to connect to X server.");
+  }
+
+  callback(&xlib, display);
+
+  unsafe {
+    (xlib.XFlush)(display);
+    (xlib.XCloseDisplay)(display);
+  }
+
+  Ok(())
+}
+
+/// Gets the value of `_NET_WM_STRUT_PARTIAL` for the given rect.
+///
+/// Struts are relative to the edges of the X screen (i.e. the bounding
+/// box of all monitors) rather than the monitor that the rect is on.
+fn strut_partial(
+  size: PhysicalSize<i32>,
+  position: PhysicalPosition<i32>,
+  edge: DockEdge,
+  screen_size: PhysicalSize<i32>,
+) -> [c_long; 12] {
+  let left = position.x as c_long;
+  let top = position.y as c_long;
+  let right = (position.x + size.width) as c_long;
+  let bottom = (position.y + size.height) as c_long;
+
+  // Order is: left, right, top, bottom, left_start_y, left_end_y,
+  // right_start_y, right_end_y, top_start_x, top_end_x, bottom_start_x,
+  // bottom_end_x. End values are inclusive.
+  let mut strut = [0; 12];
+
+  match edge {
+    DockEdge::Left => {
+      strut[0] = right;
+      strut[4] = top;
+      strut[5] = bottom - 1;
+    }
+    DockEdge::Right => {
+      strut[1] = screen_size.width as c_long - left;
+      strut[6] = top;
+      strut[7] = bottom - 1;
+    }
+    DockEdge::Top => {
+      strut[2] = bottom;
+      strut[8] = left;
+      strut[9] = right - 1;
+    }
+    DockEdge::Bottom => {
+      strut[3] = screen_size.height as c_long - top;
+      strut[10] = left;
+      strut[11] = right - 1;
+    }
+  }
+
+  strut
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Screen with a 1920x1080 monitor and a 2560x1440 monitor to its
+  /// right.
+  const SCREEN_SIZE: PhysicalSize<i32> = PhysicalSize::new(4480, 1440);
+
+  #[test]
+  fn test_strut_partial() {
+    let strut = strut_partial(
+      PhysicalSize::new(1920, 40),
+      PhysicalPosition::new(0, 0),
+      DockEdge::Top,
+      SCREEN_SIZE,
+    );
+
+    assert_eq!(strut, [0, 0, 40, 0, 0, 0, 0, 0, 0, 1919, 0, 0]);
+
+    // The bottom edge of the smaller monitor is 360px above the bottom
+    // of the screen.
+    let strut = strut_partial(
+      PhysicalSize::new(1920, 40),
+      PhysicalPosition::new(0, 1040),
+      DockEdge::Bottom,
+      SCREEN_SIZE,
+    );
+
+    assert_eq!(strut, [0, 0, 0, 400, 0, 0, 0, 0, 0, 0, 0, 1919]);
+
+    let strut = strut_partial(
+      PhysicalSize::new(60, 1440),
+      PhysicalPosition::new(4420, 0),
+      DockEdge::Right,
+      SCREEN_SIZE,
+    );
+
+    assert_eq!(strut, [0, 60, 0, 0, 0, 0, 0, 1439, 0, 0, 0, 0]);
+  }
+
+  /// Sets a strut on a window and reads it back from the X server.
+  ///
+  /// Requires an X server (e.g. `xvfb-run cargo test`), and is skipped
+  /// if `DISPLAY` is not set.
+  #[test]
+  fn test_set_strut() {
+    if std::env::var_os("DISPLAY").is_none() {
+      return;
+    }
+
+    let xlib = Xlib::open().unwrap();
+
+    unsafe {
+      let display = (xlib.XOpenDisplay)(ptr::null());
+      assert!(!display.is_null());
+
+      let root = (xlib.XDefaultRootWindow)(display);
+      let xid =
+        (xlib.XCreateSimpleWindow)(display, root, 0, 0, 100, 40, 0, 0, 0);
+
+      (xlib.XSync)(display, 0);
+
+      set_strut(
+        xid,
+        PhysicalSize::new(100, 40),
+        PhysicalPosition::new(0, 0),
+        DockEdge::Top,
+      )
+      .unwrap();
+
+      let atom =
+        (xlib.XInternAtom)(display, c"_NET_WM_STRUT_PARTIAL".as_ptr(), 0);
+
+      let mut actual_type = 0;
+      let mut actual_format = 0;
+      let mut item_count = 0;
+      let mut bytes_after = 0;
+      let mut data = ptr::null_mut();
+
+      (xlib.XGetWindowProperty)(
+        display,
+        xid,
+        atom,
+        0,
+        12,
+        0,
+        xlib::XA_CARDINAL,
+        &mut actual_type,
+        &mut actual_format,
+        &mut item_count,
+        &mut bytes_after,
+        &mut data,
+      );
+
+      assert_eq!(actual_format, 32);
+      assert_eq!(item_count, 12);
+
+      let strut = std::slice::from_raw_parts(data as *const c_long, 12);
+      assert_eq!(strut, [0, 0, 40, 0, 0, 0, 0, 0, 0, 99, 0, 0]);
+
+      (xlib.XFree)(data as *mut _);
+      (xlib.XDestroyWindow)(display, xid);
+      (xlib.XCloseDisplay)(display);
+    }
+  }
+}
@@ -0,0 +1,71 @@
+use std::sync::mpsc;
+
+use tauri::{PhysicalPosition, PhysicalSize, Runtime, Window};
+
+use super::app_bar;
+use crate::widget_pack::DockEdge;
+
+pub trait WindowExtLinux {
+  /// Prepares the window for reserving screen space. Must be called
+  /// before the window is first shown.
+  fn prepare_app_bar(&self) -> anyhow::Result<()>;
+
+  fn allocate_app_bar(
+    &self,
+    size: PhysicalSize<i32>,
+    position: PhysicalPosition<i32>,
+    edge: DockEdge,
+  ) -> anyhow::Result<(PhysicalSize<i32>, PhysicalPosition<i32>)>;
+
+  fn deallocate_app_bar(&self) -> anyhow::Result<()>;
+}
+
+impl<R: Runtime> WindowExtLinux for Window<R> {
+  fn prepare_app_bar(&self) -> anyhow::Result<()> {
+    run_on_main_thread(self, |window| {
+      app_bar::prepare_app_bar(&window.gtk_window()?)
+    })
+  }
+
+  fn allocate_app_bar(
+    &self,
+    size: PhysicalSize<i32>,
+    position: PhysicalPosition<i32>,
+    edge: DockEdge,
+  ) -> anyhow::Result<(PhysicalSize<i32>, PhysicalPosition<i32>)> {
+    let scale_factor = self.scale_factor()?;
+
+    run_on_main_thread(self, move |window| {
+      app_bar::create_app_bar(
+        &window.gtk_window()?,
+        size,
+        position,
+        edge,
+        scale_factor,
+      )
+    })
+  }
+
+  fn deallocate_app_bar(&self) -> anyhow::Result<()> {
+    run_on_main_thread(self, |window| {
+      app_bar::remove_app_bar(&window.gtk_window()?)
+    })
+  }
+}
+
+/// Runs the callback on the main thread and waits for its result.
+///
+/// GTK windows can only be accessed from the main thread.
+fn run_on_main_thread<R: Runtime, T: Send + 'static>(
+  window: &Window<R>,
+  callback: impl FnOnce(&Window<R>) -> anyhow::Result<T> + Send + 'static,
+) -> anyhow::Result<T> {
+  let (result_tx, result_rx) = mpsc::channel();
+  let window_clone = window.clone();
+
+  window.run_on_main_thread(move || {
+    let _ = result_tx.send(callback(&window_clone));
+  })?;
+
+  result_rx.recv()?
+}
@@ -0,0 +1,63 @@
+use std::{ffi::c_void, sync::OnceLock};
+
+use anyhow::bail;
+use tauri::AppHandle;
+use tokio::sync::mpsc;
+
+/// Flag passed to the reconfiguration callback before a change is
+/// applied (`kCGDisplayBeginConfigurationFlag`).
+const BEGIN_CONFIGURATION_FLAG: u32 = 1;
+
+/// Sender for display change notifications.
+///
+/// Stored globally since the callback can't capture state.
+static CHANGE_TX: OnceLock<mpsc::UnboundedSender<()>> = OnceLock::new();
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+  fn CGDisplayRegisterReconfigurationCallback(
+    callback: extern "C" fn(u32, u32, *mut c_void),
+    user_info: *mut c_void,
+  ) -> i32;
+}
+
+/// Listens for display reconfigurations (e.g. monitor connections and
+/// resolution or scaling changes).
+///
+/// The callback is invoked on the main thread's run loop.
+pub fn listen_display_changes(
+  _app_handle: &AppHandle,
+  change_tx: mpsc::UnboundedSender<()>,
+) -> anyhow::Result<()> {
+  if CHANGE_TX.set(change_tx).is_err() {
+    bail!("Display change listener is already running.");
+  }
+
+  let err = unsafe {
+    CGDisplayRegisterReconfigurationCallback(
+      on_reconfiguration,
+      std::ptr::null_mut(),
+    )
+  };
+
+  if err != 0 {
+    bail!("Failed to register display callback (error {}).", err);
+  }
+
+  Ok(())
+}
+
+extern "C" fn on_reconfiguration(
+  _display_id: u32,
+  flags: u32,
+  _user_info: *mut c_void,
+) {
+  // Ignore the notification that's sent before the change is applied.
+  if flags & BEGIN_CONFIGURATION_FLAG != 0 {
+    return;
+  }
+
+  if let Some(change_tx) = CHANGE_TX.get() {
+    let _ = change_tx.send(());
+  }
+}
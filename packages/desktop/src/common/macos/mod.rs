@@ -1,3 +1,5 @@
+mod display_listener;
 mod window_ext_macos;
 
+pub use display_listener::*;
 pub use window_ext_macos::*;
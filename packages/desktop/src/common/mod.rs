@@ -1,11 +1,16 @@
 mod format_bytes;
 mod fs_util;
 pub mod glob_util;
+pub mod http_util;
 mod interval;
 mod length_value;
+#[cfg(target_os = "linux")]
+pub mod linux;
 #[cfg(target_os = "macos")]
 pub mod macos;
 mod path_ext;
+#[cfg(test)]
+pub mod test_util;
 #[cfg(target_os = "windows")]
 pub mod windows;
 
@@ -0,0 +1,77 @@
+use std::{
+  fs,
+  io::{Read, Write},
+  net::{Ipv4Addr, TcpListener},
+  path::PathBuf,
+  thread,
+};
+
+use crate::marketplace_installer::{MarketplacePackMetadata, PackSource};
+
+/// Gets a path in the temp directory that's unique to the test process,
+/// removing anything left over from a previous run.
+pub fn temp_dir(name: &str) -> PathBuf {
+  let dir = std::env::temp_dir().join(format!(
+    "zebar-test-{}-{}",
+    name,
+    std::process::id()
+  ));
+
+  let _ = fs::remove_dir_all(&dir);
+  dir
+}
+
+/// Spawns a local HTTP server that responds to each request with the
+/// raw response returned by `handler`. The handler is passed the raw
+/// request.
+///
+/// Returns the server's URL (e.g. `http://127.0.0.1:1234`).
+pub fn spawn_http_server(
+  handler: impl Fn(&str) -> String + Send + 'static,
+) -> String {
+  let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+  let url = format!("http://{}", listener.local_addr().unwrap());
+
+  thread::spawn(move || {
+    for stream in listener.incoming() {
+      let mut stream = stream.unwrap();
+      let mut buf = [0; 4096];
+      let len = stream.read(&mut buf).unwrap();
+      let request = String::from_utf8_lossy(&buf[..len]);
+
+      let _ = stream.write_all(handler(&request).as_bytes());
+    }
+  });
+
+  url
+}
+
+/// Formats a raw HTTP response with the given status (e.g. `200 OK`),
+/// headers, and body.
+pub fn http_response(
+  status: &str,
+  headers: &[(&str, &str)],
+  body: &str,
+) -> String {
+  let headers = headers
+    .iter()
+    .map(|(name, value)| format!("{name}: {value}\r\n"))
+    .collect::<String>();
+
+  format!(
+    "HTTP/1.1 {status}\r\n{headers}Content-Length: {}\r\n\r\n{body}",
+    body.len()
+  )
+}
+
+/// Gets the metadata of a pack installed from the marketplace.
+pub fn installed(pack_id: &str, version: &str) -> MarketplacePackMetadata {
+  MarketplacePackMetadata {
+    pack_id: pack_id.to_string(),
+    version: version.to_string(),
+    installed_at: 0,
+    publisher_key: None,
+    rolled_back_from: None,
+    source: PackSource::Marketplace,
+  }
+}
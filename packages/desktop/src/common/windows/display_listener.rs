@@ -0,0 +1,111 @@
+use std::sync::OnceLock;
+
+use anyhow::bail;
+use tauri::AppHandle;
+use tokio::sync::mpsc;
+use windows::Win32::{
+  Foundation::{HWND, LPARAM, LRESULT, WPARAM},
+  UI::WindowsAndMessaging::{
+    CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW,
+    RegisterClassW, TranslateMessage, CW_USEDEFAULT, MSG, SPI_SETWORKAREA,
+    WINDOW_EX_STYLE, WM_DISPLAYCHANGE, WM_DPICHANGED, WM_SETTINGCHANGE,
+    WNDCLASSW, WS_OVERLAPPEDWINDOW,
+  },
+};
+use windows_core::w;
+
+/// Sender for display change notifications.
+///
+/// Stored globally since the window procedure can't capture state.
+static CHANGE_TX: OnceLock<mpsc::UnboundedSender<()>> = OnceLock::new();
+
+/// Listens for display changes (i.e. `WM_DISPLAYCHANGE`, `WM_DPICHANGED`
+/// and work area changes via `WM_SETTINGCHANGE`).
+///
+/// Notifications are received by a hidden window running on a dedicated
+/// thread.
+pub fn listen_display_changes(
+  _app_handle: &AppHandle,
+  change_tx: mpsc::UnboundedSender<()>,
+) -> anyhow::Result<()> {
+  if CHANGE_TX.set(change_tx).is_err() {
+    bail!("Display change listener is already running.");
+  }
+
+  let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+
+  std::thread::spawn(move || {
+    let create_res = create_listener_window();
+    let is_created = create_res.is_ok();
+    let _ = ready_tx.send(create_res);
+
+    if is_created {
+      let mut msg = MSG::default();
+
+      // Returns 0 on `WM_QUIT` and -1 on error, both of which end the
+      // loop.
+      while unsafe { GetMessageW(&mut msg, None, 0, 0) }.0 > 0 {
+        let _ = unsafe { TranslateMessage(&msg) };
+        unsafe { DispatchMessageW(&msg) };
+      }
+    }
+  });
+
+  ready_rx.recv()?
+}
+
+/// Creates the hidden window that receives display change messages.
+fn create_listener_window() -> anyhow::Result<()> {
+  let class = WNDCLASSW {
+    lpszClassName: w!("ZebarDisplayListener"),
+    lpfnWndProc: Some(window_proc),
+    ..Default::default()
+  };
+
+  if unsafe { RegisterClassW(&class) } == 0 {
+    bail!("Failed to register display listener window class.");
+  }
+
+  // A top-level window is used, since message-only windows don't receive
+  // broadcast messages such as `WM_DISPLAYCHANGE`. The window is never
+  // shown.
+  unsafe {
+    CreateWindowExW(
+      WINDOW_EX_STYLE::default(),
+      w!("ZebarDisplayListener"),
+      w!("ZebarDisplayListener"),
+      WS_OVERLAPPEDWINDOW,
+      CW_USEDEFAULT,
+      CW_USEDEFAULT,
+      CW_USEDEFAULT,
+      CW_USEDEFAULT,
+      None,
+      None,
+      class.hInstance,
+      None,
+    )
+  }?;
+
+  Ok(())
+}
+
+unsafe extern "system" fn window_proc(
+  hwnd: HWND,
+  msg: u32,
+  wparam: WPARAM,
+  lparam: LPARAM,
+) -> LRESULT {
+  let is_display_change = match msg {
+    WM_DISPLAYCHANGE | WM_DPICHANGED => true,
+    WM_SETTINGCHANGE => wparam.0 as u32 == SPI_SETWORKAREA.0,
+    _ => false,
+  };
+
+  if is_display_change {
+    if let Some(change_tx) = CHANGE_TX.get() {
+      let _ = change_tx.send(());
+    }
+  }
+
+  DefWindowProcW(hwnd, msg, wparam, lparam)
+}
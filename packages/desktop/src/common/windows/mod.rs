@@ -1,7 +1,9 @@
 mod app_bar;
 mod com;
+mod display_listener;
 mod window_ext_windows;
 
 pub use app_bar::*;
 pub use com::*;
+pub use display_listener::*;
 pub use window_ext_windows::*;
@@ -1,4 +1,5 @@
 use std::{
+  collections::BTreeMap,
   fs,
   path::{Path, PathBuf},
   slice::Iter,
@@ -8,7 +9,7 @@ use anyhow::Context;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-  app_settings::{AppSettingsValue, StartupConfig},
+  app_settings::{AppSettingsValue, StartupConfig, UpdatePolicy},
   common::{has_extension, read_and_parse_json},
   widget_pack::{WidgetConfig, WidgetPackConfig},
 };
@@ -108,6 +109,10 @@ fn migrate_startup_config(config_dir: &Path) -> anyhow::Result<()> {
       crate::app_settings::VERSION_NUMBER
     )),
     startup_configs: new_startup_configs,
+    restore_session: false,
+    asset_server_port: None,
+    update_policy: UpdatePolicy::default(),
+    content_security_policies: BTreeMap::new(),
   };
 
   // Write the migrated settings back to the file.
@@ -286,6 +291,7 @@ impl TryFrom<StartupConfigFormat> for StartupConfig {
           pack,
           widget,
           preset: "default".to_string(),
+          condition: None,
         })
       }
       StartupConfigFormat::Object { path, preset } => {
@@ -295,6 +301,7 @@ impl TryFrom<StartupConfigFormat> for StartupConfig {
           pack,
           widget,
           preset,
+          condition: None,
         })
       }
       StartupConfigFormat::Current(config) => Ok(config),
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+
+use crate::{monitor_state::Monitor, widget_pack::DockEdge};
+
+/// Screen space reserved by a widget that is docked to a monitor edge.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DockSlot {
+  /// ID of the docked widget.
+  pub widget_id: String,
+
+  /// Monitor that the widget is docked on.
+  pub monitor: Monitor,
+
+  /// Edge that the widget is docked to.
+  pub edge: DockEdge,
+
+  /// Position of the widget when stacked with other widgets on the same
+  /// edge. Widgets with a lower order are placed closer to the edge.
+  pub order: i32,
+
+  /// Order in which the widget was opened. Used to break ties between
+  /// widgets with the same `order`.
+  pub sequence: u32,
+
+  /// Length to reserve perpendicular to the edge (includes the offset
+  /// and window margin).
+  pub reserved_length: i32,
+
+  /// Margin to reserve after the window.
+  pub window_margin: i32,
+}
+
+/// Gets the distance from the monitor edge that each docked widget
+/// should be placed at, such that widgets docked to the same edge of a
+/// monitor are stacked rather than overlapping.
+///
+/// Returns a map of widget ID's to their offset from the edge.
+pub fn stack_offsets(slots: &[DockSlot]) -> HashMap<String, i32> {
+  let mut sorted_slots = slots.iter().collect::<Vec<_>>();
+  sorted_slots.sort_by_key(|slot| (slot.order, slot.sequence));
+
+  let mut offsets = HashMap::new();
+
+  for (index, slot) in sorted_slots.iter().enumerate() {
+    let offset = sorted_slots[..index]
+      .iter()
+      .filter(|other| {
+        other.monitor == slot.monitor && other.edge == slot.edge
+      })
+      .map(|other| other.reserved_length)
+      .sum();
+
+    offsets.insert(slot.widget_id.clone(), offset);
+  }
+
+  offsets
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn monitor(name: &str) -> Monitor {
+    Monitor {
+      name: Some(name.to_string()),
+      is_primary: false,
+      x: 0,
+      y: 0,
+      width: 1920,
+      height: 1080,
+      scale_factor: 1.0,
+    }
+  }
+
+  fn slot(
+    widget_id: &str,
+    monitor_name: &str,
+    edge: DockEdge,
+    order: i32,
+    sequence: u32,
+    reserved_length: i32,
+  ) -> DockSlot {
+    DockSlot {
+      widget_id: widget_id.to_string(),
+      monitor: monitor(monitor_name),
+      edge,
+      order,
+      sequence,
+      reserved_length,
+      window_margin: 0,
+    }
+  }
+
+  #[test]
+  fn test_stack_offsets() {
+    let offsets = stack_offsets(&[
+      slot("widget-1", "DISPLAY1", DockEdge::Top, 0, 1, 40),
+      slot("widget-2", "DISPLAY1", DockEdge::Top, 0, 2, 30),
+      slot("widget-3", "DISPLAY1", DockEdge::Top, 0, 3, 20),
+    ]);
+
+    assert_eq!(offsets["widget-1"], 0);
+    assert_eq!(offsets["widget-2"], 40);
+    assert_eq!(offsets["widget-3"], 70);
+  }
+
+  #[test]
+  fn test_stack_offsets_order() {
+    // A lower order takes precedence over the order that widgets were
+    // opened in.
+    let offsets = stack_offsets(&[
+      slot("widget-1", "DISPLAY1", DockEdge::Bottom, 1, 1, 40),
+      slot("widget-2", "DISPLAY1", DockEdge::Bottom, 0, 2, 30),
+      slot("widget-3", "DISPLAY1", DockEdge::Bottom, -1, 3, 20),
+    ]);
+
+    assert_eq!(offsets["widget-3"], 0);
+    assert_eq!(offsets["widget-2"], 20);
+    assert_eq!(offsets["widget-1"], 50);
+  }
+
+  #[test]
+  fn test_stack_offsets_separate_edges() {
+    // Widgets are only stacked with others on the same edge and monitor.
+    let offsets = stack_offsets(&[
+      slot("widget-1", "DISPLAY1", DockEdge::Top, 0, 1, 40),
+      slot("widget-2", "DISPLAY1", DockEdge::Left, 0, 2, 30),
+      slot("widget-3", "DISPLAY2", DockEdge::Top, 0, 3, 20),
+      slot("widget-4", "DISPLAY2", DockEdge::Top, 0, 4, 10),
+    ]);
+
+    assert_eq!(offsets["widget-1"], 0);
+    assert_eq!(offsets["widget-2"], 0);
+    assert_eq!(offsets["widget-3"], 0);
+    assert_eq!(offsets["widget-4"], 20);
+  }
+
+  #[test]
+  fn test_stack_offsets_closed_neighbor() {
+    let slots = vec![
+      slot("widget-1", "DISPLAY1", DockEdge::Top, 0, 1, 40),
+      slot("widget-2", "DISPLAY1", DockEdge::Top, 0, 2, 30),
+    ];
+
+    assert_eq!(stack_offsets(&slots)["widget-2"], 40);
+
+    // Remaining widget moves to the edge once its neighbor is closed.
+    assert_eq!(stack_offsets(&slots[1..])["widget-2"], 0);
+  }
+}
@@ -0,0 +1,101 @@
+use std::time::{Duration, Instant};
+
+/// Maximum time that a keep-alive widget stays hidden before its window
+/// is closed.
+pub const MAX_HIDDEN_DURATION: Duration = Duration::from_secs(10 * 60);
+
+/// Maximum number of keep-alive widgets that are kept hidden at once.
+///
+/// Each hidden widget holds onto its webview, so this caps the memory
+/// used by widgets that aren't shown.
+pub const MAX_HIDDEN_WIDGETS: usize = 8;
+
+/// Interval for checking for hidden widgets to evict.
+pub const EVICTION_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Gets which hidden widgets should be closed to free up their memory.
+///
+/// Widgets are evicted if they've been hidden for longer than
+/// `max_duration`, or if there are more than `max_count` hidden widgets,
+/// in which case the longest-hidden widgets are evicted first.
+///
+/// Takes a list of widget ID's and when they were hidden.
+pub fn widgets_to_evict(
+  hidden_widgets: &[(String, Instant)],
+  now: Instant,
+  max_duration: Duration,
+  max_count: usize,
+) -> Vec<String> {
+  let mut sorted_widgets = hidden_widgets.iter().collect::<Vec<_>>();
+
+  // Sort by most recently hidden first.
+  sorted_widgets
+    .sort_by_key(|(_, hidden_at)| std::cmp::Reverse(*hidden_at));
+
+  sorted_widgets
+    .into_iter()
+    .enumerate()
+    .filter(|(index, (_, hidden_at))| {
+      *index >= max_count
+        || now.saturating_duration_since(*hidden_at) > max_duration
+    })
+    .map(|(_, (widget_id, _))| widget_id.clone())
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn hidden_widgets(
+    now: Instant,
+    secs_ago: &[u64],
+  ) -> Vec<(String, Instant)> {
+    secs_ago
+      .iter()
+      .enumerate()
+      .map(|(index, secs)| {
+        (
+          format!("widget-{}", index + 1),
+          now - Duration::from_secs(*secs),
+        )
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_evict_long_hidden() {
+    let now = Instant::now() + Duration::from_secs(3600);
+    let widgets = hidden_widgets(now, &[30, 700, 599]);
+
+    let evicted =
+      widgets_to_evict(&widgets, now, Duration::from_secs(600), 8);
+
+    assert_eq!(evicted, vec!["widget-2"]);
+  }
+
+  #[test]
+  fn test_evict_over_limit() {
+    let now = Instant::now() + Duration::from_secs(3600);
+    let widgets = hidden_widgets(now, &[50, 10, 40, 20, 30]);
+
+    // Longest-hidden widgets are evicted first.
+    let mut evicted =
+      widgets_to_evict(&widgets, now, Duration::from_secs(600), 3);
+    evicted.sort();
+
+    assert_eq!(evicted, vec!["widget-1", "widget-3"]);
+  }
+
+  #[test]
+  fn test_evict_none() {
+    let now = Instant::now() + Duration::from_secs(3600);
+    let widgets = hidden_widgets(now, &[10, 20]);
+
+    assert!(widgets_to_evict(&widgets, now, Duration::from_secs(600), 8)
+      .is_empty());
+    assert!(
+      widgets_to_evict(&[], now, Duration::from_secs(600), 0).is_empty()
+    );
+  }
+}
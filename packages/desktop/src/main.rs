@@ -2,7 +2,12 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 #![feature(iterator_try_collect)]
 
-use std::{env, path::Path, sync::Arc};
+use std::{
+  env,
+  path::{Path, PathBuf},
+  sync::Arc,
+  time::Duration,
+};
 
 use anyhow::Context;
 use clap::Parser;
@@ -21,10 +26,21 @@ use tracing_subscriber::{
 use crate::common::windows::WindowExtWindows;
 use crate::{
   app_settings::AppSettings,
-  asset_server::setup_asset_server,
-  cli::{Cli, CliCommand, MonitorType, QueryArgs},
+  asset_cache::AssetCache,
+  asset_server::{
+    add_widget_capability, setup_asset_server, AssetServerInfo,
+    DEFAULT_ASSET_SERVER_PORT,
+  },
+  cli::{
+    Cli, CliCommand, ExportArgs, InstallArgs, MarketplaceArgs,
+    MonitorType, QueryArgs, StartupArgs,
+  },
+  common::{read_and_parse_json, to_si_bytes, write_atomic},
   marketplace_installer::MarketplaceInstaller,
-  monitor_state::MonitorState,
+  marketplace_updater::{MarketplaceUpdater, UPDATE_CHECK_INTERVAL},
+  message_bus::MessageBus,
+  monitor_state::{MonitorEvent, MonitorState},
+  pack_archive::PackIntegrity,
   providers::{ProviderEmission, ProviderManager},
   shell_state::ShellState,
   sys_tray::SysTray,
@@ -32,22 +48,37 @@ use crate::{
   widget_pack::{
     MonitorSelection, WidgetPack, WidgetPackManager, WidgetPlacement,
   },
+  widget_storage::WidgetStorage,
+  widget_visibility::VisibilityChange,
 };
 
 mod app_settings;
+mod asset_cache;
 mod asset_server;
 mod cli;
 mod commands;
 mod common;
 mod config_migration;
+mod dock_layout;
+mod keep_alive;
 mod marketplace_installer;
+mod marketplace_updater;
+mod message_bus;
 mod monitor_state;
+mod pack_archive;
+mod popup_placement;
 mod providers;
 mod publish;
 mod shell_state;
+mod startup_condition;
 mod sys_tray;
 mod widget_factory;
+mod widget_headers;
 mod widget_pack;
+mod widget_session;
+mod widget_storage;
+mod widget_template;
+mod widget_visibility;
 
 #[macro_use]
 extern crate rocket;
@@ -82,6 +113,21 @@ async fn main() -> anyhow::Result<()> {
               cli::print_and_exit(result);
               Ok(())
             }
+            CliCommand::Install(args) => {
+              let result = install_widget_pack(app, args).await;
+              cli::print_and_exit(result);
+              Ok(())
+            }
+            CliCommand::Export(args) => {
+              let result = export_widget_pack(app, args).await;
+              cli::print_and_exit(result);
+              Ok(())
+            }
+            CliCommand::Marketplace(args) => {
+              let result = run_marketplace_command(app, args).await;
+              cli::print_and_exit(result);
+              Ok(())
+            }
             _ => {
               let start_res = start_app(app, cli).await;
 
@@ -106,6 +152,8 @@ async fn main() -> anyhow::Result<()> {
       commands::start_widget,
       commands::start_widget_preset,
       commands::stop_widget_preset,
+      commands::show_widget,
+      commands::hide_widget,
       commands::update_widget_config,
       commands::create_widget_pack,
       commands::update_widget_pack,
@@ -116,6 +164,13 @@ async fn main() -> anyhow::Result<()> {
       commands::unlisten_provider,
       commands::call_provider_function,
       commands::install_widget_pack,
+      commands::install_widget_pack_archive,
+      commands::export_widget_pack,
+      commands::rollback_widget_pack,
+      commands::collect_pack_garbage,
+      commands::pack_updates,
+      commands::check_pack_updates,
+      commands::install_pack_update,
       commands::start_preview_widget,
       commands::stop_all_preview_widgets,
       commands::set_always_on_top,
@@ -124,11 +179,23 @@ async fn main() -> anyhow::Result<()> {
       commands::shell_spawn,
       commands::shell_write,
       commands::shell_kill,
+      commands::publish,
+      commands::subscribe,
+      commands::unsubscribe,
+      commands::storage_get,
+      commands::storage_set,
+      commands::storage_delete,
+      commands::storage_watch,
     ])
     .build(tauri::generate_context!())?;
 
   app.run(|app, event| {
     if let RunEvent::ExitRequested { .. } = &event {
+      // Keep widgets that are closed on exit in the saved session.
+      if let Some(widget_factory) = app.try_state::<Arc<WidgetFactory>>() {
+        widget_factory.freeze_session();
+      }
+
       // Deallocate any appbars on Windows.
       #[cfg(target_os = "windows")]
       {
@@ -150,21 +217,192 @@ fn output_query(app: &tauri::App, args: QueryArgs) -> anyhow::Result<()> {
       cli::print_and_exit(monitors.output_str());
       Ok(())
     }
+    QueryArgs::AssetServer { config_dir } => {
+      let config_dir = match config_dir {
+        Some(config_dir) => config_dir,
+        None => default_config_dir(app)?,
+      };
+
+      let output = read_and_parse_json::<AssetServerInfo>(
+        &AppSettings::asset_server_file(&config_dir),
+      )
+      .ok()
+      .filter(AssetServerInfo::is_running)
+      .context("Asset server is not running.")
+      .and_then(|info| Ok(serde_json::to_string(&info)?));
+
+      cli::print_and_exit(output);
+      Ok(())
+    }
+  }
+}
+
+/// Installs a widget pack from a tarball without starting Zebar.
+async fn install_widget_pack(
+  app: &tauri::App,
+  args: InstallArgs,
+) -> anyhow::Result<String> {
+  let (_, marketplace_installer, _install_rx) =
+    standalone_installer(app, args.config_dir)?;
+
+  let pack = marketplace_installer
+    .install_from_archive(
+      &args.source,
+      args.pack_id.as_deref(),
+      &PackIntegrity {
+        sha256: args.sha256,
+        ..Default::default()
+      },
+    )
+    .await?;
+
+  Ok(format!(
+    "Widget pack '{}' with version {} successfully installed! If Zebar \
+     is running, reload configs via the system tray to use it.",
+    pack.id, pack.config.version
+  ))
+}
+
+/// Exports a widget pack to a tarball without starting Zebar.
+async fn export_widget_pack(
+  app: &tauri::App,
+  args: ExportArgs,
+) -> anyhow::Result<String> {
+  let (app_settings, marketplace_installer, _) =
+    standalone_installer(app, args.config_dir)?;
+
+  let widget_pack_manager =
+    WidgetPackManager::new(app_settings, marketplace_installer)?;
+
+  let pack = widget_pack_manager
+    .widget_pack_by_id(&args.pack_id)
+    .await
+    .with_context(|| {
+      format!("No widget pack found for '{}'.", args.pack_id)
+    })?;
+
+  let sha256 = publish::export_widget_pack(&pack, &args.output)?;
+
+  Ok(format!(
+    "Widget pack '{}' exported to '{}' (SHA-256: {}).",
+    pack.id,
+    args.output.display(),
+    sha256
+  ))
+}
+
+/// Runs a `zebar marketplace` command without starting Zebar.
+async fn run_marketplace_command(
+  app: &tauri::App,
+  args: MarketplaceArgs,
+) -> anyhow::Result<String> {
+  match args {
+    MarketplaceArgs::Rollback {
+      pack_id,
+      version,
+      config_dir,
+    } => {
+      let (_, marketplace_installer, _install_rx) =
+        standalone_installer(app, config_dir)?;
+
+      let pack = marketplace_installer
+        .rollback(&pack_id, version.as_deref())
+        .await?;
+
+      Ok(format!(
+        "Widget pack '{}' rolled back to version {}! If Zebar is \
+         running, reload configs via the system tray to use it.",
+        pack.id, pack.config.version
+      ))
+    }
+    MarketplaceArgs::Gc {
+      keep,
+      dry_run,
+      config_dir,
+    } => {
+      let (_, marketplace_installer, _) =
+        standalone_installer(app, config_dir)?;
+
+      let report = marketplace_installer.collect_garbage(keep, dry_run)?;
+
+      if report.removed.is_empty() {
+        return Ok("No widget pack downloads to remove.".into());
+      }
+
+      let mut output = match dry_run {
+        true => "Reclaimable widget pack downloads:\n",
+        false => "Removed widget pack downloads:\n",
+      }
+      .to_string();
+
+      for download in &report.removed {
+        output += &format!(
+          "  {} ({})\n",
+          download
+            .path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy(),
+          format_size(download.size)
+        );
+      }
+
+      output += &format!("Total: {}", format_size(report.reclaimed_size));
+
+      Ok(output)
+    }
   }
 }
 
+/// Creates a `MarketplaceInstaller` for CLI commands that run without
+/// starting Zebar.
+///
+/// The returned receiver needs to be kept alive for installation events
+/// to be sent.
+fn standalone_installer(
+  app: &tauri::App,
+  config_dir: Option<PathBuf>,
+) -> anyhow::Result<(
+  Arc<AppSettings>,
+  Arc<MarketplaceInstaller>,
+  mpsc::Receiver<WidgetPack>,
+)> {
+  let config_dir = match config_dir {
+    Some(config_dir) => config_dir,
+    None => default_config_dir(app)?,
+  };
+
+  let app_settings = Arc::new(AppSettings::new(app.handle(), config_dir)?);
+
+  let (marketplace_installer, install_rx) =
+    MarketplaceInstaller::new(app.handle(), app_settings.clone())?;
+
+  Ok((app_settings, marketplace_installer, install_rx))
+}
+
+/// Formats a size in bytes for CLI output (e.g. `1.2 MB`).
+fn format_size(bytes: u64) -> String {
+  let (value, unit) = to_si_bytes(bytes as f64);
+  format!("{:.1} {}", value, unit)
+}
+
+/// Gets the default config directory (`~/.glzr/zebar`).
+fn default_config_dir(app: &tauri::App) -> anyhow::Result<PathBuf> {
+  app
+    .path()
+    .resolve(".glzr/zebar", BaseDirectory::Home)
+    .context("Unable to get home directory.")
+}
+
 /// Starts Zebar - either with a specific widget or all widgets.
 async fn start_app(app: &mut tauri::App, cli: Cli) -> anyhow::Result<()> {
   let config_dir = match cli.command() {
-    CliCommand::Startup(args) => args.config_dir,
-    _ => None,
-  }
-  .unwrap_or(
-    app
-      .path()
-      .resolve(".glzr/zebar", BaseDirectory::Home)
-      .context("Unable to get home directory.")?,
-  );
+    CliCommand::Startup(StartupArgs {
+      config_dir: Some(config_dir),
+      ..
+    }) => config_dir,
+    _ => default_config_dir(app)?,
+  };
 
   setup_logging(&cli, &config_dir)?;
 
@@ -177,6 +415,14 @@ async fn start_app(app: &mut tauri::App, cli: Cli) -> anyhow::Result<()> {
     MarketplaceInstaller::new(app.handle(), app_settings.clone())?;
   app.manage(marketplace_installer.clone());
 
+  // Initialize `MarketplaceUpdater` in Tauri state.
+  let marketplace_updater = Arc::new(MarketplaceUpdater::new(
+    app.handle(),
+    app_settings.clone(),
+    marketplace_installer.clone(),
+  )?);
+  app.manage(marketplace_updater.clone());
+
   // Initialize `WidgetPackManager` in Tauri state.
   let widget_pack_manager = Arc::new(WidgetPackManager::new(
     app_settings.clone(),
@@ -202,8 +448,26 @@ async fn start_app(app: &mut tauri::App, cli: Cli) -> anyhow::Result<()> {
   // guaranteed to be one of the open commands here.
   setup_single_instance(app, widget_factory.clone())?;
 
-  // Start the asset server.
-  setup_asset_server().await?;
+  // Start the asset server. Port from the CLI takes precedence over the
+  // settings file.
+  let asset_server_port = cli
+    .asset_server_port()
+    .or(app_settings.asset_server_port().await)
+    .unwrap_or(DEFAULT_ASSET_SERVER_PORT);
+
+  let asset_server_port = setup_asset_server(
+    AssetCache::new(app_settings.proxy_cache_dir.clone())?,
+    asset_server_port,
+  )
+  .await?;
+
+  add_widget_capability(app.handle(), asset_server_port)?;
+
+  // Save the bound port for `zebar query asset-server`.
+  write_atomic(
+    &app_settings.asset_server_file,
+    &serde_json::to_vec(&AssetServerInfo::current())?,
+  )?;
 
   // Prevent windows from showing up in the dock on MacOS.
   #[cfg(target_os = "macos")]
@@ -219,8 +483,23 @@ async fn start_app(app: &mut tauri::App, cli: Cli) -> anyhow::Result<()> {
   }
 
   app.manage(ShellState::new(app.handle(), widget_factory.clone()));
+
+  // Initialize `MessageBus` in Tauri state.
+  let message_bus = Arc::new(MessageBus::new(widget_factory.clone()));
+  app.manage(message_bus.clone());
+
+  // Initialize `WidgetStorage` in Tauri state.
+  let widget_storage = Arc::new(WidgetStorage::new(
+    app_settings.widget_storage_dir.clone(),
+    widget_factory.clone(),
+  ));
+  app.manage(widget_storage.clone());
+
   app.handle().plugin(tauri_plugin_dialog::init())?;
   app.handle().plugin(tauri_plugin_shell::init())?;
+  app
+    .handle()
+    .plugin(tauri_plugin_global_shortcut::Builder::new().build())?;
 
   // Initialize `ProviderManager` in Tauri state.
   let (manager, emit_rx) = ProviderManager::new(app.handle());
@@ -233,6 +512,7 @@ async fn start_app(app: &mut tauri::App, cli: Cli) -> anyhow::Result<()> {
   let tray = SysTray::new(
     app.handle(),
     app_settings.clone(),
+    marketplace_updater.clone(),
     widget_pack_manager.clone(),
     widget_factory.clone(),
   )
@@ -241,9 +521,12 @@ async fn start_app(app: &mut tauri::App, cli: Cli) -> anyhow::Result<()> {
   listen_events(
     app.handle(),
     app_settings,
+    marketplace_updater,
     widget_pack_manager,
     monitor_state,
     widget_factory,
+    message_bus,
+    widget_storage,
     tray,
     manager,
     emit_rx,
@@ -262,9 +545,12 @@ async fn start_app(app: &mut tauri::App, cli: Cli) -> anyhow::Result<()> {
 fn listen_events(
   app_handle: &AppHandle,
   app_settings: Arc<AppSettings>,
+  marketplace_updater: Arc<MarketplaceUpdater>,
   widget_pack_manager: Arc<WidgetPackManager>,
   monitor_state: Arc<MonitorState>,
   widget_factory: Arc<WidgetFactory>,
+  message_bus: Arc<MessageBus>,
+  widget_storage: Arc<WidgetStorage>,
   tray: SysTray,
   manager: Arc<ProviderManager>,
   mut emit_rx: mpsc::UnboundedReceiver<ProviderEmission>,
@@ -273,28 +559,83 @@ fn listen_events(
   let app_handle = app_handle.clone();
   let mut widget_open_rx = widget_factory.open_tx.subscribe();
   let mut widget_close_rx = widget_factory.close_tx.subscribe();
+  let mut widget_visibility_rx = widget_factory.visibility_tx.subscribe();
+  let mut widget_keep_alive_rx = widget_factory.keep_alive_tx.subscribe();
   let mut settings_change_rx = app_settings.settings_change_tx.subscribe();
   let mut monitors_change_rx = monitor_state.change_tx.subscribe();
   let mut widget_configs_change_rx =
     widget_pack_manager.widget_configs_change_tx.subscribe();
   let mut widget_packs_change_rx =
     widget_pack_manager.widget_packs_change_tx.subscribe();
+  let mut updates_change_rx =
+    marketplace_updater.updates_change_tx.subscribe();
 
   task::spawn(async move {
+    let mut eviction_interval =
+      tokio::time::interval(keep_alive::EVICTION_INTERVAL);
+
+    // Delay the first update check to avoid slowing down startup.
+    let mut update_check_interval = tokio::time::interval_at(
+      tokio::time::Instant::now() + Duration::from_secs(60),
+      UPDATE_CHECK_INTERVAL,
+    );
+
     loop {
       let res = tokio::select! {
         Ok(widget_state) = widget_open_rx.recv() => {
           info!("Widget opened.");
+          let _ = widget_factory.save_session().await;
           let _ = tray.refresh().await;
           let _ = app_handle.emit("widget-opened", widget_state);
           Ok(())
         },
         Ok(widget_id) = widget_close_rx.recv() => {
           info!("Widget closed.");
+          message_bus.remove_widget(&widget_id);
+          widget_storage.remove_widget(&widget_id).await;
+          let _ = widget_factory.save_session().await;
+          let _ = widget_factory.restack_docked().await;
           let _ = tray.refresh().await;
           let _ = app_handle.emit("widget-closed", widget_id);
           Ok(())
         },
+        Ok(widget_state) = widget_keep_alive_rx.recv() => {
+          let event = match widget_state.is_hidden {
+            true => "widget-hidden",
+            false => "widget-shown",
+          };
+
+          info!("Widget kept alive: {}.", event);
+          let _ = widget_factory.save_session().await;
+          let _ = tray.refresh().await;
+          let _ = app_handle.emit(event, widget_state.id);
+          Ok(())
+        },
+        _ = eviction_interval.tick() => {
+          widget_factory.evict_hidden().await
+        },
+        Ok(visibility_change) = widget_visibility_rx.recv() => {
+          // Run in a separate task, since the slide animation would
+          // otherwise hold up other events.
+          let widget_factory = widget_factory.clone();
+
+          task::spawn(async move {
+            let res = match visibility_change {
+              VisibilityChange::AutoHide { widget_id, hidden } => {
+                widget_factory.set_hidden(&widget_id, hidden).await
+              }
+              VisibilityChange::Hotkey(hotkey) => {
+                widget_factory.toggle_by_hotkey(&hotkey).await
+              }
+            };
+
+            if let Err(err) = res {
+              error!("Failed to change widget visibility: {:?}", err);
+            }
+          });
+
+          Ok(())
+        },
         Ok(_) = settings_change_rx.recv() => {
           info!("Settings changed.");
           tray.refresh().await
@@ -303,9 +644,36 @@ fn listen_events(
           info!("Widget packs changed.");
           tray.refresh().await
         },
-        Ok(_) = monitors_change_rx.recv() => {
-          info!("Monitors changed.");
-          widget_factory.relaunch_all().await
+        _ = update_check_interval.tick() => {
+          // Run in a separate task, since installed packs are registered
+          // via this event loop.
+          let marketplace_updater = marketplace_updater.clone();
+
+          task::spawn(async move {
+            if let Err(err) = marketplace_updater.check_scheduled().await {
+              error!("Scheduled update check failed: {:?}", err);
+            }
+          });
+
+          Ok(())
+        },
+        Ok(updates) = updates_change_rx.recv() => {
+          info!("Available widget pack updates changed: {:?}", updates);
+          tray.refresh().await
+        },
+        Ok(event) = monitors_change_rx.recv() => {
+          match event {
+            MonitorEvent::MonitorsChanged(monitors) => {
+              info!("Monitors changed: {:?}", monitors);
+              let res = widget_factory.reconcile_monitors().await;
+              widget_factory.reevaluate_startup_configs().await;
+              res
+            }
+            MonitorEvent::WorkAreasChanged(work_areas) => {
+              info!("Work areas changed: {:?}", work_areas);
+              Ok(())
+            }
+          }
         },
         Ok((pack_id, changed_config)) = widget_configs_change_rx.recv() => {
           info!("Widget config changed.");
@@ -321,8 +689,11 @@ fn listen_events(
         },
         Some(pack) = install_rx.recv() => {
           info!("Widget pack installed: {:?}", pack);
+          let pack_id = pack.id.clone();
           widget_pack_manager.register_widget_pack(pack).await;
-          Ok(())
+
+          // Relaunch open widgets so that they use the new version.
+          widget_factory.relaunch_by_pack(&pack_id).await
         },
       };
 
@@ -388,6 +759,7 @@ async fn open_widgets_by_cli_command(
               MonitorType::Secondary => MonitorSelection::Secondary,
             },
             dock_to_edge: Default::default(),
+            visibility: Default::default(),
           }),
           false,
         )
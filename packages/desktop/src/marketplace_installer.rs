@@ -1,27 +1,39 @@
 use std::{
+  cmp::Ordering,
+  collections::HashMap,
   fs::{self},
-  path::PathBuf,
+  path::{Path, PathBuf},
   sync::Arc,
-  time::{SystemTime, UNIX_EPOCH},
+  time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::{bail, Context};
-use flate2::read::GzDecoder;
 use reqwest::StatusCode;
+use semver::Version;
 use serde::{Deserialize, Serialize};
-use tar::Archive;
 use tauri::{path::BaseDirectory, AppHandle, Manager};
 use tokio::{sync::mpsc, task};
 
 use crate::{
   app_settings::AppSettings,
-  common::{copy_dir_all, read_and_parse_json},
+  common::{copy_dir_all, read_and_parse_json, visit_deep},
+  pack_archive::{
+    self, PackIntegrity, MAX_DOWNLOAD_SIZE, MAX_UNPACKED_SIZE,
+  },
   widget_pack::{WidgetPack, WidgetPackConfig, WidgetPackManager},
 };
 
 /// The ID of the built-in starter pack.
 pub const STARTER_PACK_ID: &str = "glzr-io.starter";
 
+/// Default number of downloaded versions to keep per pack.
+pub const DEFAULT_KEEP_VERSIONS: usize = 2;
+
+/// How long temporary directories are left alone after they were last
+/// written to, since an install in another process might still be
+/// extracting to them.
+const TEMP_DIR_GRACE_PERIOD: Duration = Duration::from_secs(60 * 60);
+
 /// Metadata about an installed marketplace widget pack.
 ///
 /// These are stored in `%userprofile%/.glzr/zebar/.marketplace`.
@@ -36,6 +48,35 @@ pub struct MarketplacePackMetadata {
 
   /// Installation timestamp, stored as seconds since epoch.
   pub installed_at: u64,
+
+  /// Publisher key that the pack was signed with. Later versions must be
+  /// signed with the same key.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub publisher_key: Option<String>,
+
+  /// Version that was rolled back from. Not offered as an update again.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub rolled_back_from: Option<String>,
+
+  /// Where the pack was installed from.
+  #[serde(default)]
+  pub source: PackSource,
+}
+
+/// Where an installed pack was installed from.
+#[derive(
+  Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum PackSource {
+  /// Downloaded from the marketplace (or bundled with Zebar).
+  #[default]
+  Marketplace,
+
+  /// Installed from a tarball (e.g. via `zebar install`). Not checked for
+  /// updates, since its ID doesn't necessarily refer to the marketplace
+  /// pack of the same ID.
+  Archive,
 }
 
 impl MarketplacePackMetadata {
@@ -47,10 +88,42 @@ impl MarketplacePackMetadata {
         .duration_since(UNIX_EPOCH)
         .context("Failed to get timestamp.")?
         .as_secs(),
+      publisher_key: None,
+      rolled_back_from: None,
+      source: PackSource::default(),
     })
   }
 }
 
+/// A downloaded version of a marketplace pack within the download
+/// directory.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackDownload {
+  /// ID of the pack. `None` for leftovers of interrupted installs.
+  pub pack_id: Option<String>,
+
+  /// Version of the pack. `None` for leftovers of interrupted installs.
+  pub version: Option<String>,
+
+  /// Path to the extracted pack.
+  pub path: PathBuf,
+
+  /// Total size of the extracted files in bytes.
+  pub size: u64,
+}
+
+/// Downloads that were (or would be) removed by garbage collection.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GcReport {
+  /// Removed downloads.
+  pub removed: Vec<PackDownload>,
+
+  /// Total size of the removed downloads in bytes.
+  pub reclaimed_size: u64,
+}
+
 /// Manages installation of marketplace widget packs.
 #[derive(Debug)]
 pub struct MarketplaceInstaller {
@@ -107,27 +180,134 @@ impl MarketplaceInstaller {
     Ok(packs_metadata)
   }
 
+  /// Returns the metadata of an installed pack, if any.
+  pub fn installed_pack_metadata(
+    &self,
+    pack_id: &str,
+  ) -> Option<MarketplacePackMetadata> {
+    read_and_parse_json::<MarketplacePackMetadata>(
+      &self.app_settings.marketplace_pack_metadata_path(pack_id),
+    )
+    .ok()
+  }
+
   /// Installs a widget pack from the marketplace.
+  ///
+  /// The tarball is verified against the given integrity info before
+  /// being extracted.
   pub async fn install(
     &self,
     pack_id: &str,
     version: &str,
     tarball_url: &str,
+    integrity: &PackIntegrity,
     is_preview: bool,
   ) -> anyhow::Result<WidgetPack> {
+    // ID and version come from IPC and are used in file paths.
+    validate_pack_ref(pack_id, version)?;
+
     let pack_dir = self
       .app_settings
       .marketplace_pack_download_dir(pack_id, version);
 
+    // Publisher key of the installed version, which the new version must
+    // be signed with.
+    let pinned_key = self
+      .installed_pack_metadata(pack_id)
+      .and_then(|metadata| metadata.publisher_key);
+
     // Download and extract the pack. Skip the download if the directory
-    // already exists.
-    if !pack_dir.exists() {
-      self.download_and_extract(&pack_dir, tarball_url).await?;
+    // already exists, unless it was extracted from an unverified archive.
+    let archive_marker = archive_marker_path(&pack_dir);
+
+    if !pack_dir.exists() || archive_marker.exists() {
+      self
+        .download_and_extract(
+          &pack_dir,
+          tarball_url,
+          integrity,
+          pinned_key.as_deref(),
+        )
+        .await?;
+
+      if archive_marker.exists() {
+        fs::remove_file(&archive_marker)?;
+      }
     }
 
     // Create metadata.
-    let metadata = MarketplacePackMetadata::new(pack_id, version)?;
+    let metadata = MarketplacePackMetadata {
+      publisher_key: integrity.publisher_key.clone().or(pinned_key),
+      ..MarketplacePackMetadata::new(pack_id, version)?
+    };
+
+    self.finish_install(&pack_dir, metadata, is_preview).await
+  }
+
+  /// Installs a widget pack from a tarball at a local path or URL (e.g.
+  /// one created with `zebar export`).
+  ///
+  /// The pack ID defaults to the name in the pack's `zpack.json`.
+  /// Reinstalling a version that's already installed replaces its files.
+  pub async fn install_from_archive(
+    &self,
+    source: &str,
+    pack_id: Option<&str>,
+    integrity: &PackIntegrity,
+  ) -> anyhow::Result<WidgetPack> {
+    let bytes = match source.starts_with("http://")
+      || source.starts_with("https://")
+    {
+      true => {
+        tracing::info!("Downloading widget pack from {}.", source);
+        Self::download(source).await?
+      }
+      false => Self::read_archive(Path::new(source))?,
+    };
+
+    let pack_config = serde_json::from_slice::<WidgetPackConfig>(
+      &pack_archive::read_file(&bytes, "zpack.json")?,
+    )
+    .context("Failed to parse `zpack.json` in tarball.")?;
+
+    let pack_id = pack_id.unwrap_or(&pack_config.name);
+
+    // ID and version come from the tarball and are used in file paths.
+    validate_pack_ref(pack_id, &pack_config.version)?;
+
+    let pinned_key = self
+      .installed_pack_metadata(pack_id)
+      .and_then(|metadata| metadata.publisher_key);
+
+    pack_archive::verify(&bytes, integrity, pinned_key.as_deref())?;
+
+    let pack_dir = self
+      .app_settings
+      .marketplace_pack_download_dir(pack_id, &pack_config.version);
+
+    Self::extract(bytes, &pack_dir).await?;
+
+    // Mark the directory so that marketplace installs of the same version
+    // don't reuse it without verification.
+    fs::write(archive_marker_path(&pack_dir), "")?;
+
+    let metadata = MarketplacePackMetadata {
+      publisher_key: integrity.publisher_key.clone().or(pinned_key),
+      source: PackSource::Archive,
+      ..MarketplacePackMetadata::new(pack_id, &pack_config.version)?
+    };
 
+    self.finish_install(&pack_dir, metadata, false).await
+  }
+
+  /// Reads the extracted pack and saves its metadata (unless it's a
+  /// preview).
+  async fn finish_install(
+    &self,
+    pack_dir: &Path,
+    metadata: MarketplacePackMetadata,
+    is_preview: bool,
+  ) -> anyhow::Result<WidgetPack> {
     let pack = WidgetPackManager::read_widget_pack(
       &pack_dir.join("zpack.json"),
       Some(&metadata),
@@ -138,7 +318,9 @@ impl MarketplaceInstaller {
 
       // Write metadata to file.
       fs::write(
-        self.app_settings.marketplace_pack_metadata_path(pack_id),
+        self
+          .app_settings
+          .marketplace_pack_metadata_path(&metadata.pack_id),
         serde_json::to_string_pretty(&metadata)? + "\n",
       )?;
 
@@ -146,7 +328,7 @@ impl MarketplaceInstaller {
       self.installed_tx.send(pack.clone()).await?;
     }
 
-    tracing::info!("Installed widget pack: {}", pack_id);
+    tracing::info!("Installed widget pack: {}", metadata.pack_id);
 
     Ok(pack)
   }
@@ -163,40 +345,229 @@ impl MarketplaceInstaller {
     Ok(())
   }
 
-  /// Downloads and extracts a widget pack.
+  /// Switches an installed pack back to a previously downloaded version.
+  ///
+  /// Defaults to the newest downloaded version that's older than the
+  /// installed one.
+  pub async fn rollback(
+    &self,
+    pack_id: &str,
+    version: Option<&str>,
+  ) -> anyhow::Result<WidgetPack> {
+    let installed =
+      self.installed_pack_metadata(pack_id).with_context(|| {
+        format!("Widget pack '{}' is not installed.", pack_id)
+      })?;
+
+    let versions = self
+      .downloads()?
+      .into_iter()
+      .filter(|download| download.pack_id.as_deref() == Some(pack_id))
+      .filter_map(|download| download.version)
+      .filter(|version| *version != installed.version)
+      .collect::<Vec<_>>();
+
+    let target = match version {
+      Some(version) => versions
+        .into_iter()
+        .find(|downloaded| downloaded == version)
+        .with_context(|| {
+          format!(
+            "Version {} of '{}' is not downloaded.",
+            version, pack_id
+          )
+        })?,
+      None => previous_version(&versions, &installed.version)
+        .with_context(|| {
+          format!("No previous version of '{}' is downloaded.", pack_id)
+        })?,
+    };
+
+    tracing::info!(
+      "Rolling back widget pack {} from {} to {}.",
+      pack_id,
+      installed.version,
+      target
+    );
+
+    let metadata = MarketplacePackMetadata {
+      publisher_key: installed.publisher_key,
+      rolled_back_from: Some(installed.version),
+      source: installed.source,
+      ..MarketplacePackMetadata::new(pack_id, &target)?
+    };
+
+    let pack_dir = self
+      .app_settings
+      .marketplace_pack_download_dir(pack_id, &target);
+
+    self.finish_install(&pack_dir, metadata, false).await
+  }
+
+  /// Removes downloaded pack versions, keeping the installed version and
+  /// the `keep` newest versions of each installed pack.
+  ///
+  /// Downloads of packs that are no longer installed are removed
+  /// entirely. With `dry_run`, nothing is removed.
+  pub fn collect_garbage(
+    &self,
+    keep: usize,
+    dry_run: bool,
+  ) -> anyhow::Result<GcReport> {
+    let removed = reclaimable_downloads(
+      self.downloads()?,
+      &self.installed_packs_metadata()?,
+      keep,
+    );
+
+    if !dry_run {
+      for download in &removed {
+        tracing::info!(
+          "Removing pack download: {}",
+          download.path.display()
+        );
+        fs::remove_dir_all(&download.path)?;
+
+        let archive_marker = archive_marker_path(&download.path);
+
+        if archive_marker.exists() {
+          fs::remove_file(archive_marker)?;
+        }
+      }
+    }
+
+    Ok(GcReport {
+      reclaimed_size: removed.iter().map(|download| download.size).sum(),
+      removed,
+    })
+  }
+
+  /// Returns all pack versions within the download directory.
+  fn downloads(&self) -> anyhow::Result<Vec<PackDownload>> {
+    let mut downloads = Vec::new();
+
+    for entry in fs::read_dir(&self.app_settings.marketplace_download_dir)?
+    {
+      if let Some(download) =
+        read_download(entry?.path(), SystemTime::now())?
+      {
+        downloads.push(download);
+      }
+    }
+
+    Ok(downloads)
+  }
+
+  /// Downloads, verifies, and extracts a widget pack.
   async fn download_and_extract(
     &self,
-    pack_dir: &PathBuf,
+    pack_dir: &Path,
     tarball_url: &str,
+    integrity: &PackIntegrity,
+    pinned_key: Option<&str>,
   ) -> anyhow::Result<()> {
     tracing::info!("Downloading widget pack from {}.", tarball_url);
 
-    // Download the tarball.
-    let response = reqwest::get(tarball_url).await?;
+    let bytes = Self::download(tarball_url).await?;
+    pack_archive::verify(&bytes, integrity, pinned_key)?;
+
+    Self::extract(bytes, pack_dir).await
+  }
+
+  /// Extracts a tarball to the pack directory.
+  ///
+  /// If the directory already exists, the tarball is extracted alongside
+  /// it and swapped in once extraction succeeds.
+  async fn extract(bytes: Vec<u8>, pack_dir: &Path) -> anyhow::Result<()> {
+    // Create the parent of the pack directory.
+    if let Some(parent) = pack_dir.parent() {
+      fs::create_dir_all(parent)?;
+    }
+
+    tracing::info!("Extracting widget pack to {}", pack_dir.display());
+
+    let pack_dir = pack_dir.to_path_buf();
+
+    task::spawn_blocking(move || {
+      if !pack_dir.exists() {
+        return pack_archive::extract(
+          &bytes,
+          &pack_dir,
+          MAX_UNPACKED_SIZE,
+        );
+      }
+
+      let dir_name = pack_dir
+        .file_name()
+        .context("Invalid pack directory.")?
+        .to_string_lossy();
+
+      let new_dir = pack_dir.with_file_name(format!(
+        ".{}.new-{}",
+        dir_name,
+        uuid::Uuid::new_v4()
+      ));
+
+      pack_archive::extract(&bytes, &new_dir, MAX_UNPACKED_SIZE)?;
+      fs::remove_dir_all(&pack_dir)?;
+      fs::rename(&new_dir, &pack_dir)?;
+
+      Ok(())
+    })
+    .await?
+  }
+
+  /// Reads a local tarball, failing if it exceeds `MAX_DOWNLOAD_SIZE`.
+  fn read_archive(path: &Path) -> anyhow::Result<Vec<u8>> {
+    let size = fs::metadata(path)
+      .with_context(|| {
+        format!("Tarball not found at '{}'.", path.display())
+      })?
+      .len();
+
+    if size > MAX_DOWNLOAD_SIZE {
+      bail!(
+        "Widget pack exceeds maximum size of {} bytes.",
+        MAX_DOWNLOAD_SIZE
+      );
+    }
+
+    Ok(fs::read(path)?)
+  }
+
+  /// Downloads a tarball, failing if it exceeds `MAX_DOWNLOAD_SIZE`.
+  async fn download(tarball_url: &str) -> anyhow::Result<Vec<u8>> {
+    let mut response = reqwest::get(tarball_url).await?;
 
     if response.status() != StatusCode::OK {
       bail!("Failed to download widget pack: HTTP {}", response.status());
     }
 
-    let bytes = response.bytes().await?;
+    let size_error = || {
+      anyhow::anyhow!(
+        "Widget pack exceeds maximum download size of {} bytes.",
+        MAX_DOWNLOAD_SIZE
+      )
+    };
 
-    // Create the pack directory.
-    fs::create_dir_all(pack_dir)?;
-    tracing::info!("Extracting widget pack to {}", pack_dir.display());
+    if response
+      .content_length()
+      .is_some_and(|len| len > MAX_DOWNLOAD_SIZE)
+    {
+      return Err(size_error());
+    }
 
-    // Extract the tarball.
-    task::spawn_blocking({
-      let pack_dir = pack_dir.clone();
+    let mut bytes = Vec::new();
 
-      move || {
-        let decoder = GzDecoder::new(&bytes[..]);
-        let mut archive = Archive::new(decoder);
-        archive.unpack(&pack_dir)
+    while let Some(chunk) = response.chunk().await? {
+      if (bytes.len() + chunk.len()) as u64 > MAX_DOWNLOAD_SIZE {
+        return Err(size_error());
       }
-    })
-    .await??;
 
-    Ok(())
+      bytes.extend_from_slice(&chunk);
+    }
+
+    Ok(bytes)
   }
 
   /// Installs the `glzr-io.starter` widget pack from the embedded
@@ -235,3 +606,300 @@ impl MarketplaceInstaller {
     Ok(())
   }
 }
+
+/// Reads a pack version within the download directory.
+///
+/// Returns `None` for entries that aren't pack versions, and for
+/// temporary directories that were written to within the grace period
+/// before `now`.
+fn read_download(
+  path: PathBuf,
+  now: SystemTime,
+) -> anyhow::Result<Option<PackDownload>> {
+  if !path.is_dir() {
+    return Ok(None);
+  }
+
+  let dir_name = path
+    .file_name()
+    .map(|name| name.to_string_lossy().to_string())
+    .unwrap_or_default();
+
+  // Temporary directories are prefixed with `.` (see `extract`).
+  let is_temp_dir = dir_name.starts_with('.');
+
+  let (pack_id, version) = match dir_name.split_once('@') {
+    Some((pack_id, version)) if !is_temp_dir => {
+      (Some(pack_id.to_string()), Some(version.to_string()))
+    }
+    _ if is_temp_dir => (None, None),
+    _ => return Ok(None),
+  };
+
+  let mut size = 0;
+  let mut last_modified = fs::metadata(&path)?.modified()?;
+
+  visit_deep(&path, &mut |entry| {
+    if let Ok(metadata) = entry.metadata() {
+      if metadata.is_file() {
+        size += metadata.len();
+      }
+
+      if let Ok(modified) = metadata.modified() {
+        last_modified = last_modified.max(modified);
+      }
+    }
+  })?;
+
+  let is_recent = now
+    .duration_since(last_modified)
+    .map_or(true, |elapsed| elapsed < TEMP_DIR_GRACE_PERIOD);
+
+  if is_temp_dir && is_recent {
+    return Ok(None);
+  }
+
+  Ok(Some(PackDownload {
+    pack_id,
+    version,
+    path,
+    size,
+  }))
+}
+
+/// Gets the path to the file that marks a pack directory as extracted
+/// from an archive (e.g. `<id>@<version>.archive`).
+///
+/// Kept alongside the directory rather than within it, so that it isn't
+/// served or exported with the pack's files.
+fn archive_marker_path(pack_dir: &Path) -> PathBuf {
+  let mut file_name = pack_dir.file_name().unwrap_or_default().to_owned();
+  file_name.push(".archive");
+  pack_dir.with_file_name(file_name)
+}
+
+/// Whether the string can safely be used as a file name (e.g. for pack
+/// IDs and versions).
+fn is_valid_path_segment(value: &str) -> bool {
+  !value.is_empty()
+    && !value.starts_with('.')
+    && value.chars().all(|char| {
+      char.is_ascii_alphanumeric() || matches!(char, '-' | '_' | '.' | '+')
+    })
+}
+
+/// Errors unless the pack ID and version can safely be used in the
+/// `<id>@<version>` download directory name.
+fn validate_pack_ref(pack_id: &str, version: &str) -> anyhow::Result<()> {
+  if !is_valid_path_segment(pack_id) || !is_valid_path_segment(version) {
+    bail!(
+      "Invalid pack ID or version '{}@{}'. Only letters, digits, and \
+       `-_.+` are allowed.",
+      pack_id,
+      version
+    );
+  }
+
+  Ok(())
+}
+
+/// Compares two versions by semver precedence, falling back to string
+/// comparison for invalid versions.
+fn compare_versions(a: &str, b: &str) -> Ordering {
+  match (Version::parse(a), Version::parse(b)) {
+    (Ok(a), Ok(b)) => a.cmp(&b),
+    _ => a.cmp(b),
+  }
+}
+
+/// Gets the newest of the given versions that's older than `current`.
+fn previous_version(versions: &[String], current: &str) -> Option<String> {
+  versions
+    .iter()
+    .filter(|version| compare_versions(version, current) == Ordering::Less)
+    .max_by(|a, b| compare_versions(a, b))
+    .cloned()
+}
+
+/// Gets the downloads to remove when keeping `keep` versions per pack.
+///
+/// The installed version of a pack is always kept.
+fn reclaimable_downloads(
+  downloads: Vec<PackDownload>,
+  installed_packs: &[MarketplacePackMetadata],
+  keep: usize,
+) -> Vec<PackDownload> {
+  let installed_versions = installed_packs
+    .iter()
+    .map(|metadata| (metadata.pack_id.as_str(), metadata.version.as_str()))
+    .collect::<HashMap<_, _>>();
+
+  let mut by_pack = HashMap::<String, Vec<PackDownload>>::new();
+  let mut reclaimable = Vec::new();
+
+  for download in downloads {
+    match &download.pack_id {
+      Some(pack_id)
+        if installed_versions.contains_key(pack_id.as_str()) =>
+      {
+        by_pack.entry(pack_id.clone()).or_default().push(download);
+      }
+      // Leftovers and packs that are no longer installed.
+      _ => reclaimable.push(download),
+    }
+  }
+
+  for (pack_id, mut downloads) in by_pack {
+    // Sort from newest to oldest.
+    downloads.sort_by(|a, b| {
+      compare_versions(
+        b.version.as_deref().unwrap_or_default(),
+        a.version.as_deref().unwrap_or_default(),
+      )
+    });
+
+    let installed_version = installed_versions[pack_id.as_str()];
+
+    reclaimable.extend(downloads.into_iter().enumerate().filter_map(
+      |(index, download)| {
+        let is_installed =
+          download.version.as_deref() == Some(installed_version);
+
+        (index >= keep && !is_installed).then_some(download)
+      },
+    ));
+  }
+
+  reclaimable.sort_by(|a, b| a.path.cmp(&b.path));
+  reclaimable
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::common::test_util::{installed, temp_dir};
+
+  fn download(dir_name: &str) -> PackDownload {
+    let (pack_id, version) = match dir_name.split_once('@') {
+      Some((pack_id, version)) if !dir_name.starts_with('.') => {
+        (Some(pack_id.to_string()), Some(version.to_string()))
+      }
+      _ => (None, None),
+    };
+
+    PackDownload {
+      pack_id,
+      version,
+      path: PathBuf::from(dir_name),
+      size: 100,
+    }
+  }
+
+  #[test]
+  fn test_reclaimable_downloads() {
+    let downloads = [
+      "bar@1.9.0",
+      "bar@1.10.0",
+      "bar@1.2.0",
+      "bar@1.0.0",
+      "removed@1.0.0",
+      ".bar@2.0.0.tmp-1234",
+    ]
+    .map(download)
+    .to_vec();
+
+    // Keeps the 2 newest versions, plus the rolled back to version.
+    let reclaimable =
+      reclaimable_downloads(downloads, &[installed("bar", "1.0.0")], 2);
+
+    let paths = reclaimable
+      .iter()
+      .map(|download| download.path.to_string_lossy())
+      .collect::<Vec<_>>();
+
+    assert_eq!(
+      paths,
+      [".bar@2.0.0.tmp-1234", "bar@1.2.0", "removed@1.0.0"]
+    );
+  }
+
+  #[test]
+  fn test_read_download() {
+    let dir = temp_dir("installer-downloads");
+    let pack_dir = dir.join("bar@1.0.0");
+    let temp_pack_dir = dir.join(".bar@2.0.0.tmp-1234");
+
+    for path in [&pack_dir, &temp_pack_dir] {
+      fs::create_dir_all(path).unwrap();
+      fs::write(path.join("zpack.json"), "{}").unwrap();
+    }
+
+    fs::write(dir.join("file@1.0.0"), "").unwrap();
+
+    let now = SystemTime::now();
+    let later = now + TEMP_DIR_GRACE_PERIOD + Duration::from_secs(1);
+
+    assert_eq!(
+      read_download(pack_dir.clone(), now).unwrap(),
+      Some(PackDownload {
+        pack_id: Some("bar".to_string()),
+        version: Some("1.0.0".to_string()),
+        path: pack_dir,
+        size: 2,
+      })
+    );
+    assert_eq!(read_download(dir.join("file@1.0.0"), now).unwrap(), None);
+
+    // Temporary directories might still be written to by an install.
+    assert_eq!(read_download(temp_pack_dir.clone(), now).unwrap(), None);
+    assert_eq!(
+      read_download(temp_pack_dir.clone(), later)
+        .unwrap()
+        .map(|download| download.path),
+      Some(temp_pack_dir)
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+  }
+
+  #[test]
+  fn test_previous_version() {
+    let versions = ["1.0.0", "1.10.0", "1.9.0", "2.0.0"]
+      .map(String::from)
+      .to_vec();
+
+    assert_eq!(
+      previous_version(&versions, "1.10.0"),
+      Some("1.9.0".to_string())
+    );
+    assert_eq!(previous_version(&versions, "1.0.0"), None);
+  }
+
+  #[test]
+  fn test_archive_marker_path() {
+    assert_eq!(
+      archive_marker_path(Path::new("downloads/bar@1.0.0")),
+      PathBuf::from("downloads/bar@1.0.0.archive")
+    );
+  }
+
+  #[test]
+  fn test_is_valid_path_segment() {
+    assert!(is_valid_path_segment("glzr-io.starter"));
+    assert!(is_valid_path_segment("1.0.0-beta.1+build.2"));
+    assert!(!is_valid_path_segment(""));
+    assert!(!is_valid_path_segment(".."));
+    assert!(!is_valid_path_segment("../escape"));
+    assert!(!is_valid_path_segment("nested/pack"));
+    assert!(!is_valid_path_segment("C:\\pack"));
+  }
+
+  #[test]
+  fn test_validate_pack_ref() {
+    assert!(validate_pack_ref("glzr-io.starter", "1.0.0").is_ok());
+    assert!(validate_pack_ref("../../escape", "1.0.0").is_err());
+    assert!(validate_pack_ref("glzr-io.starter", "../1.0.0").is_err());
+    assert!(validate_pack_ref("glzr-io.starter", "").is_err());
+    assert!(validate_pack_ref("other@1.0.0", "1.0.0").is_err());
+  }
+}
@@ -0,0 +1,418 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::{bail, Context};
+use reqwest::{Client, StatusCode};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::{broadcast, Mutex};
+use tracing::{info, warn};
+
+use crate::{
+  app_settings::{AppSettings, UpdatePolicy},
+  marketplace_installer::{
+    MarketplaceInstaller, MarketplacePackMetadata, PackSource,
+  },
+  pack_archive::PackIntegrity,
+  widget_pack::WidgetPack,
+};
+
+/// Default URL of the marketplace API.
+pub const DEFAULT_MARKETPLACE_API_URL: &str = "https://api.glzr.io";
+
+/// Interval between automatic update checks.
+pub const UPDATE_CHECK_INTERVAL: Duration =
+  Duration::from_secs(6 * 60 * 60);
+
+/// Timeout for requests to the marketplace API.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Newer version of an installed marketplace pack.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackUpdate {
+  /// ID of the installed pack.
+  pub pack_id: String,
+
+  /// Version that's currently installed.
+  pub current_version: String,
+
+  /// Latest version on the marketplace.
+  pub latest_version: String,
+
+  /// URL to download the latest version from.
+  pub tarball_url: String,
+
+  /// Expected digest and signature of the tarball.
+  #[serde(default)]
+  pub integrity: PackIntegrity,
+}
+
+/// Widget pack as returned by the marketplace API.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MarketplacePack {
+  latest_version: String,
+  tarball_url: String,
+  tarball_sha256: Option<String>,
+  tarball_signature: Option<String>,
+  publisher_key: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrpcResponse<T> {
+  result: TrpcResult<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrpcResult<T> {
+  data: TrpcData<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrpcData<T> {
+  json: T,
+}
+
+/// Client for the marketplace API.
+#[derive(Debug)]
+pub struct MarketplaceApi {
+  api_url: String,
+  client: Client,
+}
+
+impl MarketplaceApi {
+  /// Creates a new `MarketplaceApi` instance.
+  pub fn new(api_url: &str) -> anyhow::Result<Self> {
+    Ok(Self {
+      api_url: api_url.trim_end_matches('/').to_string(),
+      client: Client::builder().timeout(REQUEST_TIMEOUT).build()?,
+    })
+  }
+
+  /// Gets the latest version of each installed pack that's newer than
+  /// the installed version.
+  ///
+  /// Packs installed from a tarball aren't checked. Packs that can't be
+  /// fetched (e.g. because they've been removed from the marketplace) are
+  /// skipped, as are versions that a pack was rolled back from.
+  pub async fn available_updates(
+    &self,
+    installed_packs: &[MarketplacePackMetadata],
+  ) -> Vec<PackUpdate> {
+    let mut updates = Vec::new();
+
+    let marketplace_packs = installed_packs
+      .iter()
+      .filter(|metadata| metadata.source == PackSource::Marketplace);
+
+    for metadata in marketplace_packs {
+      let pack = match self.pack(&metadata.pack_id).await {
+        Ok(pack) => pack,
+        Err(err) => {
+          warn!(
+            "Failed to check for updates to {}: {:?}",
+            metadata.pack_id, err
+          );
+          continue;
+        }
+      };
+
+      if is_newer(&pack.latest_version, &metadata.version)
+        && metadata.rolled_back_from.as_ref() != Some(&pack.latest_version)
+      {
+        updates.push(PackUpdate {
+          pack_id: metadata.pack_id.clone(),
+          current_version: metadata.version.clone(),
+          latest_version: pack.latest_version,
+          tarball_url: pack.tarball_url,
+          integrity: PackIntegrity {
+            sha256: pack.tarball_sha256,
+            signature: pack.tarball_signature,
+            publisher_key: pack.publisher_key,
+          },
+        });
+      }
+    }
+
+    updates
+  }
+
+  /// Fetches a pack by its ID.
+  async fn pack(&self, pack_id: &str) -> anyhow::Result<MarketplacePack> {
+    let input = serde_json::json!({ "json": { "id": pack_id } });
+
+    let response = self
+      .client
+      .get(format!(
+        "{}/v1/trpc/widgetPack.getByPublishedId",
+        self.api_url
+      ))
+      .query(&[("input", input.to_string())])
+      .send()
+      .await?;
+
+    if response.status() != StatusCode::OK {
+      bail!("Marketplace API returned HTTP {}.", response.status());
+    }
+
+    let response = response
+      .json::<TrpcResponse<MarketplacePack>>()
+      .await
+      .context("Invalid response from marketplace API.")?;
+
+    Ok(response.result.data.json)
+  }
+}
+
+/// Whether `latest` is a newer semver version than `current`.
+///
+/// Invalid versions are never considered newer.
+fn is_newer(latest: &str, current: &str) -> bool {
+  match (Version::parse(latest), Version::parse(current)) {
+    (Ok(latest), Ok(current)) => latest > current,
+    _ => false,
+  }
+}
+
+/// Checks for and installs updates to marketplace packs.
+#[derive(Debug)]
+pub struct MarketplaceUpdater {
+  /// Handle to the Tauri application.
+  app_handle: AppHandle,
+
+  /// Reference to `AppSettings`.
+  app_settings: Arc<AppSettings>,
+
+  /// Reference to `MarketplaceInstaller`.
+  marketplace_installer: Arc<MarketplaceInstaller>,
+
+  api: MarketplaceApi,
+
+  /// Updates found in the last check.
+  available_updates: Mutex<Vec<PackUpdate>>,
+
+  _updates_change_rx: broadcast::Receiver<Vec<PackUpdate>>,
+
+  pub updates_change_tx: broadcast::Sender<Vec<PackUpdate>>,
+}
+
+impl MarketplaceUpdater {
+  /// Creates a new `MarketplaceUpdater` instance.
+  ///
+  /// The API URL can be overridden with the `ZEBAR_MARKETPLACE_API_URL`
+  /// environment variable (e.g. for testing against a local API).
+  pub fn new(
+    app_handle: &AppHandle,
+    app_settings: Arc<AppSettings>,
+    marketplace_installer: Arc<MarketplaceInstaller>,
+  ) -> anyhow::Result<Self> {
+    let api_url = std::env::var("ZEBAR_MARKETPLACE_API_URL")
+      .unwrap_or(DEFAULT_MARKETPLACE_API_URL.to_string());
+
+    let (updates_change_tx, _updates_change_rx) = broadcast::channel(16);
+
+    Ok(Self {
+      app_handle: app_handle.clone(),
+      app_settings,
+      marketplace_installer,
+      api: MarketplaceApi::new(&api_url)?,
+      available_updates: Mutex::new(Vec::new()),
+      _updates_change_rx,
+      updates_change_tx,
+    })
+  }
+
+  /// Returns the updates found in the last check.
+  pub async fn available_updates(&self) -> Vec<PackUpdate> {
+    self.available_updates.lock().await.clone()
+  }
+
+  /// Checks the marketplace for updates to installed packs.
+  ///
+  /// Emits an `updates-available` event if any updates are found.
+  pub async fn check(&self) -> anyhow::Result<Vec<PackUpdate>> {
+    let installed_packs =
+      self.marketplace_installer.installed_packs_metadata()?;
+
+    let updates = self.api.available_updates(&installed_packs).await;
+    info!("Found {} widget pack update(s).", updates.len());
+
+    self.set_available_updates(updates.clone()).await;
+
+    if !updates.is_empty() {
+      self.app_handle.emit("updates-available", &updates)?;
+    }
+
+    Ok(updates)
+  }
+
+  /// Runs a scheduled update check according to the update policy.
+  pub async fn check_scheduled(&self) -> anyhow::Result<()> {
+    match self.app_settings.update_policy().await {
+      UpdatePolicy::Manual => Ok(()),
+      UpdatePolicy::Notify => self.check().await.map(|_| ()),
+      UpdatePolicy::Auto => {
+        self.check().await?;
+        self.install_all().await
+      }
+    }
+  }
+
+  /// Installs the available update for a pack.
+  ///
+  /// Open widgets from the pack are relaunched once the new version is
+  /// registered.
+  pub async fn install(
+    &self,
+    pack_id: &str,
+  ) -> anyhow::Result<WidgetPack> {
+    let update = self
+      .available_updates()
+      .await
+      .into_iter()
+      .find(|update| update.pack_id == pack_id)
+      .with_context(|| {
+        format!("No update available for '{}'.", pack_id)
+      })?;
+
+    info!(
+      "Updating widget pack {} from {} to {}.",
+      pack_id, update.current_version, update.latest_version
+    );
+
+    let pack = self
+      .marketplace_installer
+      .install(
+        pack_id,
+        &update.latest_version,
+        &update.tarball_url,
+        &update.integrity,
+        false,
+      )
+      .await?;
+
+    let mut updates = self.available_updates().await;
+    updates.retain(|update| update.pack_id != pack_id);
+    self.set_available_updates(updates).await;
+
+    Ok(pack)
+  }
+
+  /// Installs all available updates.
+  ///
+  /// A failed update doesn't prevent the remaining ones from being
+  /// installed. Fails afterwards if any of them failed.
+  pub async fn install_all(&self) -> anyhow::Result<()> {
+    let mut failed_count = 0;
+
+    for update in self.available_updates().await {
+      if let Err(err) = self.install(&update.pack_id).await {
+        warn!(
+          "Failed to update {} to {}: {:?}",
+          update.pack_id, update.latest_version, err
+        );
+
+        failed_count += 1;
+      }
+    }
+
+    if failed_count > 0 {
+      bail!("Failed to install {} widget pack update(s).", failed_count);
+    }
+
+    Ok(())
+  }
+
+  async fn set_available_updates(&self, updates: Vec<PackUpdate>) {
+    *self.available_updates.lock().await = updates.clone();
+
+    // Broadcast the change.
+    let _ = self.updates_change_tx.send(updates);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::common::test_util::{
+    http_response, installed, spawn_http_server,
+  };
+
+  /// Local stand-in for the marketplace API, serving the given latest
+  /// versions by pack ID. Unknown packs respond with `404 Not Found`.
+  fn spawn_api(packs: &'static [(&'static str, &'static str)]) -> String {
+    spawn_http_server(move |request| {
+      let pack = packs.iter().find(|(pack_id, _)| {
+        request.contains(&format!("%22{}%22", pack_id))
+      });
+
+      match pack {
+        Some((pack_id, version)) => {
+          let body = serde_json::json!({
+            "result": { "data": { "json": {
+              "publishedId": pack_id,
+              "latestVersion": version,
+              "tarballUrl": format!("https://example.com/{pack_id}.tar.gz"),
+              "tarballSha256": "deadbeef",
+            }}}
+          })
+          .to_string();
+
+          http_response(
+            "200 OK",
+            &[("Content-Type", "application/json")],
+            &body,
+          )
+        }
+        None => http_response("404 Not Found", &[], ""),
+      }
+    })
+  }
+
+  #[test]
+  fn test_is_newer() {
+    assert!(is_newer("1.10.0", "1.9.0"));
+    assert!(is_newer("2.0.0", "2.0.0-beta.1"));
+    assert!(!is_newer("1.0.0", "1.0.0"));
+    assert!(!is_newer("0.9.0", "1.0.0"));
+    assert!(!is_newer("latest", "1.0.0"));
+  }
+
+  #[tokio::test]
+  async fn test_available_updates() {
+    let api_url = spawn_api(&[
+      ("glzr-io.starter", "1.2.0"),
+      ("someone.clock", "2.0.0"),
+    ]);
+
+    let api = MarketplaceApi::new(&api_url).unwrap();
+
+    let updates = api
+      .available_updates(&[
+        installed("glzr-io.starter", "1.0.0"),
+        installed("someone.clock", "2.0.0"),
+        installed("someone.removed", "1.0.0"),
+        MarketplacePackMetadata {
+          source: PackSource::Archive,
+          ..installed("someone.clock", "1.0.0")
+        },
+      ])
+      .await;
+
+    assert_eq!(
+      updates,
+      vec![PackUpdate {
+        pack_id: "glzr-io.starter".to_string(),
+        current_version: "1.0.0".to_string(),
+        latest_version: "1.2.0".to_string(),
+        tarball_url: "https://example.com/glzr-io.starter.tar.gz"
+          .to_string(),
+        integrity: PackIntegrity {
+          sha256: Some("deadbeef".to_string()),
+          ..Default::default()
+        },
+      }]
+    );
+  }
+}
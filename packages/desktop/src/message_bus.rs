@@ -0,0 +1,410 @@
+use std::{
+  collections::HashMap,
+  fmt,
+  sync::{Arc, Mutex},
+};
+
+use anyhow::Context;
+use serde::Serialize;
+use tauri::ipc::Channel;
+
+use crate::widget_factory::WidgetFactory;
+
+/// Message published by a widget.
+///
+/// Sent to subscribed widgets via the channel of their subscription.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BusMessage {
+  /// Topic that the message was published to (without the pack
+  /// namespace).
+  pub topic: String,
+
+  /// Arbitrary JSON payload.
+  pub payload: serde_json::Value,
+
+  /// ID of the widget that published the message.
+  pub sender_id: String,
+}
+
+/// A widget's subscription to a topic.
+///
+/// Messages are sent over the IPC channel that the widget passed when
+/// subscribing, rather than as events, which any widget could listen to.
+#[derive(Clone)]
+struct Subscription {
+  widget_id: String,
+  channel: Channel<BusMessage>,
+}
+
+impl fmt::Debug for Subscription {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Subscription")
+      .field("widget_id", &self.widget_id)
+      .field("channel_id", &self.channel.id())
+      .finish()
+  }
+}
+
+/// Subscriptions and retained messages by namespaced topic.
+#[derive(Debug, Default)]
+struct Topics {
+  /// Map of namespaced topics to their subscriptions.
+  subscribers: HashMap<String, Vec<Subscription>>,
+
+  /// Map of namespaced topics to their last retained message.
+  retained: HashMap<String, BusMessage>,
+}
+
+impl Topics {
+  /// Subscribes a widget to the topic.
+  ///
+  /// Returns the retained message of the topic (if any).
+  fn subscribe(
+    &mut self,
+    topic: &str,
+    subscription: Subscription,
+  ) -> Option<BusMessage> {
+    self
+      .subscribers
+      .entry(topic.to_string())
+      .or_default()
+      .push(subscription);
+
+    self.retained.get(topic).cloned()
+  }
+
+  /// Removes the widget's subscription to the topic with the given
+  /// channel ID.
+  fn unsubscribe(
+    &mut self,
+    topic: &str,
+    widget_id: &str,
+    channel_id: u32,
+  ) {
+    if let Some(subscriptions) = self.subscribers.get_mut(topic) {
+      subscriptions.retain(|subscription| {
+        subscription.widget_id != widget_id
+          || subscription.channel.id() != channel_id
+      });
+
+      if subscriptions.is_empty() {
+        self.subscribers.remove(topic);
+      }
+    }
+  }
+
+  /// Unsubscribes a widget from all topics.
+  fn remove_widget(&mut self, widget_id: &str) {
+    self.subscribers.retain(|_, subscriptions| {
+      subscriptions
+        .retain(|subscription| subscription.widget_id != widget_id);
+      !subscriptions.is_empty()
+    });
+  }
+
+  /// Publishes a message to the topic, and optionally retains it for
+  /// future subscribers. Retaining a `null` payload clears the retained
+  /// message.
+  ///
+  /// Returns the subscriptions to the topic.
+  fn publish(
+    &mut self,
+    topic: &str,
+    message: &BusMessage,
+    retain: bool,
+  ) -> Vec<Subscription> {
+    if retain {
+      match message.payload.is_null() {
+        true => self.retained.remove(topic),
+        false => self.retained.insert(topic.to_string(), message.clone()),
+      };
+    }
+
+    self.subscribers.get(topic).cloned().unwrap_or_default()
+  }
+}
+
+/// Topic-based pub/sub for communication between widgets.
+///
+/// Topics are namespaced by widget pack, such that widgets can only
+/// communicate with other widgets from the same pack.
+#[derive(Debug)]
+pub struct MessageBus {
+  topics: Mutex<Topics>,
+  widget_factory: Arc<WidgetFactory>,
+}
+
+impl MessageBus {
+  /// Creates a new `MessageBus` instance.
+  pub fn new(widget_factory: Arc<WidgetFactory>) -> Self {
+    Self {
+      topics: Mutex::new(Topics::default()),
+      widget_factory,
+    }
+  }
+
+  /// Publishes a message to widgets from the same pack that are
+  /// subscribed to the topic.
+  pub async fn publish(
+    &self,
+    widget_id: &str,
+    topic: &str,
+    payload: serde_json::Value,
+    retain: bool,
+  ) -> anyhow::Result<()> {
+    let pack_id = self.pack_id(widget_id).await?;
+
+    let message = BusMessage {
+      topic: topic.to_string(),
+      payload,
+      sender_id: widget_id.to_string(),
+    };
+
+    let subscriptions = self.topics.lock().unwrap().publish(
+      &namespaced_topic(&pack_id, topic),
+      &message,
+      retain,
+    );
+
+    // Only send to widgets that are still open.
+    let widget_states = self.widget_factory.states().await;
+
+    for subscription in subscriptions {
+      if widget_states
+        .get(&subscription.widget_id)
+        .is_some_and(|state| state.pack_id == pack_id)
+      {
+        send_message(&subscription.channel, &message)?;
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Subscribes a widget to the topic, sending its messages over the
+  /// given channel.
+  ///
+  /// The topic's retained message is sent to the channel right away.
+  pub async fn subscribe(
+    &self,
+    widget_id: &str,
+    topic: &str,
+    channel: Channel<BusMessage>,
+  ) -> anyhow::Result<()> {
+    let pack_id = self.pack_id(widget_id).await?;
+
+    let retained = self.topics.lock().unwrap().subscribe(
+      &namespaced_topic(&pack_id, topic),
+      Subscription {
+        widget_id: widget_id.to_string(),
+        channel: channel.clone(),
+      },
+    );
+
+    if let Some(message) = retained {
+      send_message(&channel, &message)?;
+    }
+
+    Ok(())
+  }
+
+  /// Removes the widget's subscription to the topic with the given
+  /// channel ID.
+  pub async fn unsubscribe(
+    &self,
+    widget_id: &str,
+    topic: &str,
+    channel_id: u32,
+  ) -> anyhow::Result<()> {
+    let pack_id = self.pack_id(widget_id).await?;
+
+    self.topics.lock().unwrap().unsubscribe(
+      &namespaced_topic(&pack_id, topic),
+      widget_id,
+      channel_id,
+    );
+
+    Ok(())
+  }
+
+  /// Unsubscribes a closed widget from all topics.
+  pub fn remove_widget(&self, widget_id: &str) {
+    self.topics.lock().unwrap().remove_widget(widget_id);
+  }
+
+  /// Gets the ID of the pack that the widget belongs to.
+  async fn pack_id(&self, widget_id: &str) -> anyhow::Result<String> {
+    let widget = self
+      .widget_factory
+      .state_by_id(widget_id)
+      .await
+      .with_context(|| {
+        format!("Widget with ID '{widget_id}' not found.")
+      })?;
+
+    Ok(widget.pack_id)
+  }
+}
+
+/// Gets the topic prefixed by the pack ID.
+fn namespaced_topic(pack_id: &str, topic: &str) -> String {
+  format!("{pack_id}/{topic}")
+}
+
+/// Sends a message over a subscription's channel.
+fn send_message(
+  channel: &Channel<BusMessage>,
+  message: &BusMessage,
+) -> anyhow::Result<()> {
+  channel.send(message.clone())?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+
+  fn message(payload: serde_json::Value) -> BusMessage {
+    BusMessage {
+      topic: "calendar/tab".to_string(),
+      payload,
+      sender_id: "widget-1".to_string(),
+    }
+  }
+
+  fn subscription(widget_id: &str) -> Subscription {
+    Subscription {
+      widget_id: widget_id.to_string(),
+      channel: Channel::new(|_| Ok(())),
+    }
+  }
+
+  fn widget_ids(subscriptions: &[Subscription]) -> Vec<&str> {
+    let mut widget_ids = subscriptions
+      .iter()
+      .map(|subscription| subscription.widget_id.as_str())
+      .collect::<Vec<_>>();
+
+    widget_ids.sort();
+    widget_ids
+  }
+
+  #[test]
+  fn test_publish() {
+    let mut topics = Topics::default();
+    let topic = namespaced_topic("glzr-io.starter", "calendar/tab");
+
+    let widget_2 = subscription("widget-2");
+    topics.subscribe(&topic, widget_2.clone());
+    topics.subscribe(&topic, subscription("widget-3"));
+    topics.subscribe(&topic, subscription("widget-3"));
+    topics.subscribe("glzr-io.starter/other", subscription("widget-4"));
+
+    let subscriptions =
+      topics.publish(&topic, &message(json!("month")), false);
+
+    assert_eq!(
+      widget_ids(&subscriptions),
+      ["widget-2", "widget-3", "widget-3"]
+    );
+
+    // Only the subscription with the given channel is removed.
+    topics.unsubscribe(&topic, "widget-3", widget_2.channel.id());
+    topics.unsubscribe(&topic, "widget-2", widget_2.channel.id());
+
+    let subscriptions =
+      topics.publish(&topic, &message(json!("month")), false);
+
+    assert_eq!(widget_ids(&subscriptions), ["widget-3", "widget-3"]);
+
+    topics.remove_widget("widget-3");
+
+    assert!(topics
+      .publish(&topic, &message(json!("month")), false)
+      .is_empty());
+    assert!(!topics.subscribers.contains_key(&topic));
+  }
+
+  #[test]
+  fn test_namespaced_topics() {
+    let mut topics = Topics::default();
+
+    // Packs can't receive messages published to another pack's topic.
+    topics.subscribe(
+      &namespaced_topic("other.pack", "tab"),
+      subscription("widget-2"),
+    );
+
+    assert!(topics
+      .publish(
+        &namespaced_topic("glzr-io.starter", "tab"),
+        &message(json!(1)),
+        true
+      )
+      .is_empty());
+
+    assert_eq!(
+      topics.subscribe(
+        &namespaced_topic("other.pack", "tab"),
+        subscription("widget-3")
+      ),
+      None
+    );
+  }
+
+  #[test]
+  fn test_retained_message() {
+    let mut topics = Topics::default();
+
+    topics.publish("pack/tab", &message(json!("week")), true);
+    topics.publish("pack/tab", &message(json!("month")), false);
+
+    // Only retained messages are sent to new subscribers.
+    assert_eq!(
+      topics.subscribe("pack/tab", subscription("widget-2")),
+      Some(message(json!("week")))
+    );
+
+    // Retaining a `null` payload clears the retained message.
+    topics.publish("pack/tab", &message(json!(null)), true);
+    assert_eq!(
+      topics.subscribe("pack/tab", subscription("widget-3")),
+      None
+    );
+  }
+
+  #[test]
+  fn test_send_message() {
+    use tauri::{Listener, WebviewUrl, WebviewWindowBuilder};
+
+    let app = tauri::test::mock_app();
+    let (received_tx, received_rx) = std::sync::mpsc::channel();
+
+    for widget_id in ["widget-1", "widget-2"] {
+      WebviewWindowBuilder::new(&app, widget_id, WebviewUrl::default())
+        .build()
+        .unwrap();
+    }
+
+    // Any widget can listen to events of any target, so messages must
+    // not be emitted as events.
+    let any_tx = received_tx.clone();
+    app.listen_any("bus-message", move |_| {
+      let _ = any_tx.send("any");
+    });
+
+    let channel_tx = received_tx.clone();
+    let channel = Channel::new(move |_| {
+      let _ = channel_tx.send("widget-1");
+      Ok(())
+    });
+
+    send_message(&channel, &message(json!("month"))).unwrap();
+
+    assert_eq!(received_rx.try_iter().collect::<Vec<_>>(), ["widget-1"]);
+  }
+}
@@ -3,18 +3,32 @@ use std::{sync::Arc, time::Duration};
 use serde::Serialize;
 use tauri::AppHandle;
 use tokio::{
-  sync::{broadcast, RwLock},
+  sync::{broadcast, mpsc, RwLock},
   task,
 };
-use tracing::info;
+use tracing::{info, warn};
 
+#[cfg(target_os = "linux")]
+use crate::common::linux::listen_display_changes;
+#[cfg(target_os = "macos")]
+use crate::common::macos::listen_display_changes;
+#[cfg(target_os = "windows")]
+use crate::common::windows::listen_display_changes;
 use crate::widget_pack::MonitorSelection;
 
+/// Delay before re-reading monitors after a display change, so that a
+/// burst of notifications results in a single update.
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(250);
+
+/// Interval for polling monitors if display change notifications are
+/// unavailable.
+const FALLBACK_POLL_INTERVAL: Duration = Duration::from_secs(4);
+
 #[derive(Debug)]
 pub struct MonitorState {
-  _change_rx: broadcast::Receiver<Vec<Monitor>>,
+  _change_rx: broadcast::Receiver<MonitorEvent>,
 
-  pub change_tx: broadcast::Sender<Vec<Monitor>>,
+  pub change_tx: broadcast::Sender<MonitorEvent>,
 
   /// Available monitors sorted from left-to-right and top-to-bottom.
   monitors: Arc<RwLock<Vec<Monitor>>>,
@@ -32,17 +46,115 @@ pub struct Monitor {
   pub scale_factor: f32,
 }
 
+/// Usable area of a monitor, excluding the taskbar, dock, and any other
+/// reserved space.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkArea {
+  pub monitor_name: Option<String>,
+  pub x: i32,
+  pub y: i32,
+  pub width: u32,
+  pub height: u32,
+}
+
+#[derive(Clone, Debug)]
+pub enum MonitorEvent {
+  /// Monitors were connected, disconnected, or changed geometry or
+  /// scale factor.
+  MonitorsChanged(Vec<Monitor>),
+
+  /// Work areas changed while the monitors themselves stayed the same
+  /// (e.g. the taskbar was moved or resized).
+  WorkAreasChanged(Vec<WorkArea>),
+}
+
+impl Monitor {
+  /// Whether both monitors refer to the same physical display.
+  ///
+  /// Monitors are matched by name, or by position if they are unnamed.
+  pub fn is_same_display(&self, other: &Monitor) -> bool {
+    match (&self.name, &other.name) {
+      (Some(name), Some(other_name)) => name == other_name,
+      (None, None) => self.x == other.x && self.y == other.y,
+      _ => false,
+    }
+  }
+}
+
+/// Difference between an old and new list of monitors.
+#[derive(Debug, Default, PartialEq)]
+pub struct MonitorDiff {
+  /// Monitors that are identical in both lists.
+  pub unchanged: Vec<Monitor>,
+
+  /// Monitors whose geometry or scale factor changed.
+  ///
+  /// Tuple of `(old_monitor, new_monitor)`.
+  pub changed: Vec<(Monitor, Monitor)>,
+
+  /// Monitors that are only in the new list.
+  pub added: Vec<Monitor>,
+
+  /// Monitors that are only in the old list.
+  pub removed: Vec<Monitor>,
+}
+
+impl MonitorDiff {
+  /// Compares the old and new list of monitors.
+  pub fn new(old_monitors: &[Monitor], new_monitors: &[Monitor]) -> Self {
+    let mut diff = Self::default();
+    let mut remaining = new_monitors.to_vec();
+    let mut unmatched = Vec::new();
+
+    // Match identical monitors first, so that a monitor isn't paired with
+    // a different display that happens to share its name.
+    for old_monitor in old_monitors {
+      match remaining.iter().position(|monitor| monitor == old_monitor) {
+        Some(index) => diff.unchanged.push(remaining.remove(index)),
+        None => unmatched.push(old_monitor.clone()),
+      }
+    }
+
+    for old_monitor in unmatched {
+      match remaining
+        .iter()
+        .position(|monitor| monitor.is_same_display(&old_monitor))
+      {
+        Some(index) => {
+          diff.changed.push((old_monitor, remaining.remove(index)))
+        }
+        None => diff.removed.push(old_monitor),
+      }
+    }
+
+    diff.added = remaining;
+    diff
+  }
+}
+
 impl MonitorState {
   /// Creates a new `MonitorState` instance.
   pub fn new(app_handle: &AppHandle) -> Self {
     let (change_tx, _change_rx) = broadcast::channel(16);
+    let (monitors, work_areas) = Self::available_monitors(app_handle);
+    let monitors = Arc::new(RwLock::new(monitors));
 
-    let monitors =
-      Arc::new(RwLock::new(Self::available_monitors(app_handle)));
+    let (display_tx, display_rx) = mpsc::unbounded_channel();
+
+    let display_rx = match listen_display_changes(app_handle, display_tx) {
+      Ok(()) => Some(display_rx),
+      Err(err) => {
+        warn!("Falling back to polling for monitor changes: {:?}", err);
+        None
+      }
+    };
 
     Self::listen_changes(
       app_handle.clone(),
       monitors.clone(),
+      work_areas,
+      display_rx,
       change_tx.clone(),
     );
 
@@ -56,32 +168,62 @@ impl MonitorState {
   /// Listens for display setting changes.
   ///
   /// Updates monitor state on scaling changes, monitor connections, and
-  /// monitor disconnections. Does not update on working area changes.
+  /// monitor disconnections. Work area changes are emitted as a separate
+  /// event.
+  ///
+  /// Changes are read on notifications from the OS, and monitors are
+  /// only polled if no notifications are available.
   fn listen_changes(
     app_handle: AppHandle,
     monitors: Arc<RwLock<Vec<Monitor>>>,
-    change_tx: broadcast::Sender<Vec<Monitor>>,
+    mut work_areas: Vec<WorkArea>,
+    mut display_rx: Option<mpsc::UnboundedReceiver<()>>,
+    change_tx: broadcast::Sender<MonitorEvent>,
   ) {
     task::spawn(async move {
-      let mut interval = tokio::time::interval(Duration::from_secs(4));
+      let mut interval = tokio::time::interval(FALLBACK_POLL_INTERVAL);
 
       interval
         .set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
       loop {
-        interval.tick().await;
-        let new_monitors = Self::available_monitors(&app_handle);
+        match display_rx.as_mut() {
+          Some(rx) => {
+            if rx.recv().await.is_none() {
+              warn!("Display listener stopped, falling back to polling.");
+              display_rx = None;
+              continue;
+            }
+
+            // Wait for the burst of notifications to settle.
+            tokio::time::sleep(DEBOUNCE_DELAY).await;
+            while rx.try_recv().is_ok() {}
+          }
+          None => {
+            interval.tick().await;
+          }
+        }
 
-        let should_update = {
+        let (new_monitors, new_work_areas) =
+          Self::available_monitors(&app_handle);
+
+        let monitors_changed = {
           let current_monitors = monitors.read().await;
           *current_monitors != new_monitors
         };
 
-        if should_update {
+        if monitors_changed {
           info!("Detected change in monitors.");
           *monitors.write().await = new_monitors.clone();
-          let _ = change_tx.send(new_monitors);
+          let _ =
+            change_tx.send(MonitorEvent::MonitorsChanged(new_monitors));
+        } else if work_areas != new_work_areas {
+          info!("Detected change in work areas.");
+          let _ = change_tx
+            .send(MonitorEvent::WorkAreasChanged(new_work_areas.clone()));
         }
+
+        work_areas = new_work_areas;
       }
     });
   }
@@ -89,30 +231,29 @@ impl MonitorState {
   /// Gets available monitors on the system.
   ///
   /// Returns a vector of `Monitor` instances sorted from left-to-right and
-  /// top-to-bottom.
-  fn available_monitors(app_handle: &AppHandle) -> Vec<Monitor> {
+  /// top-to-bottom, along with the work area of each monitor.
+  fn available_monitors(
+    app_handle: &AppHandle,
+  ) -> (Vec<Monitor>, Vec<WorkArea>) {
     let primary_monitor = app_handle.primary_monitor().unwrap_or(None);
+    let available_monitors =
+      app_handle.available_monitors().unwrap_or(Vec::new());
 
-    let mut monitors = app_handle
-      .available_monitors()
-      .map(|monitors| {
-        monitors
-          .into_iter()
-          .map(|monitor| Monitor {
-            name: monitor.name().cloned(),
-            is_primary: primary_monitor
-              .as_ref()
-              .map(|m| m.name() == monitor.name())
-              .unwrap_or(false),
-            x: monitor.position().x,
-            y: monitor.position().y,
-            width: monitor.size().width,
-            height: monitor.size().height,
-            scale_factor: monitor.scale_factor() as f32,
-          })
-          .collect()
+    let mut monitors = available_monitors
+      .iter()
+      .map(|monitor| Monitor {
+        name: monitor.name().cloned(),
+        is_primary: primary_monitor
+          .as_ref()
+          .map(|m| m.name() == monitor.name())
+          .unwrap_or(false),
+        x: monitor.position().x,
+        y: monitor.position().y,
+        width: monitor.size().width,
+        height: monitor.size().height,
+        scale_factor: monitor.scale_factor() as f32,
       })
-      .unwrap_or(Vec::new());
+      .collect::<Vec<_>>();
 
     // Sort monitors from left-to-right, top-to-bottom.
     monitors.sort_by(|monitor_a, monitor_b| {
@@ -123,7 +264,22 @@ impl MonitorState {
       }
     });
 
-    monitors
+    let work_areas = available_monitors
+      .iter()
+      .map(|monitor| {
+        let work_area = monitor.work_area();
+
+        WorkArea {
+          monitor_name: monitor.name().cloned(),
+          x: work_area.position.x,
+          y: work_area.position.y,
+          width: work_area.size.width,
+          height: work_area.size.height,
+        }
+      })
+      .collect();
+
+    (monitors, work_areas)
   }
 
   /// Returns a string representation of the monitors.
@@ -162,3 +318,88 @@ impl MonitorState {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn monitor(name: Option<&str>, x: i32, scale_factor: f32) -> Monitor {
+    Monitor {
+      name: name.map(String::from),
+      is_primary: x == 0,
+      x,
+      y: 0,
+      width: 1920,
+      height: 1080,
+      scale_factor,
+    }
+  }
+
+  #[test]
+  fn test_diff_unchanged() {
+    let monitors = vec![
+      monitor(Some("DISPLAY1"), 0, 1.0),
+      monitor(Some("DISPLAY2"), 1920, 1.0),
+    ];
+
+    let diff = MonitorDiff::new(&monitors, &monitors);
+
+    assert_eq!(diff.unchanged, monitors);
+    assert!(diff.changed.is_empty());
+    assert!(diff.added.is_empty());
+    assert!(diff.removed.is_empty());
+  }
+
+  #[test]
+  fn test_diff_scale_factor_change() {
+    let old = vec![
+      monitor(Some("DISPLAY1"), 0, 1.0),
+      monitor(Some("DISPLAY2"), 1920, 1.0),
+    ];
+
+    let new = vec![
+      monitor(Some("DISPLAY1"), 0, 1.0),
+      monitor(Some("DISPLAY2"), 1920, 1.5),
+    ];
+
+    let diff = MonitorDiff::new(&old, &new);
+
+    assert_eq!(diff.unchanged, vec![old[0].clone()]);
+    assert_eq!(diff.changed, vec![(old[1].clone(), new[1].clone())]);
+    assert!(diff.added.is_empty());
+    assert!(diff.removed.is_empty());
+  }
+
+  #[test]
+  fn test_diff_hotplug() {
+    let old = vec![
+      monitor(Some("DISPLAY1"), 0, 1.0),
+      monitor(Some("DISPLAY2"), 1920, 1.0),
+    ];
+
+    let new = vec![
+      monitor(Some("DISPLAY1"), 0, 1.0),
+      monitor(Some("DISPLAY3"), -1920, 1.0),
+    ];
+
+    let diff = MonitorDiff::new(&old, &new);
+
+    assert_eq!(diff.unchanged, vec![old[0].clone()]);
+    assert!(diff.changed.is_empty());
+    assert_eq!(diff.added, vec![new[1].clone()]);
+    assert_eq!(diff.removed, vec![old[1].clone()]);
+  }
+
+  #[test]
+  fn test_diff_unnamed_monitors() {
+    let old = vec![monitor(None, 0, 1.0), monitor(None, 1920, 1.0)];
+    let new = vec![monitor(None, 0, 2.0), monitor(None, 3840, 1.0)];
+
+    let diff = MonitorDiff::new(&old, &new);
+
+    // Unnamed monitors are matched by position.
+    assert_eq!(diff.changed, vec![(old[0].clone(), new[0].clone())]);
+    assert_eq!(diff.added, vec![new[1].clone()]);
+    assert_eq!(diff.removed, vec![old[1].clone()]);
+  }
+}
@@ -0,0 +1,392 @@
+use std::{
+  fs,
+  io::Read,
+  path::{Component, Path},
+};
+
+use anyhow::{bail, Context};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use flate2::read::GzDecoder;
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tar::{Archive, EntryType};
+
+/// Maximum size of a downloaded widget pack tarball.
+pub const MAX_DOWNLOAD_SIZE: u64 = 50 * 1024 * 1024;
+
+/// Maximum total size of the files in a widget pack tarball.
+pub const MAX_UNPACKED_SIZE: u64 = 200 * 1024 * 1024;
+
+/// Expected digest and signature of a widget pack tarball, as provided by
+/// the marketplace.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackIntegrity {
+  /// Hex-encoded SHA-256 digest of the tarball.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub sha256: Option<String>,
+
+  /// Base64-encoded Ed25519 signature of the tarball.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub signature: Option<String>,
+
+  /// Base64-encoded Ed25519 public key of the pack's publisher.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub publisher_key: Option<String>,
+}
+
+/// Verifies a tarball against its expected digest and signature.
+///
+/// `pinned_key` is the publisher key of a previously installed version of
+/// the pack. Once a pack has been installed with a signature, later
+/// versions must be signed with the same key.
+pub fn verify(
+  bytes: &[u8],
+  integrity: &PackIntegrity,
+  pinned_key: Option<&str>,
+) -> anyhow::Result<()> {
+  match &integrity.sha256 {
+    Some(expected) => {
+      let digest = sha256_hex(bytes);
+
+      if !digest.eq_ignore_ascii_case(expected.trim()) {
+        bail!(
+          "SHA-256 mismatch: expected {}, got {}.",
+          expected.trim(),
+          digest
+        );
+      }
+    }
+    None => tracing::warn!("No SHA-256 digest provided for tarball."),
+  }
+
+  if let (Some(pinned_key), Some(key)) =
+    (pinned_key, &integrity.publisher_key)
+  {
+    if pinned_key != key {
+      bail!(
+        "Publisher key differs from the previously installed version."
+      );
+    }
+  }
+
+  let key = integrity.publisher_key.as_deref().or(pinned_key);
+
+  match (&integrity.signature, key) {
+    (Some(signature), Some(key)) => {
+      verify_signature(bytes, signature, key)
+    }
+    (Some(_), None) => {
+      bail!("Tarball is signed but has no publisher key.")
+    }
+    (None, Some(_)) => {
+      bail!("Tarball from a signed publisher is unsigned.")
+    }
+    (None, None) => Ok(()),
+  }
+}
+
+/// Gets the hex-encoded SHA-256 digest of the given bytes.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+  Sha256::digest(bytes)
+    .iter()
+    .map(|byte| format!("{:02x}", byte))
+    .collect()
+}
+
+fn verify_signature(
+  bytes: &[u8],
+  signature: &str,
+  key: &str,
+) -> anyhow::Result<()> {
+  let signature = STANDARD
+    .decode(signature.trim())
+    .context("Signature is not valid base64.")?;
+
+  let key = STANDARD
+    .decode(key.trim())
+    .context("Publisher key is not valid base64.")?;
+
+  UnparsedPublicKey::new(&ED25519, key)
+    .verify(bytes, &signature)
+    .map_err(|_| anyhow::anyhow!("Invalid tarball signature."))
+}
+
+/// Extracts a gzipped tarball to the given directory.
+///
+/// Entries are extracted to a temporary sibling directory, which is
+/// renamed to `dest_dir` once extraction succeeds. Fails if `dest_dir`
+/// already exists.
+///
+/// Only regular files and directories with relative paths are allowed.
+/// Symlinks, hardlinks, and device entries are rejected.
+pub fn extract(
+  bytes: &[u8],
+  dest_dir: &Path,
+  max_unpacked_size: u64,
+) -> anyhow::Result<()> {
+  let dir_name = dest_dir
+    .file_name()
+    .context("Invalid extraction directory.")?
+    .to_string_lossy();
+
+  let temp_dir = dest_dir.with_file_name(format!(
+    ".{}.tmp-{}",
+    dir_name,
+    uuid::Uuid::new_v4()
+  ));
+
+  fs::create_dir_all(&temp_dir)?;
+
+  let res =
+    unpack_entries(GzDecoder::new(bytes), &temp_dir, max_unpacked_size)
+      .and_then(|_| {
+        fs::rename(&temp_dir, dest_dir).with_context(|| {
+          format!("Failed to move pack to {}.", dest_dir.display())
+        })
+      });
+
+  if res.is_err() {
+    let _ = fs::remove_dir_all(&temp_dir);
+  }
+
+  res
+}
+
+/// Reads a file from a gzipped tarball without extracting it.
+pub fn read_file(
+  bytes: &[u8],
+  file_path: &str,
+) -> anyhow::Result<Vec<u8>> {
+  let mut archive = Archive::new(GzDecoder::new(bytes));
+
+  // Compare without `.` components (e.g. `./zpack.json`).
+  fn normalize(path: &Path) -> Vec<Component<'_>> {
+    path
+      .components()
+      .filter(|component| *component != Component::CurDir)
+      .collect()
+  }
+
+  let file_path = Path::new(file_path);
+
+  for entry in archive.entries()? {
+    let mut entry = entry?;
+
+    if entry.header().entry_type() == EntryType::Regular
+      && normalize(&entry.path()?) == normalize(file_path)
+    {
+      let mut contents = Vec::new();
+      entry.read_to_end(&mut contents)?;
+      return Ok(contents);
+    }
+  }
+
+  bail!("No '{}' file found in tarball.", file_path.display())
+}
+
+fn unpack_entries(
+  reader: impl Read,
+  dir: &Path,
+  max_unpacked_size: u64,
+) -> anyhow::Result<()> {
+  let mut archive = Archive::new(reader);
+  let mut unpacked_size = 0;
+
+  for entry in archive.entries()? {
+    let mut entry = entry?;
+    let path = entry.path()?.into_owned();
+
+    match entry.header().entry_type() {
+      EntryType::Regular
+      | EntryType::Continuous
+      | EntryType::Directory => {}
+      // Extended headers only hold metadata for other entries.
+      EntryType::XGlobalHeader | EntryType::XHeader => continue,
+      entry_type => {
+        bail!(
+          "Unsupported entry type {:?} in tarball: {}",
+          entry_type,
+          path.display()
+        )
+      }
+    }
+
+    let is_relative = path.components().all(|component| {
+      matches!(component, Component::Normal(_) | Component::CurDir)
+    });
+
+    if !is_relative {
+      bail!("Invalid path in tarball: {}", path.display());
+    }
+
+    unpacked_size += entry.size();
+
+    if unpacked_size > max_unpacked_size {
+      bail!(
+        "Tarball exceeds maximum unpacked size of {} bytes.",
+        max_unpacked_size
+      );
+    }
+
+    entry.unpack_in(dir)?;
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use flate2::{write::GzEncoder, Compression};
+  use ring::{
+    rand::SystemRandom,
+    signature::{Ed25519KeyPair, KeyPair},
+  };
+
+  use super::*;
+  use crate::common::test_util::temp_dir;
+
+  /// Creates a gzipped tarball from `(path, entry type, contents)`.
+  fn tarball(entries: &[(&str, EntryType, &[u8])]) -> Vec<u8> {
+    let encoder = GzEncoder::new(Vec::new(), Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+
+    for (path, entry_type, contents) in entries {
+      let mut header = tar::Header::new_gnu();
+      header.set_entry_type(*entry_type);
+      header.set_size(contents.len() as u64);
+      header.set_mode(0o644);
+
+      // Bypass the path validation of `set_path` to allow writing
+      // malicious paths.
+      let name = &mut header.as_old_mut().name;
+      name[..path.len()].copy_from_slice(path.as_bytes());
+
+      if *entry_type == EntryType::Symlink {
+        header.set_link_name("/etc/passwd").unwrap();
+      }
+
+      header.set_cksum();
+      builder.append(&header, *contents).unwrap();
+    }
+
+    builder.into_inner().unwrap().finish().unwrap()
+  }
+
+  #[test]
+  fn test_verify_sha256() {
+    let bytes = b"tarball";
+
+    let integrity = PackIntegrity {
+      sha256: Some(sha256_hex(bytes).to_uppercase()),
+      ..Default::default()
+    };
+
+    assert!(verify(bytes, &integrity, None).is_ok());
+    assert!(verify(b"tampered", &integrity, None).is_err());
+  }
+
+  #[test]
+  fn test_verify_signature() {
+    let rng = SystemRandom::new();
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+    let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+    let key = STANDARD.encode(key_pair.public_key().as_ref());
+
+    let bytes = b"tarball";
+
+    let integrity = PackIntegrity {
+      sha256: None,
+      signature: Some(STANDARD.encode(key_pair.sign(bytes).as_ref())),
+      publisher_key: Some(key.clone()),
+    };
+
+    assert!(verify(bytes, &integrity, None).is_ok());
+    assert!(verify(bytes, &integrity, Some(&key)).is_ok());
+    assert!(verify(b"tampered", &integrity, None).is_err());
+
+    // Key must match the pinned key of the installed version.
+    assert!(verify(bytes, &integrity, Some("b3RoZXI=")).is_err());
+
+    // Signature can't be dropped once a key is pinned.
+    assert!(verify(bytes, &PackIntegrity::default(), Some(&key)).is_err());
+  }
+
+  #[test]
+  fn test_extract() {
+    let dir = temp_dir("pack-archive-extract");
+
+    let bytes = tarball(&[
+      ("zpack.json", EntryType::Regular, b"{}"),
+      ("bar/", EntryType::Directory, b""),
+      ("bar/index.html", EntryType::Regular, b"<html></html>"),
+    ]);
+
+    extract(&bytes, &dir, MAX_UNPACKED_SIZE).unwrap();
+
+    assert_eq!(fs::read(dir.join("zpack.json")).unwrap(), b"{}");
+    assert!(dir.join("bar/index.html").is_file());
+
+    // Fails if the directory already exists.
+    assert!(extract(&bytes, &dir, MAX_UNPACKED_SIZE).is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn test_read_file() {
+    let bytes = tarball(&[
+      ("./zpack.json", EntryType::Regular, b"{}"),
+      ("bar/zpack.json", EntryType::Regular, b"nested"),
+    ]);
+
+    assert_eq!(read_file(&bytes, "zpack.json").unwrap(), b"{}");
+    assert_eq!(read_file(&bytes, "bar/zpack.json").unwrap(), b"nested");
+    assert!(read_file(&bytes, "missing.json").is_err());
+  }
+
+  #[test]
+  fn test_extract_rejects_unsafe_entries() {
+    let dir = temp_dir("pack-archive-unsafe");
+
+    for entries in [
+      vec![("link", EntryType::Symlink, &b""[..])],
+      vec![("link", EntryType::Link, &b""[..])],
+      vec![("dev", EntryType::Char, &b""[..])],
+      vec![("../escape.txt", EntryType::Regular, &b"x"[..])],
+      vec![("/abs.txt", EntryType::Regular, &b"x"[..])],
+    ] {
+      let bytes = tarball(&entries);
+
+      assert!(extract(&bytes, &dir, MAX_UNPACKED_SIZE).is_err());
+      assert!(!dir.exists());
+    }
+
+    // No temporary directories are left behind.
+    let parent = dir.parent().unwrap();
+    let prefix =
+      format!(".{}.tmp-", dir.file_name().unwrap().to_string_lossy());
+
+    assert!(!fs::read_dir(parent).unwrap().any(|entry| {
+      entry
+        .unwrap()
+        .file_name()
+        .to_string_lossy()
+        .starts_with(&prefix)
+    }));
+  }
+
+  #[test]
+  fn test_extract_size_limit() {
+    let dir = temp_dir("pack-archive-size");
+
+    let bytes = tarball(&[
+      ("a.txt", EntryType::Regular, &[0; 600]),
+      ("b.txt", EntryType::Regular, &[0; 600]),
+    ]);
+
+    assert!(extract(&bytes, &dir, 1000).is_err());
+    assert!(!dir.exists());
+  }
+}
@@ -0,0 +1,298 @@
+use serde::{Deserialize, Serialize};
+use tauri::{PhysicalPosition, PhysicalSize};
+
+use crate::{monitor_state::Monitor, widget_pack::AnchorPoint};
+
+/// What a popup widget is placed next to.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnchorTarget {
+  /// Current position of the cursor.
+  Cursor,
+
+  /// Rect within another widget's window (e.g. the bounding rect of the
+  /// element that opened the popup).
+  ///
+  /// Values are in logical pixels relative to the widget's viewport.
+  #[serde(rename_all = "camelCase")]
+  Rect {
+    widget_id: String,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+  },
+}
+
+/// Rect in physical pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TargetRect {
+  pub x: i32,
+  pub y: i32,
+  pub width: i32,
+  pub height: i32,
+}
+
+impl TargetRect {
+  /// Whether the center of the rect is within the given monitor.
+  pub fn is_centered_on(&self, monitor: &Monitor) -> bool {
+    let center_x = self.x + self.width / 2;
+    let center_y = self.y + self.height / 2;
+
+    center_x >= monitor.x
+      && center_x < monitor.x + monitor.width as i32
+      && center_y >= monitor.y
+      && center_y < monitor.y + monitor.height as i32
+  }
+}
+
+/// Gets the position of a popup that is placed next to the target rect.
+///
+/// The vertical component of the anchor point determines which side of
+/// the target that the popup opens towards:
+///  * `Top*`: Below the target (i.e. the popup's top edge is attached).
+///  * `Bottom*`: Above the target.
+///  * `CenterLeft`/`CenterRight`: Right/left of the target.
+///  * `Center`: Centered over the target.
+///
+/// For popups that open above or below, the horizontal component
+/// aligns the popup with the target's left edge, center, or right edge.
+///
+/// The popup flips to the opposite side if it would otherwise overflow
+/// the monitor, and is then shifted to stay within the monitor's bounds.
+/// `offset` is the gap from the target, and is mirrored when flipped.
+pub fn popup_position(
+  target: TargetRect,
+  size: PhysicalSize<i32>,
+  anchor: AnchorPoint,
+  offset: PhysicalPosition<i32>,
+  monitor: &Monitor,
+) -> PhysicalPosition<i32> {
+  let monitor_start = (monitor.x, monitor.y);
+  let monitor_end = (
+    monitor.x + monitor.width as i32,
+    monitor.y + monitor.height as i32,
+  );
+
+  let (x, y) = match anchor {
+    AnchorPoint::TopLeft
+    | AnchorPoint::TopCenter
+    | AnchorPoint::TopRight
+    | AnchorPoint::BottomLeft
+    | AnchorPoint::BottomCenter
+    | AnchorPoint::BottomRight => {
+      let opens_below = matches!(
+        anchor,
+        AnchorPoint::TopLeft
+          | AnchorPoint::TopCenter
+          | AnchorPoint::TopRight
+      );
+
+      let x = match anchor {
+        AnchorPoint::TopLeft | AnchorPoint::BottomLeft => target.x,
+        AnchorPoint::TopRight | AnchorPoint::BottomRight => {
+          target.x + target.width - size.width
+        }
+        _ => target.x + target.width / 2 - size.width / 2,
+      };
+
+      let y = flip_along_axis(
+        target.y,
+        target.height,
+        size.height,
+        offset.y,
+        opens_below,
+        monitor_start.1,
+        monitor_end.1,
+      );
+
+      (x + offset.x, y)
+    }
+    AnchorPoint::CenterLeft | AnchorPoint::CenterRight => {
+      let x = flip_along_axis(
+        target.x,
+        target.width,
+        size.width,
+        offset.x,
+        anchor == AnchorPoint::CenterLeft,
+        monitor_start.0,
+        monitor_end.0,
+      );
+
+      let y = target.y + target.height / 2 - size.height / 2;
+
+      (x, y + offset.y)
+    }
+    AnchorPoint::Center => (
+      target.x + target.width / 2 - size.width / 2 + offset.x,
+      target.y + target.height / 2 - size.height / 2 + offset.y,
+    ),
+  };
+
+  PhysicalPosition::new(
+    clamp_to_monitor(x, size.width, monitor_start.0, monitor_end.0),
+    clamp_to_monitor(y, size.height, monitor_start.1, monitor_end.1),
+  )
+}
+
+/// Gets the start of the popup along an axis, placing it after the
+/// target (i.e. below or right of it) if `prefers_after` is set, and
+/// otherwise before it.
+///
+/// Flips to the other side if the preferred side overflows the monitor
+/// and the other side has more space.
+fn flip_along_axis(
+  target_start: i32,
+  target_length: i32,
+  length: i32,
+  gap: i32,
+  prefers_after: bool,
+  monitor_start: i32,
+  monitor_end: i32,
+) -> i32 {
+  let after = target_start + target_length + gap;
+  let before = target_start - gap - length;
+
+  let space_after = monitor_end - (target_start + target_length);
+  let space_before = target_start - monitor_start;
+
+  match prefers_after {
+    true if after + length > monitor_end && space_before > space_after => {
+      before
+    }
+    true => after,
+    false if before < monitor_start && space_after > space_before => after,
+    false => before,
+  }
+}
+
+/// Shifts the popup along an axis to stay within the monitor.
+///
+/// Popups larger than the monitor are aligned to the monitor's start.
+fn clamp_to_monitor(
+  start: i32,
+  length: i32,
+  monitor_start: i32,
+  monitor_end: i32,
+) -> i32 {
+  start.min(monitor_end - length).max(monitor_start)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn monitor() -> Monitor {
+    Monitor {
+      name: Some("DISPLAY1".to_string()),
+      is_primary: true,
+      x: 0,
+      y: 0,
+      width: 1920,
+      height: 1080,
+      scale_factor: 1.0,
+    }
+  }
+
+  fn rect(x: i32, y: i32, width: i32, height: i32) -> TargetRect {
+    TargetRect {
+      x,
+      y,
+      width,
+      height,
+    }
+  }
+
+  const SIZE: PhysicalSize<i32> = PhysicalSize::new(300, 200);
+  const NO_OFFSET: PhysicalPosition<i32> = PhysicalPosition::new(0, 0);
+
+  #[test]
+  fn test_popup_below_target() {
+    // Bar item at the top of the monitor.
+    let target = rect(500, 0, 100, 40);
+
+    let position =
+      |anchor| popup_position(target, SIZE, anchor, NO_OFFSET, &monitor());
+
+    assert_eq!(
+      position(AnchorPoint::TopLeft),
+      PhysicalPosition::new(500, 40)
+    );
+    assert_eq!(
+      position(AnchorPoint::TopCenter),
+      PhysicalPosition::new(400, 40)
+    );
+    assert_eq!(
+      position(AnchorPoint::TopRight),
+      PhysicalPosition::new(300, 40)
+    );
+  }
+
+  #[test]
+  fn test_popup_flips_on_overflow() {
+    // Bar item at the bottom of the monitor can't open below.
+    let target = rect(500, 1040, 100, 40);
+
+    assert_eq!(
+      popup_position(
+        target,
+        SIZE,
+        AnchorPoint::TopLeft,
+        PhysicalPosition::new(0, 8),
+        &monitor()
+      ),
+      PhysicalPosition::new(500, 832)
+    );
+
+    // Side bar at the right edge of the monitor opens to its left.
+    let target = rect(1880, 500, 40, 40);
+
+    assert_eq!(
+      popup_position(
+        target,
+        SIZE,
+        AnchorPoint::CenterLeft,
+        NO_OFFSET,
+        &monitor()
+      ),
+      PhysicalPosition::new(1580, 420)
+    );
+  }
+
+  #[test]
+  fn test_popup_clamped_to_monitor() {
+    // Cursor near the right edge of the monitor.
+    let target = rect(1900, 300, 0, 0);
+
+    assert_eq!(
+      popup_position(
+        target,
+        SIZE,
+        AnchorPoint::TopLeft,
+        NO_OFFSET,
+        &monitor()
+      ),
+      PhysicalPosition::new(1620, 300)
+    );
+
+    // Popup larger than the monitor is aligned to its start.
+    assert_eq!(
+      popup_position(
+        target,
+        PhysicalSize::new(2000, 200),
+        AnchorPoint::TopLeft,
+        NO_OFFSET,
+        &monitor()
+      ),
+      PhysicalPosition::new(0, 300)
+    );
+  }
+
+  #[test]
+  fn test_is_centered_on() {
+    let monitor = monitor();
+
+    assert!(rect(1800, 0, 100, 40).is_centered_on(&monitor));
+    assert!(!rect(1900, 0, 100, 40).is_centered_on(&monitor));
+  }
+}
@@ -0,0 +1,159 @@
+use anyhow::bail;
+use async_trait::async_trait;
+use hyprland_util::HyprlandClient;
+use serde::Deserialize;
+
+use crate::providers::{
+  CommonProviderState, HyprlandDirection, HyprlandFunction, Provider,
+  ProviderFunction, ProviderFunctionResponse, ProviderInputMsg,
+  RuntimeType,
+};
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HyprlandProviderConfig {}
+
+pub type HyprlandOutput = hyprland_util::HyprlandOutput;
+
+pub struct HyprlandProvider {
+  common: CommonProviderState,
+}
+
+impl HyprlandProvider {
+  pub fn new(
+    _config: HyprlandProviderConfig,
+    common: CommonProviderState,
+  ) -> HyprlandProvider {
+    HyprlandProvider { common }
+  }
+
+  async fn handle_function(
+    client: &HyprlandClient,
+    function: HyprlandFunction,
+  ) -> anyhow::Result<ProviderFunctionResponse> {
+    let dispatcher = Self::dispatcher(function);
+    Self::ensure_allowed(&dispatcher)?;
+
+    client.dispatch(&dispatcher).await?;
+
+    Ok(ProviderFunctionResponse::Null)
+  }
+
+  /// Gets the dispatcher and its arguments for a function.
+  fn dispatcher(function: HyprlandFunction) -> String {
+    match function {
+      HyprlandFunction::FocusWorkspace(args) => {
+        format!("workspace {}", args.workspace)
+      }
+      HyprlandFunction::MoveToWorkspace(args) => match args.silent {
+        true => format!("movetoworkspacesilent {}", args.workspace),
+        false => format!("movetoworkspace {}", args.workspace),
+      },
+      HyprlandFunction::MoveWindow(args) => {
+        format!("movewindow {}", Self::direction(args.direction))
+      }
+    }
+  }
+
+  /// Fails for dispatchers that run programs or batch multiple commands,
+  /// since widgets need shell privileges to run programs.
+  ///
+  /// Workspace arguments come from widgets, so they could otherwise be
+  /// used to smuggle in other dispatchers.
+  fn ensure_allowed(dispatcher: &str) -> anyhow::Result<()> {
+    let name = dispatcher.split_whitespace().next().unwrap_or_default();
+
+    if matches!(name, "exec" | "execr")
+      || dispatcher.contains("[[BATCH]]")
+      || dispatcher.chars().any(char::is_control)
+    {
+      bail!("Dispatcher '{}' is not allowed.", dispatcher);
+    }
+
+    Ok(())
+  }
+
+  fn direction(direction: HyprlandDirection) -> &'static str {
+    match direction {
+      HyprlandDirection::Left => "l",
+      HyprlandDirection::Right => "r",
+      HyprlandDirection::Up => "u",
+      HyprlandDirection::Down => "d",
+    }
+  }
+}
+
+#[async_trait]
+impl Provider for HyprlandProvider {
+  fn runtime_type(&self) -> RuntimeType {
+    RuntimeType::Async
+  }
+
+  async fn start_async(&mut self) {
+    let mut client = match HyprlandClient::new() {
+      Ok(client) => client,
+      Err(err) => {
+        self
+          .common
+          .emitter
+          .emit_output::<HyprlandOutput>(Err(anyhow::anyhow!(err)));
+
+        return;
+      }
+    };
+
+    loop {
+      tokio::select! {
+        output = client.output() => {
+          self.common.emitter.emit_output(output.map_err(|err| anyhow::anyhow!(err)));
+        }
+        Some(input) = self.common.input.async_rx.recv() => {
+          match input {
+            ProviderInputMsg::Stop => {
+              break;
+            }
+            ProviderInputMsg::Function(
+              ProviderFunction::Hyprland(hyprland_function),
+              sender,
+            ) => {
+              let res = Self::handle_function(&client, hyprland_function)
+                .await
+                .map_err(|err| err.to_string());
+
+              sender.send(res).unwrap();
+            }
+            _ => {}
+          }
+        }
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::providers::HyprlandWorkspaceArgs;
+
+  #[test]
+  fn test_ensure_allowed() {
+    let dispatcher = HyprlandProvider::dispatcher(
+      HyprlandFunction::FocusWorkspace(HyprlandWorkspaceArgs {
+        workspace: "name:web".into(),
+      }),
+    );
+
+    assert_eq!(dispatcher, "workspace name:web");
+    assert!(HyprlandProvider::ensure_allowed(&dispatcher).is_ok());
+
+    assert!(HyprlandProvider::ensure_allowed("exec kitty").is_err());
+    assert!(HyprlandProvider::ensure_allowed(" execr kitty").is_err());
+    assert!(HyprlandProvider::ensure_allowed(
+      "workspace 1;[[BATCH]]dispatch exec kitty"
+    )
+    .is_err());
+    assert!(
+      HyprlandProvider::ensure_allowed("workspace 1\nexec kitty").is_err()
+    );
+  }
+}
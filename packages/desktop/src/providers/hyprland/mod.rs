@@ -0,0 +1,3 @@
+mod hyprland_provider;
+
+pub use hyprland_provider::*;
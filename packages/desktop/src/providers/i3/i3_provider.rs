@@ -0,0 +1,150 @@
+use anyhow::bail;
+use async_trait::async_trait;
+use i3_util::I3Client;
+use serde::Deserialize;
+
+use crate::providers::{
+  CommonProviderState, I3Function, Provider, ProviderFunction,
+  ProviderFunctionResponse, ProviderInputMsg, RuntimeType,
+};
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct I3ProviderConfig {}
+
+pub type I3Output = i3_util::I3Output;
+
+pub struct I3Provider {
+  common: CommonProviderState,
+}
+
+impl I3Provider {
+  pub fn new(
+    _config: I3ProviderConfig,
+    common: CommonProviderState,
+  ) -> I3Provider {
+    I3Provider { common }
+  }
+
+  async fn handle_function(
+    client: &I3Client,
+    function: I3Function,
+  ) -> anyhow::Result<ProviderFunctionResponse> {
+    match function {
+      I3Function::RunCommand(args) => {
+        Self::ensure_allowed(&args.command)?;
+        client.run_command(&args.command).await?;
+      }
+    }
+
+    Ok(ProviderFunctionResponse::Null)
+  }
+
+  /// Fails if the command (or any command chained with `;` or `,`) runs
+  /// a program, since widgets need shell privileges to run programs.
+  ///
+  /// Matches on words rather than parsing the command, so workspaces and
+  /// marks with these names are refused as well.
+  fn ensure_allowed(command: &str) -> anyhow::Result<()> {
+    const PROGRAM_COMMANDS: [&str; 5] = [
+      "exec",
+      "exec_always",
+      "status_command",
+      "swaybar_command",
+      "swaynag_command",
+    ];
+
+    let runs_program = command
+      .split(|char: char| {
+        char.is_whitespace()
+          || matches!(char, ';' | ',' | '"' | '\'' | '[' | ']')
+      })
+      .any(|word| {
+        PROGRAM_COMMANDS.iter().any(|program_command| {
+          word.eq_ignore_ascii_case(program_command)
+        })
+      });
+
+    if runs_program {
+      bail!("Command '{}' is not allowed.", command);
+    }
+
+    Ok(())
+  }
+}
+
+#[async_trait]
+impl Provider for I3Provider {
+  fn runtime_type(&self) -> RuntimeType {
+    RuntimeType::Async
+  }
+
+  async fn start_async(&mut self) {
+    let mut client = match I3Client::new() {
+      Ok(client) => client,
+      Err(err) => {
+        self
+          .common
+          .emitter
+          .emit_output::<I3Output>(Err(anyhow::anyhow!(err)));
+
+        return;
+      }
+    };
+
+    loop {
+      tokio::select! {
+        output = client.output() => {
+          self.common.emitter.emit_output(output.map_err(|err| anyhow::anyhow!(err)));
+        }
+        Some(input) = self.common.input.async_rx.recv() => {
+          match input {
+            ProviderInputMsg::Stop => {
+              break;
+            }
+            ProviderInputMsg::Function(
+              ProviderFunction::I3(i3_function),
+              sender,
+            ) => {
+              let res = Self::handle_function(&client, i3_function)
+                .await
+                .map_err(|err| err.to_string());
+
+              sender.send(res).unwrap();
+            }
+            _ => {}
+          }
+        }
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_ensure_allowed() {
+    assert!(I3Provider::ensure_allowed("workspace 2").is_ok());
+    assert!(
+      I3Provider::ensure_allowed("move container to workspace 3").is_ok()
+    );
+
+    assert!(I3Provider::ensure_allowed("exec firefox").is_err());
+    assert!(
+      I3Provider::ensure_allowed("workspace 2;exec firefox").is_err()
+    );
+    assert!(
+      I3Provider::ensure_allowed("workspace 2, EXEC firefox").is_err()
+    );
+    assert!(I3Provider::ensure_allowed(
+      "[class=\"x\"]exec_always firefox"
+    )
+    .is_err());
+    assert!(I3Provider::ensure_allowed(
+      "bar bar-0 status_command firefox"
+    )
+    .is_err());
+  }
+}
@@ -0,0 +1,3 @@
+mod i3_provider;
+
+pub use i3_provider::*;
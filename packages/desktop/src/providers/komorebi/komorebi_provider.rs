@@ -1,27 +1,144 @@
+use std::time::Duration;
+
 use async_trait::async_trait;
-use komorebi_util::KomorebiClient;
+use komorebi_util::{
+  ClientOptions, CycleDirection, KomorebiClient, KomorebiCommand,
+  OperationDirection, ReconnectBackoff,
+};
 use serde::Deserialize;
 
 use crate::providers::{
-  CommonProviderState, Provider, ProviderInputMsg, RuntimeType,
+  CommonProviderState, KomorebiCycleDirection, KomorebiDirection,
+  KomorebiFunction, Provider, ProviderFunction, ProviderFunctionResponse,
+  ProviderInputMsg, RuntimeType,
 };
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct KomorebiProviderConfig {}
+pub struct KomorebiProviderConfig {
+  #[serde(default)]
+  pub reconnect_backoff: KomorebiReconnectBackoffConfig,
+}
+
+/// Delays between attempts to reconnect to Komorebi. Unset fields fall
+/// back to the defaults of `ReconnectBackoff`.
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct KomorebiReconnectBackoffConfig {
+  /// Delay in milliseconds after the first failed connection attempt.
+  pub initial_delay: Option<u64>,
+
+  /// Upper bound in milliseconds for the delay between attempts.
+  pub max_delay: Option<u64>,
+
+  /// Factor to multiply the delay by after each failed attempt.
+  pub multiplier: Option<u32>,
+}
+
+impl From<&KomorebiReconnectBackoffConfig> for ReconnectBackoff {
+  fn from(config: &KomorebiReconnectBackoffConfig) -> Self {
+    let default = ReconnectBackoff::default();
+
+    let initial_delay = config
+      .initial_delay
+      .map(Duration::from_millis)
+      .unwrap_or(default.initial_delay);
+
+    let max_delay = config
+      .max_delay
+      .map(Duration::from_millis)
+      .unwrap_or(default.max_delay);
+
+    ReconnectBackoff {
+      initial_delay,
+      max_delay: max_delay.max(initial_delay),
+      multiplier: config.multiplier.unwrap_or(default.multiplier).max(1),
+    }
+  }
+}
 
 pub type KomorebiOutput = komorebi_util::KomorebiOutput;
 
 pub struct KomorebiProvider {
+  config: KomorebiProviderConfig,
   common: CommonProviderState,
 }
 
 impl KomorebiProvider {
   pub fn new(
-    _config: KomorebiProviderConfig,
+    config: KomorebiProviderConfig,
     common: CommonProviderState,
   ) -> KomorebiProvider {
-    KomorebiProvider { common }
+    KomorebiProvider { config, common }
+  }
+
+  fn handle_function(
+    client: &KomorebiClient,
+    function: KomorebiFunction,
+  ) -> anyhow::Result<ProviderFunctionResponse> {
+    let command = match function {
+      KomorebiFunction::FocusWorkspace(args) => match args.monitor_index {
+        Some(monitor_index) => {
+          KomorebiCommand::FocusMonitorWorkspaceNumber(
+            monitor_index,
+            args.workspace_index,
+          )
+        }
+        None => {
+          KomorebiCommand::FocusWorkspaceNumber(args.workspace_index)
+        }
+      },
+      KomorebiFunction::FocusNamedWorkspace(args) => {
+        KomorebiCommand::FocusNamedWorkspace(args.workspace_name)
+      }
+      KomorebiFunction::CycleFocusWorkspace(args) => {
+        KomorebiCommand::CycleFocusWorkspace(Self::cycle(args.direction))
+      }
+      KomorebiFunction::MoveToWorkspace(args) => {
+        match args.monitor_index {
+          Some(monitor_index) => {
+            KomorebiCommand::MoveContainerToMonitorWorkspaceNumber(
+              monitor_index,
+              args.workspace_index,
+            )
+          }
+          None => KomorebiCommand::MoveContainerToWorkspaceNumber(
+            args.workspace_index,
+          ),
+        }
+      }
+      KomorebiFunction::FocusWindow(args) => {
+        KomorebiCommand::FocusWindow(Self::direction(args.direction))
+      }
+      KomorebiFunction::MoveWindow(args) => {
+        KomorebiCommand::MoveWindow(Self::direction(args.direction))
+      }
+      KomorebiFunction::CycleLayout(args) => {
+        KomorebiCommand::CycleLayout(Self::cycle(args.direction))
+      }
+      KomorebiFunction::ToggleMonocle => KomorebiCommand::ToggleMonocle,
+      KomorebiFunction::ToggleFloat => KomorebiCommand::ToggleFloat,
+    };
+
+    client.send_command(&command)?;
+
+    Ok(ProviderFunctionResponse::Null)
+  }
+
+  fn direction(direction: KomorebiDirection) -> OperationDirection {
+    match direction {
+      KomorebiDirection::Left => OperationDirection::Left,
+      KomorebiDirection::Right => OperationDirection::Right,
+      KomorebiDirection::Up => OperationDirection::Up,
+      KomorebiDirection::Down => OperationDirection::Down,
+    }
+  }
+
+  fn cycle(direction: KomorebiCycleDirection) -> CycleDirection {
+    match direction {
+      KomorebiCycleDirection::Previous => CycleDirection::Previous,
+      KomorebiCycleDirection::Next => CycleDirection::Next,
+    }
   }
 }
 
@@ -32,7 +149,12 @@ impl Provider for KomorebiProvider {
   }
 
   async fn start_async(&mut self) {
-    let Ok(mut client) = KomorebiClient::new("zebar.sock") else {
+    let options = ClientOptions {
+      reconnect_backoff: (&self.config.reconnect_backoff).into(),
+      ..ClientOptions::new("zebar.sock")
+    };
+
+    let Ok(mut client) = KomorebiClient::with_options(options) else {
       self.common.emitter.emit_output::<KomorebiOutput>(Err(
         anyhow::anyhow!("Failed to initialize komorebi client."),
       ));
@@ -46,11 +168,56 @@ impl Provider for KomorebiProvider {
           self.common.emitter.emit_output(output.map_err(|err| anyhow::anyhow!(err)));
         }
         Some(input) = self.common.input.async_rx.recv() => {
-          if let ProviderInputMsg::Stop = input {
-            break;
+          match input {
+            ProviderInputMsg::Stop => {
+              break;
+            }
+            ProviderInputMsg::Function(
+              ProviderFunction::Komorebi(komorebi_function),
+              sender,
+            ) => {
+              let res = Self::handle_function(&client, komorebi_function)
+                .map_err(|err| err.to_string());
+
+              sender.send(res).unwrap();
+            }
+            _ => {}
           }
         }
       }
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_reconnect_backoff_config() {
+    let config: KomorebiProviderConfig =
+      serde_json::from_str(r#"{ "type": "komorebi" }"#).unwrap();
+
+    assert_eq!(
+      ReconnectBackoff::from(&config.reconnect_backoff),
+      ReconnectBackoff::default()
+    );
+
+    let config: KomorebiProviderConfig = serde_json::from_str(
+      r#"{
+        "type": "komorebi",
+        "reconnectBackoff": { "initialDelay": 500, "multiplier": 0 }
+      }"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+      ReconnectBackoff::from(&config.reconnect_backoff),
+      ReconnectBackoff {
+        initial_delay: Duration::from_millis(500),
+        max_delay: Duration::from_secs(15),
+        multiplier: 1,
+      }
+    );
+  }
+}
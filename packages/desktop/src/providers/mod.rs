@@ -4,6 +4,10 @@ mod battery;
 mod cpu;
 mod disk;
 mod host;
+#[cfg(target_os = "linux")]
+mod hyprland;
+#[cfg(target_os = "linux")]
+mod i3;
 mod ip;
 #[cfg(windows)]
 mod keyboard;
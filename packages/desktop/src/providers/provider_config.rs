@@ -13,6 +13,8 @@ use super::{
   ip::IpProviderConfig, memory::MemoryProviderConfig,
   network::NetworkProviderConfig, weather::WeatherProviderConfig,
 };
+#[cfg(target_os = "linux")]
+use super::{hyprland::HyprlandProviderConfig, i3::I3ProviderConfig};
 
 #[derive(Deserialize, Debug)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -22,6 +24,10 @@ pub enum ProviderConfig {
   Battery(BatteryProviderConfig),
   Cpu(CpuProviderConfig),
   Host(HostProviderConfig),
+  #[cfg(target_os = "linux")]
+  Hyprland(HyprlandProviderConfig),
+  #[cfg(target_os = "linux")]
+  I3(I3ProviderConfig),
   Ip(IpProviderConfig),
   #[cfg(any(target_os = "macos", windows))]
   Komorebi(KomorebiProviderConfig),
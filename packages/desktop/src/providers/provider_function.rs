@@ -4,6 +4,9 @@ use serde::{Deserialize, Serialize};
 #[serde(tag = "type", content = "function", rename_all = "snake_case")]
 pub enum ProviderFunction {
   Audio(AudioFunction),
+  Hyprland(HyprlandFunction),
+  I3(I3Function),
+  Komorebi(KomorebiFunction),
   Media(MediaFunction),
   Systray(SystrayFunction),
 }
@@ -29,6 +32,115 @@ pub struct SetMuteArgs {
   pub device_id: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "name", content = "args", rename_all = "snake_case")]
+pub enum HyprlandFunction {
+  FocusWorkspace(HyprlandWorkspaceArgs),
+  MoveToWorkspace(HyprlandMoveToWorkspaceArgs),
+  MoveWindow(HyprlandDirectionArgs),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HyprlandWorkspaceArgs {
+  /// Workspace ID, name (`name:web`), or relative selector (`e+1`).
+  pub workspace: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HyprlandMoveToWorkspaceArgs {
+  pub workspace: String,
+
+  /// Whether to move the window without following it.
+  #[serde(default)]
+  pub silent: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HyprlandDirectionArgs {
+  pub direction: HyprlandDirection,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HyprlandDirection {
+  Left,
+  Right,
+  Up,
+  Down,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "name", content = "args", rename_all = "snake_case")]
+pub enum I3Function {
+  RunCommand(I3RunCommandArgs),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct I3RunCommandArgs {
+  pub command: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "name", content = "args", rename_all = "snake_case")]
+pub enum KomorebiFunction {
+  FocusWorkspace(KomorebiWorkspaceArgs),
+  FocusNamedWorkspace(KomorebiNamedWorkspaceArgs),
+  CycleFocusWorkspace(KomorebiCycleArgs),
+  MoveToWorkspace(KomorebiWorkspaceArgs),
+  FocusWindow(KomorebiDirectionArgs),
+  MoveWindow(KomorebiDirectionArgs),
+  CycleLayout(KomorebiCycleArgs),
+  ToggleMonocle,
+  ToggleFloat,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KomorebiWorkspaceArgs {
+  pub workspace_index: usize,
+
+  /// Monitor to target. Defaults to the focused monitor.
+  pub monitor_index: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KomorebiNamedWorkspaceArgs {
+  pub workspace_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KomorebiDirectionArgs {
+  pub direction: KomorebiDirection,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KomorebiCycleArgs {
+  pub direction: KomorebiCycleDirection,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KomorebiDirection {
+  Left,
+  Right,
+  Up,
+  Down,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KomorebiCycleDirection {
+  Previous,
+  Next,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "name", content = "args", rename_all = "snake_case")]
 pub enum MediaFunction {
@@ -23,6 +23,8 @@ use super::{
   ProviderConfig, ProviderFunction, ProviderFunctionResponse,
   ProviderFunctionResult, ProviderOutput, RuntimeType,
 };
+#[cfg(target_os = "linux")]
+use super::{hyprland::HyprlandProvider, i3::I3Provider};
 
 /// Common fields for a provider.
 pub struct CommonProviderState {
@@ -250,6 +252,10 @@ impl ProviderManager {
       ProviderConfig::Ip(..) | ProviderConfig::Weather(..) => {
         RuntimeType::Async
       }
+      #[cfg(target_os = "linux")]
+      ProviderConfig::Hyprland(..) | ProviderConfig::I3(..) => {
+        RuntimeType::Async
+      }
       #[cfg(any(target_os = "macos", windows))]
       ProviderConfig::Komorebi(..) => RuntimeType::Async,
       #[cfg(windows)]
@@ -269,6 +275,16 @@ impl ProviderManager {
             let mut provider = WeatherProvider::new(config, common);
             provider.start_async().await;
           }
+          #[cfg(target_os = "linux")]
+          ProviderConfig::Hyprland(config) => {
+            let mut provider = HyprlandProvider::new(config, common);
+            provider.start_async().await;
+          }
+          #[cfg(target_os = "linux")]
+          ProviderConfig::I3(config) => {
+            let mut provider = I3Provider::new(config, common);
+            provider.start_async().await;
+          }
           #[cfg(any(target_os = "macos", windows))]
           ProviderConfig::Komorebi(config) => {
             let mut provider = KomorebiProvider::new(config, common);
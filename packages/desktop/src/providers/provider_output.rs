@@ -12,6 +12,8 @@ use super::{
   host::HostOutput, ip::IpOutput, memory::MemoryOutput,
   network::NetworkOutput, weather::WeatherOutput,
 };
+#[cfg(target_os = "linux")]
+use super::{hyprland::HyprlandOutput, i3::I3Output};
 
 /// Implements `From<T>` for `ProviderOutput` for each given variant.
 macro_rules! impl_provider_output {
@@ -34,6 +36,10 @@ pub enum ProviderOutput {
   Battery(BatteryOutput),
   Cpu(CpuOutput),
   Host(HostOutput),
+  #[cfg(target_os = "linux")]
+  Hyprland(HyprlandOutput),
+  #[cfg(target_os = "linux")]
+  I3(I3Output),
   Ip(IpOutput),
   #[cfg(any(target_os = "macos", windows))]
   Komorebi(KomorebiOutput),
@@ -60,6 +66,12 @@ impl_provider_output! {
   Weather(WeatherOutput)
 }
 
+#[cfg(target_os = "linux")]
+impl_provider_output! {
+  Hyprland(HyprlandOutput),
+  I3(I3Output),
+}
+
 #[cfg(target_os = "macos")]
 impl_provider_output! {
   Komorebi(KomorebiOutput),
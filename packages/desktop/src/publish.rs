@@ -1,5 +1,5 @@
 use std::{
-  collections::HashSet,
+  collections::BTreeSet,
   fs::{self, File},
   path::{Path, PathBuf},
 };
@@ -11,6 +11,7 @@ use serde::Deserialize;
 use crate::{
   cli::PublishArgs,
   common::{glob_util, PathExt},
+  pack_archive,
   widget_pack::{WidgetPack, WidgetPackManager},
 };
 
@@ -73,7 +74,13 @@ pub async fn publish_widget_pack(
 
   // Create the tarball of the widget pack.
   let pack = WidgetPackManager::read_widget_pack(&pack_config_path, None)?;
-  let tarball_path = create_tarball(&pack)?;
+  let tarball_path = std::env::temp_dir().join("zebar-pack.tar.gz");
+
+  println!("Files to be included in the widget pack:");
+
+  for path in create_tarball(&pack, &tarball_path)? {
+    println!("  {}", path.display());
+  }
 
   // Upload to marketplace.
   let response =
@@ -90,16 +97,37 @@ pub async fn publish_widget_pack(
   ))
 }
 
-/// Creates a tarball of the widget pack.
+/// Exports a widget pack to a tarball that can be installed with
+/// `zebar install`.
+///
+/// Returns the SHA-256 digest of the tarball.
+pub fn export_widget_pack(
+  pack: &WidgetPack,
+  tarball_path: &Path,
+) -> anyhow::Result<String> {
+  if let Some(parent) = tarball_path.parent() {
+    fs::create_dir_all(parent)?;
+  }
+
+  create_tarball(pack, tarball_path)?;
+
+  Ok(pack_archive::sha256_hex(&fs::read(tarball_path)?))
+}
+
+/// Creates a tarball of the widget pack at the given path.
 ///
 /// The tarball includes all files in the widget directories that match the
 /// include pattern.
-fn create_tarball(pack: &WidgetPack) -> anyhow::Result<PathBuf> {
-  // Create temporary .tar.gz file.
-  let tarball_path = std::env::temp_dir().join("zebar-pack.tar.gz");
-  let tarball_file = File::create(&tarball_path)?;
+///
+/// Returns the paths of the included files, relative to the pack
+/// directory.
+fn create_tarball(
+  pack: &WidgetPack,
+  tarball_path: &Path,
+) -> anyhow::Result<Vec<PathBuf>> {
+  let tarball_file = File::create(tarball_path)?;
 
-  let mut included_files = HashSet::new();
+  let mut included_files = BTreeSet::new();
   included_files.insert(pack.config_path.clone());
 
   // Include preview images.
@@ -142,18 +170,18 @@ fn create_tarball(pack: &WidgetPack) -> anyhow::Result<PathBuf> {
   let encoder = GzEncoder::new(tarball_file, Compression::default());
   let mut builder = tar::Builder::new(encoder);
 
-  println!("Files to be included in the widget pack:");
+  let mut relative_paths = Vec::new();
 
-  // Add files to the tarball, printing them as they are added.
+  // Add files to the tarball.
   for entry in &included_files {
     let relative_path = entry.strip_prefix(&pack.directory_path)?;
-    println!("  {}", relative_path.display());
     builder.append_file(relative_path, &mut File::open(entry)?)?;
+    relative_paths.push(relative_path.to_path_buf());
   }
 
-  builder.finish()?;
+  builder.into_inner()?.finish()?;
 
-  Ok(tarball_path)
+  Ok(relative_paths)
 }
 
 /// Uploads the widget pack to the Zebar marketplace.
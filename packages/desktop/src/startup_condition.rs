@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+use starship_battery::Manager;
+use sysinfo::System;
+
+use crate::monitor_state::Monitor;
+
+/// Conditions that need to be met for a startup config to be launched.
+///
+/// All specified conditions need to be met. This allows the same
+/// settings file to be shared across multiple machines.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupCondition {
+  /// Hostname of the machine (case-insensitive).
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub hostname: Option<String>,
+
+  /// Operating system (`windows`, `macos`, or `linux`).
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub os: Option<String>,
+
+  /// Minimum number of connected monitors.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub min_monitors: Option<usize>,
+
+  /// Maximum number of connected monitors.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub max_monitors: Option<usize>,
+
+  /// Name of a monitor that needs to be connected.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub monitor_name: Option<String>,
+
+  /// Whether the machine has a battery (e.g. a laptop).
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub has_battery: Option<bool>,
+}
+
+/// Machine that startup conditions are evaluated against.
+#[derive(Clone, Debug)]
+pub struct StartupEnvironment {
+  pub hostname: Option<String>,
+  pub os: String,
+  pub monitor_names: Vec<Option<String>>,
+  pub has_battery: bool,
+}
+
+impl StartupEnvironment {
+  /// Gets the environment of the current machine with the given
+  /// monitors connected.
+  pub fn current(monitors: &[Monitor]) -> Self {
+    let has_battery = Manager::new()
+      .and_then(|manager| manager.batteries())
+      .map(|mut batteries| batteries.next().is_some_and(|b| b.is_ok()))
+      .unwrap_or(false);
+
+    Self {
+      hostname: System::host_name(),
+      os: std::env::consts::OS.to_string(),
+      monitor_names: monitors
+        .iter()
+        .map(|monitor| monitor.name.clone())
+        .collect(),
+      has_battery,
+    }
+  }
+}
+
+impl StartupCondition {
+  /// Whether all conditions are met in the given environment.
+  pub fn is_met(&self, env: &StartupEnvironment) -> bool {
+    let monitor_count = env.monitor_names.len();
+
+    let is_hostname_met = self.hostname.as_ref().is_none_or(|hostname| {
+      env
+        .hostname
+        .as_ref()
+        .is_some_and(|host| host.eq_ignore_ascii_case(hostname))
+    });
+
+    let is_os_met = self
+      .os
+      .as_ref()
+      .is_none_or(|os| os.eq_ignore_ascii_case(&env.os));
+
+    let is_monitor_count_met =
+      self.min_monitors.is_none_or(|min| monitor_count >= min)
+        && self.max_monitors.is_none_or(|max| monitor_count <= max);
+
+    let is_monitor_name_met =
+      self.monitor_name.as_ref().is_none_or(|name| {
+        env
+          .monitor_names
+          .iter()
+          .any(|monitor_name| monitor_name.as_ref() == Some(name))
+      });
+
+    let is_battery_met = self
+      .has_battery
+      .is_none_or(|has_battery| has_battery == env.has_battery);
+
+    is_hostname_met
+      && is_os_met
+      && is_monitor_count_met
+      && is_monitor_name_met
+      && is_battery_met
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn laptop(monitor_names: &[&str]) -> StartupEnvironment {
+    StartupEnvironment {
+      hostname: Some("WORK-LAPTOP".to_string()),
+      os: "windows".to_string(),
+      monitor_names: monitor_names
+        .iter()
+        .map(|name| Some(name.to_string()))
+        .collect(),
+      has_battery: true,
+    }
+  }
+
+  #[test]
+  fn test_empty_condition() {
+    assert!(StartupCondition::default().is_met(&laptop(&[])));
+  }
+
+  #[test]
+  fn test_machine_conditions() {
+    let env = laptop(&["DISPLAY1"]);
+
+    let condition = StartupCondition {
+      hostname: Some("work-laptop".to_string()),
+      os: Some("windows".to_string()),
+      has_battery: Some(true),
+      ..Default::default()
+    };
+
+    assert!(condition.is_met(&env));
+
+    let desktop = StartupCondition {
+      has_battery: Some(false),
+      ..condition.clone()
+    };
+
+    assert!(!desktop.is_met(&env));
+
+    let mac = StartupCondition {
+      os: Some("macos".to_string()),
+      ..condition
+    };
+
+    assert!(!mac.is_met(&env));
+  }
+
+  #[test]
+  fn test_monitor_conditions() {
+    let docked = StartupCondition {
+      min_monitors: Some(2),
+      monitor_name: Some("DELL U2720Q".to_string()),
+      ..Default::default()
+    };
+
+    let undocked = StartupCondition {
+      max_monitors: Some(1),
+      ..Default::default()
+    };
+
+    let env = laptop(&["DISPLAY1", "DELL U2720Q"]);
+    assert!(docked.is_met(&env));
+    assert!(!undocked.is_met(&env));
+
+    let env = laptop(&["DISPLAY1", "DISPLAY2"]);
+    assert!(!docked.is_met(&env));
+
+    let env = laptop(&["DISPLAY1"]);
+    assert!(!docked.is_met(&env));
+    assert!(undocked.is_met(&env));
+  }
+}
@@ -20,6 +20,7 @@ use tracing::{error, info};
 
 use crate::{
   app_settings::{AppSettings, StartupConfig, VERSION_NUMBER},
+  marketplace_updater::MarketplaceUpdater,
   widget_factory::{WidgetFactory, WidgetOpenOptions, WidgetState},
   widget_pack::{WidgetConfig, WidgetPack, WidgetPackManager},
 };
@@ -28,8 +29,13 @@ use crate::{
 enum MenuEvent {
   ShowConfigFolder,
   ReloadConfigs,
+  ToggleRestoreSession {
+    enable: bool,
+  },
   OpenSettings,
   BrowseWidgets,
+  CheckForUpdates,
+  InstallUpdates,
   Exit,
   EditWidgetPack {
     pack_id: String,
@@ -57,8 +63,13 @@ impl Display for MenuEvent {
     match self {
       MenuEvent::ShowConfigFolder => write!(f, "show_config_folder"),
       MenuEvent::ReloadConfigs => write!(f, "reload_configs"),
+      MenuEvent::ToggleRestoreSession { enable } => {
+        write!(f, "toggle_restore_session_{}", enable)
+      }
       MenuEvent::OpenSettings => write!(f, "open_settings"),
       MenuEvent::BrowseWidgets => write!(f, "browse_widgets"),
+      MenuEvent::CheckForUpdates => write!(f, "check_for_updates"),
+      MenuEvent::InstallUpdates => write!(f, "install_updates"),
       MenuEvent::Exit => write!(f, "exit"),
       MenuEvent::EditWidgetPack { pack_id } => {
         write!(f, "edit_widget_pack_{}", pack_id)
@@ -106,8 +117,15 @@ impl FromStr for MenuEvent {
     match parts.as_slice() {
       ["show", "config", "folder"] => Ok(Self::ShowConfigFolder),
       ["reload", "configs"] => Ok(Self::ReloadConfigs),
+      ["toggle", "restore", "session", enable @ ("true" | "false")] => {
+        Ok(Self::ToggleRestoreSession {
+          enable: *enable == "true",
+        })
+      }
       ["open", "settings"] => Ok(Self::OpenSettings),
       ["browse", "widgets"] => Ok(Self::BrowseWidgets),
+      ["check", "for", "updates"] => Ok(Self::CheckForUpdates),
+      ["install", "updates"] => Ok(Self::InstallUpdates),
       ["exit"] => Ok(Self::Exit),
       ["edit", "widget", "pack", pack_id] => Ok(Self::EditWidgetPack {
         pack_id: pack_id.to_string(),
@@ -153,6 +171,7 @@ enum SettingsRoute {
 pub struct SysTray {
   app_handle: AppHandle,
   app_settings: Arc<AppSettings>,
+  marketplace_updater: Arc<MarketplaceUpdater>,
   tray_icon: Option<TrayIcon>,
   widget_factory: Arc<WidgetFactory>,
   widget_pack_manager: Arc<WidgetPackManager>,
@@ -163,12 +182,14 @@ impl SysTray {
   pub async fn new(
     app_handle: &AppHandle,
     app_settings: Arc<AppSettings>,
+    marketplace_updater: Arc<MarketplaceUpdater>,
     widget_pack_manager: Arc<WidgetPackManager>,
     widget_factory: Arc<WidgetFactory>,
   ) -> anyhow::Result<SysTray> {
     let mut sys_tray = Self {
       app_handle: app_handle.clone(),
       app_settings,
+      marketplace_updater,
       tray_icon: None,
       widget_factory,
       widget_pack_manager,
@@ -180,7 +201,8 @@ impl SysTray {
   }
 
   async fn create_tray_icon(&self) -> anyhow::Result<TrayIcon> {
-    let tooltip = format!("Zebar v{}", VERSION_NUMBER);
+    let update_count =
+      self.marketplace_updater.available_updates().await.len();
 
     // Linting: `mut` needed for Windows where `tray_icon` is modified with
     // additional click handler.
@@ -188,9 +210,10 @@ impl SysTray {
     let mut tray_icon = TrayIconBuilder::with_id("tray")
       .icon(self.icon_image()?)
       .menu(&self.create_tray_menu().await?)
-      .tooltip(tooltip)
+      .tooltip(Self::tooltip(update_count))
       .on_menu_event({
         let app_settings = self.app_settings.clone();
+        let marketplace_updater = self.marketplace_updater.clone();
         let widget_factory = self.widget_factory.clone();
         let widget_pack_manager = self.widget_pack_manager.clone();
 
@@ -200,6 +223,7 @@ impl SysTray {
               menu_event,
               app_handle.clone(),
               app_settings.clone(),
+              marketplace_updater.clone(),
               widget_factory.clone(),
               widget_pack_manager.clone(),
             );
@@ -215,6 +239,7 @@ impl SysTray {
         .on_tray_icon_event({
           let app_handle = self.app_handle.clone();
           let app_settings = self.app_settings.clone();
+          let marketplace_updater = self.marketplace_updater.clone();
           let widget_factory = self.widget_factory.clone();
           let widget_pack_manager = self.widget_pack_manager.clone();
 
@@ -229,6 +254,7 @@ impl SysTray {
                 MenuEvent::OpenSettings,
                 app_handle.clone(),
                 app_settings.clone(),
+                marketplace_updater.clone(),
                 widget_factory.clone(),
                 widget_pack_manager.clone(),
               );
@@ -237,7 +263,10 @@ impl SysTray {
         });
     }
 
-    Ok(tray_icon.build(&self.app_handle)?)
+    let tray_icon = tray_icon.build(&self.app_handle)?;
+    Self::set_update_badge(&tray_icon, update_count)?;
+
+    Ok(tray_icon)
   }
 
   pub async fn refresh(&self) -> anyhow::Result<()> {
@@ -246,11 +275,45 @@ impl SysTray {
     if let Some(tray_icon) = self.tray_icon.as_ref() {
       let tray_menu = self.create_tray_menu().await?;
       tray_icon.set_menu(Some(tray_menu))?;
+
+      let update_count =
+        self.marketplace_updater.available_updates().await.len();
+
+      tray_icon.set_tooltip(Some(Self::tooltip(update_count)))?;
+      Self::set_update_badge(tray_icon, update_count)?;
     }
 
     Ok(())
   }
 
+  /// Returns the tooltip for the system tray icon.
+  fn tooltip(update_count: usize) -> String {
+    match update_count {
+      0 => format!("Zebar v{}", VERSION_NUMBER),
+      1 => format!("Zebar v{} (1 update available)", VERSION_NUMBER),
+      _ => format!(
+        "Zebar v{} ({} updates available)",
+        VERSION_NUMBER, update_count
+      ),
+    }
+  }
+
+  /// Shows the number of available updates next to the tray icon.
+  ///
+  /// Only supported on MacOS and Linux; on Windows, the count is shown in
+  /// the tooltip and menu instead.
+  fn set_update_badge(
+    tray_icon: &TrayIcon,
+    update_count: usize,
+  ) -> anyhow::Result<()> {
+    let title = match update_count {
+      0 => None,
+      _ => Some(update_count.to_string()),
+    };
+
+    Ok(tray_icon.set_title(title)?)
+  }
+
   /// Returns the image to use for the system tray icon.
   fn icon_image(&self) -> anyhow::Result<Image> {
     self
@@ -263,8 +326,14 @@ impl SysTray {
   /// Creates and returns the main system tray menu.
   async fn create_tray_menu(&self) -> anyhow::Result<Menu<Wry>> {
     let widget_packs = self.widget_pack_manager.widget_packs().await;
-    let widget_states = self.widget_factory.states().await;
+    let mut widget_states = self.widget_factory.states().await;
     let startup_configs = self.app_settings.startup_configs().await;
+    let restore_session = self.app_settings.restore_session().await;
+    let update_count =
+      self.marketplace_updater.available_updates().await.len();
+
+    // Widgets that are hidden while being kept alive are shown as stopped.
+    widget_states.retain(|_, state| !state.is_hidden);
 
     let configs_menu = self.create_packs_menu(
       &widget_packs,
@@ -272,9 +341,31 @@ impl SysTray {
       &startup_configs,
     )?;
 
+    let restore_session_item = CheckMenuItem::with_id(
+      &self.app_handle,
+      MenuEvent::ToggleRestoreSession {
+        enable: !restore_session,
+      },
+      "Restore widgets on start-up",
+      true,
+      restore_session,
+      None::<&str>,
+    )?;
+
     let mut tray_menu = MenuBuilder::new(&self.app_handle)
       .text(MenuEvent::OpenSettings, "Open settings")
-      .text(MenuEvent::BrowseWidgets, "Browse widgets...")
+      .text(MenuEvent::BrowseWidgets, "Browse widgets...");
+
+    tray_menu = match update_count {
+      0 => tray_menu
+        .text(MenuEvent::CheckForUpdates, "Check for widget pack updates"),
+      _ => tray_menu.text(
+        MenuEvent::InstallUpdates,
+        format!("Update widget packs ({})", update_count),
+      ),
+    };
+
+    tray_menu = tray_menu
       .item(&configs_menu)
       .text(MenuEvent::ReloadConfigs, {
         #[cfg(windows)]
@@ -287,6 +378,7 @@ impl SysTray {
           "Empty cache & reload configs"
         }
       })
+      .item(&restore_session_item)
       .separator();
 
     // Add submenus for currently active widget packs.
@@ -336,6 +428,7 @@ impl SysTray {
     event: MenuEvent,
     app_handle: AppHandle,
     app_settings: Arc<AppSettings>,
+    marketplace_updater: Arc<MarketplaceUpdater>,
     widget_factory: Arc<WidgetFactory>,
     widget_pack_manager: Arc<WidgetPackManager>,
   ) {
@@ -354,6 +447,13 @@ impl SysTray {
           let _ = widget_pack_manager.reload().await;
           widget_factory.relaunch_all().await
         }
+        MenuEvent::ToggleRestoreSession { enable } => {
+          match app_settings.set_restore_session(enable).await {
+            // Snapshot the widgets that are currently open.
+            Ok(()) => widget_factory.save_session().await,
+            Err(err) => Err(err),
+          }
+        }
         MenuEvent::OpenSettings => {
           Self::open_settings_window(&app_handle, SettingsRoute::Index)
         }
@@ -361,6 +461,12 @@ impl SysTray {
           &app_handle,
           SettingsRoute::Marketplace,
         ),
+        MenuEvent::CheckForUpdates => {
+          marketplace_updater.check().await.map(|_| ())
+        }
+        MenuEvent::InstallUpdates => {
+          marketplace_updater.install_all().await
+        }
         MenuEvent::Exit => {
           app_handle.exit(0);
           Ok(())
@@ -2,35 +2,48 @@ use std::{
   collections::HashMap,
   path::PathBuf,
   sync::{
-    atomic::{AtomicU32, Ordering},
+    atomic::{AtomicBool, AtomicU32, Ordering},
     Arc,
   },
+  time::{Duration, Instant},
 };
 
 use anyhow::{bail, Context};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tauri::{
   self, AppHandle, Manager, PhysicalPosition, PhysicalSize, WebviewUrl,
   WebviewWindowBuilder, WindowEvent,
 };
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
 use tokio::{
   sync::{broadcast, Mutex},
   task,
 };
 use tracing::{error, info};
 
+#[cfg(target_os = "linux")]
+use crate::common::linux::WindowExtLinux;
 #[cfg(target_os = "macos")]
 use crate::common::macos::WindowExtMacOs;
 #[cfg(target_os = "windows")]
 use crate::common::windows::{remove_app_bar, WindowExtWindows};
 use crate::{
-  app_settings::AppSettings,
-  asset_server::create_init_url,
+  app_settings::{AppSettings, StartupConfig},
+  asset_server::{self, create_widget_url},
   common::PathExt,
-  monitor_state::{Monitor, MonitorState},
+  dock_layout::{self, DockSlot},
+  keep_alive,
+  monitor_state::{Monitor, MonitorDiff, MonitorState},
+  popup_placement::{self, AnchorTarget, TargetRect},
   widget_pack::{
-    AnchorPoint, DockConfig, DockEdge, WidgetConfig, WidgetPack,
-    WidgetPackManager, WidgetPlacement, ZOrder,
+    AnchorPoint, DockConfig, DockEdge, MonitorSelection, WidgetConfig,
+    WidgetPack, WidgetPackManager, WidgetPlacement, WidgetVisibility,
+    ZOrder,
+  },
+  widget_session::Session,
+  widget_template,
+  widget_visibility::{
+    self, VisibilityChange, VisibilityState, SLIDE_STEP_DELAY,
   },
 };
 
@@ -54,6 +67,16 @@ pub struct WidgetFactory {
 
   pub open_tx: broadcast::Sender<WidgetState>,
 
+  _visibility_rx: broadcast::Receiver<VisibilityChange>,
+
+  pub visibility_tx: broadcast::Sender<VisibilityChange>,
+
+  _keep_alive_rx: broadcast::Receiver<WidgetState>,
+
+  /// Emits when a widget is hidden or shown via `hide_by_id` or
+  /// `show_by_id`.
+  pub keep_alive_tx: broadcast::Sender<WidgetState>,
+
   /// Reference to `MonitorState`.
   monitor_state: Arc<MonitorState>,
 
@@ -65,6 +88,17 @@ pub struct WidgetFactory {
 
   /// Map of widget ID's to their states.
   widget_states: Arc<Mutex<HashMap<String, WidgetState>>>,
+
+  /// Whether the session is no longer saved (i.e. when exiting).
+  is_session_frozen: AtomicBool,
+
+  /// Conditional startup configs whose conditions were met when last
+  /// evaluated.
+  matched_startup_configs: Mutex<Vec<StartupConfig>>,
+
+  /// Serializes visibility changes, since sliding a widget in or out
+  /// spans several awaits.
+  visibility_lock: Mutex<()>,
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -97,13 +131,45 @@ pub struct WidgetState {
 
   /// How the widget was opened.
   pub open_options: WidgetOpenOptions,
+
+  /// Monitor that the widget was placed on.
+  #[serde(skip)]
+  pub monitor: Monitor,
+
+  /// Screen space reserved by the widget if it's docked to an edge.
+  #[serde(skip)]
+  pub dock_slot: Option<DockSlot>,
+
+  /// Offset from the monitor edge that the docked widget is currently
+  /// placed at.
+  #[serde(skip)]
+  pub dock_offset: Option<i32>,
+
+  /// Visibility mode of the widget. `None` if the widget is always shown.
+  #[serde(skip)]
+  pub visibility: Option<VisibilityState>,
+
+  /// Whether the widget's window is hidden while being kept alive.
+  pub is_hidden: bool,
+
+  /// When the widget was hidden. Used to evict long-hidden widgets.
+  #[serde(skip)]
+  pub hidden_at: Option<Instant>,
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum WidgetOpenOptions {
   Standalone(WidgetPlacement),
   Preset(String),
+
+  /// Placed next to the anchor target rather than relative to the
+  /// monitor. Popups are closed when they lose focus.
+  #[serde(rename_all = "camelCase")]
+  Popup {
+    placement: WidgetPlacement,
+    anchor_target: AnchorTarget,
+  },
 }
 
 struct WidgetCoordinates {
@@ -167,6 +233,8 @@ impl WidgetFactory {
   ) -> Self {
     let (open_tx, _open_rx) = broadcast::channel(16);
     let (close_tx, _close_rx) = broadcast::channel(16);
+    let (visibility_tx, _visibility_rx) = broadcast::channel(16);
+    let (keep_alive_tx, _keep_alive_rx) = broadcast::channel(16);
 
     Self {
       app_handle: app_handle.clone(),
@@ -176,9 +244,16 @@ impl WidgetFactory {
       widget_pack_manager,
       _open_rx,
       open_tx,
+      _visibility_rx,
+      visibility_tx,
+      _keep_alive_rx,
+      keep_alive_tx,
       monitor_state,
       widget_count: Arc::new(AtomicU32::new(0)),
       widget_states: Arc::new(Mutex::new(HashMap::new())),
+      is_session_frozen: AtomicBool::new(false),
+      matched_startup_configs: Mutex::new(Vec::new()),
+      visibility_lock: Mutex::new(()),
     }
   }
 
@@ -228,174 +303,303 @@ impl WidgetFactory {
         )
       })?;
 
-    // No-op if preset is already open.
+    // No-op if preset is already open. Presets that are kept alive are
+    // shown again instead.
     if let WidgetOpenOptions::Preset(_) = open_options {
-      let is_preset_open = {
+      let preset_states = {
         self
           .widget_states
           .lock()
           .await
           .values()
-          .find(|state| {
+          .filter(|state| {
             state.pack_id == widget_pack.id
               && state.name == widget_config.name
               && state.open_options == *open_options
           })
-          .is_some()
+          .map(|state| (state.id.clone(), state.is_hidden))
+          .collect::<Vec<_>>()
       };
 
-      if is_preset_open {
+      if !preset_states.is_empty() {
+        for (widget_id, is_hidden) in preset_states {
+          if is_hidden {
+            self.show_by_id(&widget_id).await?;
+          }
+        }
+
         return Ok(());
       }
     }
 
-    // Extract placement from widget preset (if applicable).
-    let placement = match open_options {
-      WidgetOpenOptions::Standalone(placement) => placement,
-      WidgetOpenOptions::Preset(name) => {
-        &widget_config
-          .presets
-          .iter()
-          .find(|preset| preset.name == *name)
-          .with_context(|| {
-            format!(
-              "No preset with name '{}' at config '{}'.",
-              name,
-              widget_pack.config_path.display()
-            )
-          })?
-          .placement
+    let placement =
+      Self::placement(widget_pack, widget_config, open_options)?;
+
+    let all_coordinates = match open_options {
+      WidgetOpenOptions::Popup { anchor_target, .. } => {
+        vec![self.popup_coordinates(placement, anchor_target).await?]
       }
+      _ => self.widget_coordinates(placement).await,
     };
 
-    for coordinates in self.widget_coordinates(placement).await {
-      let new_count =
-        self.widget_count.fetch_add(1, Ordering::Relaxed) + 1;
+    for coordinates in all_coordinates {
+      self
+        .create_window(
+          widget_pack,
+          widget_config,
+          placement,
+          open_options,
+          is_preview,
+          coordinates,
+        )
+        .await?;
+    }
 
-      // Use running widget count as a unique label for the Tauri window.
-      let widget_id = format!("widget-{}", new_count);
+    Ok(())
+  }
 
-      info!(
-        "Creating window {} for {} from {}",
-        widget_id, widget_name, widget_pack.id
-      );
+  /// Extracts the placement from the widget preset (if applicable).
+  fn placement<'a>(
+    widget_pack: &WidgetPack,
+    widget_config: &'a WidgetConfig,
+    open_options: &'a WidgetOpenOptions,
+  ) -> anyhow::Result<&'a WidgetPlacement> {
+    match open_options {
+      WidgetOpenOptions::Standalone(placement)
+      | WidgetOpenOptions::Popup { placement, .. } => Ok(placement),
+      WidgetOpenOptions::Preset(name) => widget_config
+        .presets
+        .iter()
+        .find(|preset| preset.name == *name)
+        .map(|preset| &preset.placement)
+        .with_context(|| {
+          format!(
+            "No preset with name '{}' at config '{}'.",
+            name,
+            widget_pack.config_path.display()
+          )
+        }),
+    }
+  }
 
-      let html_path =
-        widget_pack.directory_path.join(&widget_config.html_path);
+  /// Creates a widget window at the given coordinates.
+  async fn create_window(
+    &self,
+    widget_pack: &WidgetPack,
+    widget_config: &WidgetConfig,
+    placement: &WidgetPlacement,
+    open_options: &WidgetOpenOptions,
+    is_preview: bool,
+    coordinates: WidgetCoordinates,
+  ) -> anyhow::Result<()> {
+    let widget_name = &widget_config.name;
+    let is_popup = matches!(open_options, WidgetOpenOptions::Popup { .. });
+    let new_count = self.widget_count.fetch_add(1, Ordering::Relaxed) + 1;
 
-      if !html_path.exists() {
-        bail!(
-          "HTML file not found at '{}' for config '{}'.",
-          widget_config.html_path.display(),
-          widget_pack.config_path.display()
-        )
-      }
+    // Use running widget count as a unique label for the Tauri window.
+    let widget_id = format!("widget-{}", new_count);
 
-      let webview_url = WebviewUrl::External(
-        create_init_url(
-          &widget_pack.directory_path,
-          &html_path,
-          widget_pack.include_files(),
-        )
-        .await?,
-      );
+    info!(
+      "Creating window {} for {} from {}",
+      widget_id, widget_name, widget_pack.id
+    );
 
-      let mut state = WidgetState {
-        id: widget_id.clone(),
-        name: widget_name.to_string(),
-        pack_id: widget_pack.id.clone(),
-        window_handle: None,
-        config: widget_config.clone(),
-        html_path: html_path.canonicalize_pretty()?,
-        open_options: open_options.clone(),
-        is_preview,
-      };
+    let html_path =
+      widget_pack.directory_path.join(&widget_config.html_path);
 
-      let window = WebviewWindowBuilder::new(
-        &self.app_handle,
-        widget_id.clone(),
-        webview_url,
+    if !html_path.exists() {
+      bail!(
+        "HTML file not found at '{}' for config '{}'.",
+        widget_config.html_path.display(),
+        widget_pack.config_path.display()
       )
-      .title(format!("Zebar - {} / {}", widget_pack.id, widget_name))
-      .focused(widget_config.focused)
-      .skip_taskbar(!widget_config.shown_in_taskbar)
-      .visible_on_all_workspaces(true)
-      .transparent(widget_config.transparent)
-      .shadow(false)
-      .decorations(false)
-      .resizable(widget_config.resizable)
-      .initialization_script(&self.initialization_script(&state)?)
-      // Widgets from the same pack share their browser cache (i.e.
-      // `localStorage`, `sessionStorage`, SW cache, etc.).
-      // TODO: Add this as an ext method on the Tauri window.
-      .data_directory(
-        self.app_settings.webview_cache_dir.join(&widget_pack.id),
+    }
+
+    let webview_url = WebviewUrl::External(
+      create_widget_url(
+        &widget_id,
+        widget_pack,
+        widget_config,
+        widget_template::template_context(
+          &widget_id,
+          widget_pack,
+          widget_config,
+          open_options,
+          &coordinates.monitor,
+        )?,
+        self
+          .app_settings
+          .content_security_policy(&widget_pack.id)
+          .await
+          .as_deref(),
       )
-      .build()?;
+      .await?,
+    );
 
-      // Widget coordinates might be modified when docked to an edge.
-      let (size, position) = match placement.dock_to_edge.enabled {
-        false => (coordinates.size, coordinates.position),
-        true => self.dock_to_edge(
-          &window,
+    let mut state = WidgetState {
+      id: widget_id.clone(),
+      name: widget_name.to_string(),
+      pack_id: widget_pack.id.clone(),
+      window_handle: None,
+      config: widget_config.clone(),
+      html_path: html_path.canonicalize_pretty()?,
+      open_options: open_options.clone(),
+      is_preview,
+      monitor: coordinates.monitor.clone(),
+      // Popups are never docked, since they're placed next to their
+      // anchor target instead.
+      dock_slot: match is_popup {
+        true => None,
+        false => Self::dock_slot(
+          &widget_id,
+          new_count,
           &placement.dock_to_edge,
           &coordinates,
-        )?,
-      };
+        ),
+      },
+      dock_offset: None,
+      visibility: None,
+      is_hidden: false,
+      hidden_at: None,
+    };
 
-      // Adjust the z-order of the window.
-      Self::set_z_order(&window, &widget_config.z_order, placement)?;
+    if placement.visibility != WidgetVisibility::Always {
+      state.visibility = Some(VisibilityState {
+        visibility: placement.visibility.clone(),
+        edge: state
+          .dock_slot
+          .as_ref()
+          .map(|slot| slot.edge)
+          .unwrap_or_else(|| coordinates.closest_edge()),
+        hidden_from: None,
+      });
+    }
 
-      info!("Positioning widget to {:?} {:?}", size, position);
+    let window = WebviewWindowBuilder::new(
+      &self.app_handle,
+      widget_id.clone(),
+      webview_url,
+    )
+    .title(format!("Zebar - {} / {}", widget_pack.id, widget_name))
+    .focused(widget_config.focused || is_popup)
+    .skip_taskbar(!widget_config.shown_in_taskbar)
+    .visible_on_all_workspaces(true)
+    .transparent(widget_config.transparent)
+    .shadow(false)
+    .decorations(false)
+    .resizable(widget_config.resizable)
+    .initialization_script(&self.initialization_script(&state)?)
+    // Widgets from the same pack share their browser cache (i.e.
+    // `localStorage`, `sessionStorage`, SW cache, etc.).
+    // TODO: Add this as an ext method on the Tauri window.
+    .data_directory(
+      self.app_settings.webview_cache_dir.join(&widget_pack.id),
+    );
 
-      // On Windows, we need to set the position twice to account for
-      // different monitor scale factors. Using the logical position/size
-      // positions the window incorrectly (see: https://github.com/glzr-io/zebar/issues/273).
-      #[cfg(windows)]
-      {
-        let _ = window.set_size(size);
-        let _ = window.set_position(position);
-        let _ = window.set_size(size);
-        let _ = window.set_position(position);
-      }
+    // On Linux, docked windows are created hidden, since they can only be
+    // made into layer surfaces (on Wayland) before they're first shown.
+    #[cfg(target_os = "linux")]
+    let window = window.visible(state.dock_slot.is_none());
 
-      // On macOS/Linux, convert to logical coordinates using the target
-      // monitor's scale factor. Using the physical position/size positions
-      // the window incorrectly.
-      #[cfg(not(windows))]
-      {
-        let scale_factor = coordinates.monitor.scale_factor as f64;
-        let _ = window.set_size(size.to_logical::<f64>(scale_factor));
-        let _ =
-          window.set_position(position.to_logical::<f64>(scale_factor));
+    let window = window.build()?;
+
+    #[cfg(target_os = "linux")]
+    if state.dock_slot.is_some() {
+      if let Err(err) = window.as_ref().window().prepare_app_bar() {
+        tracing::warn!("Failed to prepare window as app bar: {:?}", err);
       }
 
-      // On Windows, Tauri's `skip_taskbar` option isn't 100% reliable,
-      // so we also set the window as a tool window.
-      #[cfg(target_os = "windows")]
-      let _ = window
-        .as_ref()
-        .window()
-        .set_tool_window(!widget_config.shown_in_taskbar);
+      window.show()?;
+    }
 
-      // Store the underlying window handle (Windows only).
-      #[cfg(target_os = "windows")]
-      {
-        state.window_handle = {
-          let handle =
-            window.hwnd().context("Failed to get window handle.")?;
+    // Docked widgets are positioned once their state is stored, since
+    // they're stacked with other widgets docked to the same edge.
+    if state.dock_slot.is_none() {
+      self.position_window(
+        &window,
+        coordinates.size,
+        coordinates.position,
+        coordinates.monitor.scale_factor,
+      )?;
+    }
 
-          Some(handle.0 as isize)
-        };
-      }
+    // Adjust the z-order of the window.
+    Self::set_z_order(&window, &widget_config.z_order, placement)?;
 
-      {
-        let mut widget_states = self.widget_states.lock().await;
-        widget_states.insert(state.id.clone(), state.clone());
-      }
+    // On Windows, Tauri's `skip_taskbar` option isn't 100% reliable, so
+    // we also set the window as a tool window.
+    #[cfg(target_os = "windows")]
+    let _ = window
+      .as_ref()
+      .window()
+      .set_tool_window(!widget_config.shown_in_taskbar);
+
+    // Store the underlying window handle (Windows only).
+    #[cfg(target_os = "windows")]
+    {
+      state.window_handle = {
+        let handle =
+          window.hwnd().context("Failed to get window handle.")?;
+
+        Some(handle.0 as isize)
+      };
+    }
+
+    {
+      let mut widget_states = self.widget_states.lock().await;
+      widget_states.insert(state.id.clone(), state.clone());
+    }
+
+    if state.dock_slot.is_some() {
+      self.restack_docked().await?;
+    }
+
+    self.register_window_events(&window, widget_id.clone(), is_popup)?;
+
+    if let Some(visibility) = &state.visibility {
+      self
+        .watch_visibility(&widget_id, &visibility.visibility)
+        .await?;
+    }
+
+    self.open_tx.send(state)?;
+
+    Ok(())
+  }
 
-      self.register_window_events(&window, widget_id)?;
-      self.open_tx.send(state)?;
+  /// Moves and resizes the window to the given size and position.
+  fn position_window(
+    &self,
+    window: &tauri::WebviewWindow,
+    size: PhysicalSize<i32>,
+    position: PhysicalPosition<i32>,
+    scale_factor: f32,
+  ) -> anyhow::Result<()> {
+    info!(
+      "Positioning widget to {:?} {:?} with scale factor {}.",
+      size, position, scale_factor
+    );
+
+    // On Windows, we need to set the position twice to account for
+    // different monitor scale factors. Using the logical position/size
+    // positions the window incorrectly (see: https://github.com/glzr-io/zebar/issues/273).
+    #[cfg(windows)]
+    {
+      let _ = window.set_size(size);
+      let _ = window.set_position(position);
+      let _ = window.set_size(size);
+      let _ = window.set_position(position);
+    }
+
+    // On macOS/Linux, convert to logical coordinates using the target
+    // monitor's scale factor. Using the physical position/size positions
+    // the window incorrectly.
+    #[cfg(not(windows))]
+    {
+      let scale_factor = scale_factor as f64;
+      let _ = window.set_size(size.to_logical::<f64>(scale_factor));
+      let _ =
+        window.set_position(position.to_logical::<f64>(scale_factor));
     }
 
     Ok(())
@@ -448,289 +652,927 @@ impl WidgetFactory {
     }
   }
 
-  /// Dock the widget window to a given edge. This might result in the
-  /// window being resized or repositioned (e.g. if a window is already
-  /// docked to the given edge).
+  /// Gets the screen space to reserve for a widget that is docked to an
+  /// edge.
+  ///
+  /// Returns `None` if docking is disabled or not possible (e.g. on
+  /// macOS or with a centered anchor point).
+  fn dock_slot(
+    widget_id: &str,
+    sequence: u32,
+    dock_config: &DockConfig,
+    coords: &WidgetCoordinates,
+  ) -> Option<DockSlot> {
+    // Docking is not supported on macOS. Also disallow docking with a
+    // centered anchor point, since it doesn't make sense.
+    if !dock_config.enabled
+      || cfg!(target_os = "macos")
+      || coords.anchor == AnchorPoint::Center
+    {
+      return None;
+    }
+
+    let edge = dock_config.edge.unwrap_or_else(|| coords.closest_edge());
+
+    // Offset from the monitor edge to the window.
+    let offset = match edge {
+      DockEdge::Top => coords.offset.y,
+      DockEdge::Bottom => -coords.offset.y,
+      DockEdge::Left => coords.offset.x,
+      DockEdge::Right => -coords.offset.x,
+    };
+
+    // Length of the window perpendicular to the monitor edge.
+    let window_length = if edge.is_horizontal() {
+      coords.size.height
+    } else {
+      coords.size.width
+    };
+
+    // Margin to reserve *after* the window. Can be negative, but should
+    // not be smaller than the size of the window.
+    let window_margin = dock_config
+      .window_margin
+      .to_px_scaled(window_length as i32, coords.monitor.scale_factor)
+      .clamp(-coords.size.height, i32::MAX);
+
+    let monitor_length = if edge.is_horizontal() {
+      coords.monitor.height
+    } else {
+      coords.monitor.width
+    };
+
+    // Prevent the reserved amount from exceeding 50% of the monitor
+    // size. This maximum is arbitrary but should be sufficient for
+    // most cases.
+    let reserved_length = (offset + window_length + window_margin)
+      .clamp(0, monitor_length as i32 / 2);
+
+    Some(DockSlot {
+      widget_id: widget_id.to_string(),
+      monitor: coords.monitor.clone(),
+      edge,
+      order: dock_config.order,
+      sequence,
+      reserved_length,
+      window_margin,
+    })
+  }
+
+  /// Dock the widget window to a given edge, at the given offset from
+  /// the monitor edge. This might result in the window being resized or
+  /// repositioned (e.g. if a window is already docked to the given edge).
   ///
   /// Returns the new window size and position.
   fn dock_to_edge(
     &self,
     window: &tauri::WebviewWindow,
-    dock_config: &DockConfig,
-    coords: &WidgetCoordinates,
+    slot: &DockSlot,
+    stack_offset: i32,
   ) -> anyhow::Result<(PhysicalSize<i32>, PhysicalPosition<i32>)> {
-    #[cfg(not(target_os = "windows"))]
-    {
-      return Ok((coords.size, coords.position));
-    }
+    let monitor = &slot.monitor;
+    let edge = slot.edge;
+    let reserved_length = slot.reserved_length;
+    let window_margin = slot.window_margin;
+
+    let reserve_size = if edge.is_horizontal() {
+      PhysicalSize::new(monitor.width as i32, reserved_length)
+    } else {
+      PhysicalSize::new(reserved_length, monitor.height as i32)
+    };
 
-    #[cfg(target_os = "windows")]
-    {
-      // Disallow docking with a centered anchor point. Doesn't make sense.
-      if coords.anchor == AnchorPoint::Center {
-        return Ok((coords.size, coords.position));
+    let reserve_position = match edge {
+      DockEdge::Top => {
+        PhysicalPosition::new(monitor.x, monitor.y + stack_offset)
+      }
+      DockEdge::Left => {
+        PhysicalPosition::new(monitor.x + stack_offset, monitor.y)
       }
+      DockEdge::Bottom => PhysicalPosition::new(
+        monitor.x,
+        monitor.y + monitor.height as i32 - reserved_length - stack_offset,
+      ),
+      DockEdge::Right => PhysicalPosition::new(
+        monitor.x + monitor.width as i32 - reserved_length - stack_offset,
+        monitor.y,
+      ),
+    };
 
-      let edge = dock_config.edge.unwrap_or_else(|| coords.closest_edge());
+    // Unreachable on macOS, since `dock_slot` always returns `None`.
+    #[cfg(target_os = "macos")]
+    let (allocated_size, allocated_position) = {
+      let _ = window;
+      (reserve_size, reserve_position)
+    };
 
-      // Offset from the monitor edge to the window.
-      let offset = match edge {
-        DockEdge::Top => coords.offset.y,
-        DockEdge::Bottom => -coords.offset.y,
-        DockEdge::Left => coords.offset.x,
-        DockEdge::Right => -coords.offset.x,
-      };
+    #[cfg(not(target_os = "macos"))]
+    let (allocated_size, allocated_position) = window
+      .as_ref()
+      .window()
+      .allocate_app_bar(reserve_size, reserve_position, edge)?;
+
+    // Adjust the size to account for the window margin.
+    let final_size = if edge.is_horizontal() {
+      PhysicalSize::new(
+        allocated_size.width,
+        allocated_size.height.saturating_sub(window_margin.abs()),
+      )
+    } else {
+      PhysicalSize::new(
+        allocated_size.width.saturating_sub(window_margin.abs()),
+        allocated_size.height,
+      )
+    };
 
-      // Length of the window perpendicular to the monitor edge.
-      let window_length = if edge.is_horizontal() {
-        coords.size.height
-      } else {
-        coords.size.width
-      };
+    // Adjust position if we're docked to bottom or right edge to account
+    // for the size reduction.
+    let final_position = match edge {
+      DockEdge::Bottom => PhysicalPosition::new(
+        allocated_position.x,
+        allocated_position.y + (allocated_size.height - final_size.height),
+      ),
+      DockEdge::Right => PhysicalPosition::new(
+        allocated_position.x + (allocated_size.width - final_size.width),
+        allocated_position.y,
+      ),
+      _ => allocated_position,
+    };
 
-      // Margin to reserve *after* the window. Can be negative, but should
-      // not be smaller than the size of the window.
-      let window_margin = dock_config
-        .window_margin
-        .to_px_scaled(window_length as i32, coords.monitor.scale_factor)
-        .clamp(-coords.size.height, i32::MAX);
-
-      let monitor_length = if edge.is_horizontal() {
-        coords.monitor.height
-      } else {
-        coords.monitor.width
-      };
+    tracing::info!(
+      "Docked widget to edge '{:?}' with size {:?} and position {:?}.",
+      edge,
+      final_size,
+      final_position
+    );
 
-      // Prevent the reserved amount from exceeding 50% of the monitor
-      // size. This maximum is arbitrary but should be sufficient for
-      // most cases.
-      let reserved_length = (offset + window_length + window_margin)
-        .clamp(0, monitor_length as i32 / 2);
+    Ok((final_size, final_position))
+  }
 
-      let reserve_size = if edge.is_horizontal() {
-        PhysicalSize::new(coords.monitor.width as i32, reserved_length)
-      } else {
-        PhysicalSize::new(reserved_length, coords.monitor.height as i32)
-      };
+  /// Repositions docked widgets, such that widgets docked to the same
+  /// edge of a monitor are stacked rather than overlapping.
+  ///
+  /// Only widgets whose offset from the edge has changed are moved (e.g.
+  /// when a docked neighbor is opened or closed).
+  pub async fn restack_docked(&self) -> anyhow::Result<()> {
+    let docked_slots = {
+      self
+        .widget_states
+        .lock()
+        .await
+        .values()
+        // Hidden widgets don't reserve any space.
+        .filter(|state| {
+          !state.is_hidden
+            && !state
+              .visibility
+              .as_ref()
+              .is_some_and(|visibility| visibility.hidden_from.is_some())
+        })
+        .filter_map(|state| {
+          state
+            .dock_slot
+            .clone()
+            .map(|slot| (slot, state.dock_offset))
+        })
+        .collect::<Vec<_>>()
+    };
 
-      let reserve_position = match edge {
-        DockEdge::Top | DockEdge::Left => {
-          PhysicalPosition::new(coords.monitor.x, coords.monitor.y)
-        }
-        DockEdge::Bottom => PhysicalPosition::new(
-          coords.monitor.x,
-          coords.monitor.y + coords.monitor.height as i32
-            - reserved_length,
-        ),
-        DockEdge::Right => PhysicalPosition::new(
-          coords.monitor.x + coords.monitor.width as i32 - reserved_length,
-          coords.monitor.y,
-        ),
-      };
+    let offsets = dock_layout::stack_offsets(
+      &docked_slots
+        .iter()
+        .map(|(slot, _)| slot.clone())
+        .collect::<Vec<_>>(),
+    );
 
-      let (allocated_size, allocated_position) = window
-        .as_ref()
-        .window()
-        .allocate_app_bar(reserve_size, reserve_position, edge)?;
+    let mut moved_slots = docked_slots
+      .into_iter()
+      .filter(|(slot, current_offset)| {
+        *current_offset != Some(offsets[&slot.widget_id])
+      })
+      .collect::<Vec<_>>();
+
+    // Release all space being moved first, so that the re-allocated
+    // space doesn't collide with a widget's previous position.
+    #[cfg(not(target_os = "macos"))]
+    for (slot, current_offset) in &moved_slots {
+      if let (Some(_), Some(window)) = (
+        current_offset,
+        self.app_handle.get_webview_window(&slot.widget_id),
+      ) {
+        let _ = window.as_ref().window().deallocate_app_bar();
+      }
+    }
 
-      // Adjust the size to account for the window margin.
-      let final_size = if edge.is_horizontal() {
-        PhysicalSize::new(
-          allocated_size.width,
-          allocated_size.height.saturating_sub(window_margin.abs()),
-        )
-      } else {
-        PhysicalSize::new(
-          allocated_size.width.saturating_sub(window_margin.abs()),
-          allocated_size.height,
-        )
-      };
+    // Allocate space starting from the widget closest to the edge.
+    moved_slots.sort_by_key(|(slot, _)| (slot.order, slot.sequence));
 
-      // Adjust position if we're docked to bottom or right edge to account
-      // for the size reduction.
-      let final_position = match edge {
-        DockEdge::Bottom => PhysicalPosition::new(
-          allocated_position.x,
-          allocated_position.y
-            + (allocated_size.height - final_size.height),
-        ),
-        DockEdge::Right => PhysicalPosition::new(
-          allocated_position.x + (allocated_size.width - final_size.width),
-          allocated_position.y,
-        ),
-        _ => allocated_position,
+    for (slot, _) in moved_slots {
+      let Some(window) =
+        self.app_handle.get_webview_window(&slot.widget_id)
+      else {
+        continue;
       };
 
-      tracing::info!(
-        "Docked widget to edge '{:?}' with size {:?} and position {:?}.",
-        edge,
-        final_size,
-        final_position
-      );
-
-      Ok((final_size, final_position))
-    }
-  }
+      let offset = offsets[&slot.widget_id];
+      let (size, position) = self.dock_to_edge(&window, &slot, offset)?;
 
-  /// Opens presets that are configured to be launched on startup.
-  pub async fn startup(&self) -> anyhow::Result<()> {
-    let startup_configs = self.app_settings.startup_configs().await;
+      self.position_window(
+        &window,
+        size,
+        position,
+        slot.monitor.scale_factor,
+      )?;
 
-    for startup_config in startup_configs {
-      if let Err(err) = self
-        .start_widget_by_id(
-          &startup_config.pack,
-          &startup_config.widget,
-          &WidgetOpenOptions::Preset(startup_config.preset),
-          false,
-        )
-        .await
+      if let Some(state) =
+        self.widget_states.lock().await.get_mut(&slot.widget_id)
       {
-        tracing::error!("Failed to start widget on startup: {:?}", err);
+        state.dock_offset = Some(offset);
       }
     }
 
     Ok(())
   }
 
-  fn initialization_script(
+  /// Starts hiding and revealing the widget based on its visibility
+  /// mode.
+  async fn watch_visibility(
     &self,
-    state: &WidgetState,
-  ) -> anyhow::Result<String> {
-    let state_script =
-      format!("window.__ZEBAR_STATE={};", serde_json::to_string(state)?);
+    widget_id: &str,
+    visibility: &WidgetVisibility,
+  ) -> anyhow::Result<()> {
+    match visibility {
+      WidgetVisibility::Always => {}
+      WidgetVisibility::AutoHide {
+        hide_delay,
+        reveal_delay,
+      } => {
+        widget_visibility::watch_auto_hide(
+          self.app_handle.clone(),
+          self.widget_states.clone(),
+          widget_id.to_string(),
+          Duration::from_millis(*hide_delay),
+          Duration::from_millis(*reveal_delay),
+          self.visibility_tx.clone(),
+        );
+      }
+      WidgetVisibility::Hotkey { hotkey } => {
+        // The hotkey might already be registered by another widget
+        // (e.g. the same preset on a different monitor).
+        let shortcuts = self.app_handle.global_shortcut();
+
+        if !shortcuts.is_registered(hotkey.as_str()) {
+          let visibility_tx = self.visibility_tx.clone();
+          let hotkey_name = hotkey.clone();
+
+          shortcuts.on_shortcut(hotkey.as_str(), move |_, _, event| {
+            if event.state == ShortcutState::Pressed {
+              let _ = visibility_tx
+                .send(VisibilityChange::Hotkey(hotkey_name.clone()));
+            }
+          })?;
+        }
 
-    let sw_script = include_str!("../resources/initialization-script.js");
+        // Widgets with a hotkey are hidden until it's pressed.
+        self.set_hidden(widget_id, true).await?;
+      }
+    }
 
-    Ok(format!("{state_script}\n{sw_script}"))
+    Ok(())
   }
 
-  /// Registers window events for a given widget.
-  fn register_window_events(
+  /// Toggles all widgets that are shown and hidden by the given hotkey.
+  pub async fn toggle_by_hotkey(
     &self,
-    window: &tauri::WebviewWindow,
-    widget_id: String,
+    hotkey: &str,
   ) -> anyhow::Result<()> {
-    let widget_states = self.widget_states.clone();
-    let close_tx = self.close_tx.clone();
-
-    window.on_window_event(move |event| {
-      if let WindowEvent::Destroyed = event {
-        let widget_states = widget_states.clone();
-        let close_tx = close_tx.clone();
-        let widget_id = widget_id.clone();
-
-        task::spawn(async move {
-          let mut widget_states = widget_states.lock().await;
+    let toggled_widgets = {
+      self
+        .widget_states
+        .lock()
+        .await
+        .values()
+        .filter_map(|state| {
+          // Ignore widgets hidden while being kept alive.
+          if state.is_hidden {
+            return None;
+          }
 
-          // Remove the widget state.
-          let state = widget_states.remove(&widget_id);
+          let visibility = state.visibility.as_ref()?;
 
-          // Ensure appbar space is deallocated on close.
-          #[cfg(target_os = "windows")]
-          {
-            if let Some(window_handle) =
-              state.and_then(|state| state.window_handle)
-            {
-              let _ = remove_app_bar(window_handle);
+          match &visibility.visibility {
+            WidgetVisibility::Hotkey {
+              hotkey: widget_hotkey,
+            } if widget_hotkey == hotkey => {
+              Some((state.id.clone(), visibility.hidden_from.is_none()))
             }
+            _ => None,
           }
+        })
+        .collect::<Vec<_>>()
+    };
 
-          // Broadcast the close event.
-          if let Err(err) = close_tx.send(widget_id) {
-            error!("Failed to send window close event: {:?}", err);
-          }
-        });
-      }
-    });
+    for (widget_id, hidden) in toggled_widgets {
+      self.set_hidden(&widget_id, hidden).await?;
+    }
 
     Ok(())
   }
 
-  /// Returns coordinates for window placement based on the given config.
-  async fn widget_coordinates(
+  /// Slides the widget off-screen past its edge, or back into view.
+  ///
+  /// Docked widgets release their reserved space while hidden. No-op if
+  /// the widget is already in the given state, or if it's hidden while
+  /// being kept alive.
+  pub async fn set_hidden(
     &self,
-    placement: &WidgetPlacement,
-  ) -> Vec<WidgetCoordinates> {
-    let mut coordinates = vec![];
+    widget_id: &str,
+    hidden: bool,
+  ) -> anyhow::Result<()> {
+    let _visibility_guard = self.visibility_lock.lock().await;
 
-    let monitors = self
-      .monitor_state
-      .monitors_by_selection(&placement.monitor_selection)
-      .await;
+    let (visibility, dock_offset, monitor) = {
+      let widget_states = self.widget_states.lock().await;
+      let state = widget_states
+        .get(widget_id)
+        .context("No widget found for the given widget ID.")?;
 
-    for monitor in monitors {
-      let monitor_width = monitor.width as i32;
-      let monitor_height = monitor.height as i32;
+      if state.is_hidden {
+        return Ok(());
+      }
 
-      // Pixel values should be scaled by the monitor's scale factor,
-      // whereas percentage values are left as-is. This is because the
-      // percentage values are already relative to the monitor's size.
-      let window_width = placement
-        .width
-        .to_px_scaled(monitor_width, monitor.scale_factor);
+      (
+        state
+          .visibility
+          .clone()
+          .context("Widget is always shown.")?,
+        state.dock_offset,
+        state.monitor.clone(),
+      )
+    };
 
-      let window_height = placement
-        .height
-        .to_px_scaled(monitor_height, monitor.scale_factor);
+    if visibility.hidden_from.is_some() == hidden {
+      return Ok(());
+    }
 
-      let window_size = PhysicalSize::new(window_width, window_height);
+    let window = self
+      .app_handle
+      .get_webview_window(widget_id)
+      .context("No Tauri window found for the given widget ID.")?;
 
-      let (anchor_x, anchor_y) = match placement.anchor {
-        AnchorPoint::TopLeft => (monitor.x, monitor.y),
-        AnchorPoint::TopCenter => (
-          monitor.x + (monitor_width / 2) - (window_size.width / 2),
-          monitor.y,
-        ),
-        AnchorPoint::TopRight => {
-          (monitor.x + monitor_width - window_size.width, monitor.y)
+    let size = window.outer_size()?;
+    let size = PhysicalSize::new(size.width as i32, size.height as i32);
+
+    match visibility.hidden_from {
+      None => {
+        let position = window.outer_position()?;
+        let target = widget_visibility::hidden_position(
+          &monitor,
+          visibility.edge,
+          position,
+          size,
+        );
+
+        info!(
+          "Hiding widget {} past {:?} edge.",
+          widget_id, visibility.edge
+        );
+
+        if let Some(state) =
+          self.widget_states.lock().await.get_mut(widget_id)
+        {
+          state.dock_offset = None;
+          state.visibility = Some(VisibilityState {
+            hidden_from: Some(position),
+            ..visibility
+          });
         }
-        AnchorPoint::CenterLeft => (
-          monitor.x,
-          monitor.y + (monitor_height / 2) - (window_size.height / 2),
-        ),
-        AnchorPoint::Center => (
-          monitor.x + (monitor_width / 2) - (window_size.width / 2),
-          monitor.y + (monitor_height / 2) - (window_size.height / 2),
-        ),
-        AnchorPoint::CenterRight => (
-          monitor.x + monitor_width - window_size.width,
-          monitor.y + (monitor_height / 2) - (window_size.height / 2),
-        ),
-        AnchorPoint::BottomLeft => {
-          (monitor.x, monitor.y + monitor_height - window_size.height)
+
+        // Release reserved space and let docked neighbors move in.
+        if dock_offset.is_some() {
+          #[cfg(not(target_os = "macos"))]
+          let _ = window.as_ref().window().deallocate_app_bar();
+
+          self.restack_docked().await?;
         }
-        AnchorPoint::BottomCenter => (
-          monitor.x + (monitor_width / 2) - (window_size.width / 2),
-          monitor.y + monitor_height - window_size.height,
-        ),
-        AnchorPoint::BottomRight => (
-          monitor.x + monitor_width - window_size.width,
-          monitor.y + monitor_height - window_size.height,
-        ),
+
+        self
+          .slide_window(&window, position, target, monitor.scale_factor)
+          .await;
+        window.hide()?;
+      }
+      Some(shown_position) => {
+        info!("Revealing widget {}.", widget_id);
+
+        let position = window.outer_position()?;
+        window.show()?;
+        self
+          .slide_window(
+            &window,
+            position,
+            shown_position,
+            monitor.scale_factor,
+          )
+          .await;
+
+        if let Some(state) =
+          self.widget_states.lock().await.get_mut(widget_id)
+        {
+          state.visibility = Some(VisibilityState {
+            hidden_from: None,
+            ..visibility
+          });
+        }
+
+        // Reserve space again for docked widgets.
+        self.restack_docked().await?;
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Animates the window from one position to another.
+  ///
+  /// Positions are converted the same way as in `position_window`.
+  async fn slide_window(
+    &self,
+    window: &tauri::WebviewWindow,
+    from: PhysicalPosition<i32>,
+    to: PhysicalPosition<i32>,
+    scale_factor: f32,
+  ) {
+    for position in widget_visibility::slide_positions(from, to) {
+      #[cfg(windows)]
+      let _ = (window.set_position(position), scale_factor);
+
+      #[cfg(not(windows))]
+      let _ = window
+        .set_position(position.to_logical::<f64>(scale_factor as f64));
+
+      tokio::time::sleep(SLIDE_STEP_DELAY).await;
+    }
+  }
+
+  /// Opens presets that are configured to be launched on startup.
+  pub async fn startup(&self) -> anyhow::Result<()> {
+    let startup_configs = self.matching_startup_configs().await;
+
+    *self.matched_startup_configs.lock().await = startup_configs
+      .iter()
+      .filter(|config| config.condition.is_some())
+      .cloned()
+      .collect();
+
+    // Read the last session before opening any widgets, since opening
+    // widgets overwrites the session file.
+    let session = match self.app_settings.restore_session().await {
+      true => match Session::read(&self.app_settings.session_file) {
+        Ok(session) => Some(session),
+        Err(err) => {
+          error!("Failed to read session file: {:?}", err);
+          None
+        }
+      },
+      false => None,
+    };
+
+    for startup_config in &startup_configs {
+      if let Err(err) = self
+        .start_widget_by_id(
+          &startup_config.pack,
+          &startup_config.widget,
+          &WidgetOpenOptions::Preset(startup_config.preset.clone()),
+          false,
+        )
+        .await
+      {
+        tracing::error!("Failed to start widget on startup: {:?}", err);
+      }
+    }
+
+    if let Some(session) = session {
+      // Presets whose startup conditions are no longer met shouldn't be
+      // reopened, even if they were open in the last session.
+      let unmet_configs = self
+        .app_settings
+        .startup_configs()
+        .await
+        .into_iter()
+        .filter(|config| !startup_configs.contains(config))
+        .collect::<Vec<_>>();
+
+      self
+        .restore_session(session.without_startup_configs(&unmet_configs))
+        .await;
+    }
+
+    Ok(())
+  }
+
+  /// Re-evaluates the conditions of startup configs (e.g. after monitors
+  /// are connected or disconnected).
+  ///
+  /// Opens widgets whose conditions are now met, and closes widgets
+  /// whose conditions are no longer met. Unconditional startup configs
+  /// are left as-is.
+  pub async fn reevaluate_startup_configs(&self) {
+    let matched_configs = self
+      .matching_startup_configs()
+      .await
+      .into_iter()
+      .filter(|config| config.condition.is_some())
+      .collect::<Vec<_>>();
+
+    let prev_matched_configs = std::mem::replace(
+      &mut *self.matched_startup_configs.lock().await,
+      matched_configs.clone(),
+    );
+
+    for config in &prev_matched_configs {
+      if !matched_configs.contains(config) {
+        info!(
+          "Startup condition no longer met for {} from {}.",
+          config.widget, config.pack
+        );
+
+        if let Err(err) = self
+          .stop_by_preset(&config.pack, &config.widget, &config.preset)
+          .await
+        {
+          error!("Failed to stop widget: {:?}", err);
+        }
+      }
+    }
+
+    for config in matched_configs {
+      if !prev_matched_configs.contains(&config) {
+        info!(
+          "Startup condition met for {} from {}.",
+          config.widget, config.pack
+        );
+
+        if let Err(err) = self
+          .start_widget_by_id(
+            &config.pack,
+            &config.widget,
+            &WidgetOpenOptions::Preset(config.preset),
+            false,
+          )
+          .await
+        {
+          error!("Failed to start widget: {:?}", err);
+        }
+      }
+    }
+  }
+
+  /// Gets the startup configs whose conditions are met with the
+  /// currently connected monitors.
+  async fn matching_startup_configs(&self) -> Vec<StartupConfig> {
+    let monitors = self
+      .monitor_state
+      .monitors_by_selection(&MonitorSelection::All)
+      .await;
+
+    self.app_settings.matching_startup_configs(&monitors).await
+  }
+
+  /// Reopens widgets from the last session.
+  ///
+  /// Widgets whose pack, widget, or preset no longer exists are skipped.
+  async fn restore_session(&self, session: Session) {
+    for widget in session.widgets {
+      let Some(widget_pack) = self
+        .widget_pack_manager
+        .widget_pack_by_id(&widget.pack_id)
+        .await
+      else {
+        info!("Skipping restore of removed pack {}.", widget.pack_id);
+        continue;
       };
 
-      let offset_x = placement
-        .offset_x
-        .to_px_scaled(monitor_width, monitor.scale_factor);
+      let Some(widget_config) = widget_pack
+        .config
+        .widgets
+        .iter()
+        .find(|config| config.name == widget.widget_name)
+      else {
+        info!(
+          "Skipping restore of removed widget {} from {}.",
+          widget.widget_name, widget.pack_id
+        );
+        continue;
+      };
 
-      let offset_y = placement
-        .offset_y
-        .to_px_scaled(monitor_height, monitor.scale_factor);
+      if let WidgetOpenOptions::Preset(preset_name) = &widget.open_options
+      {
+        if !widget_config
+          .presets
+          .iter()
+          .any(|preset| preset.name == *preset_name)
+        {
+          info!(
+            "Skipping restore of removed preset {} for {} from {}.",
+            preset_name, widget.widget_name, widget.pack_id
+          );
+          continue;
+        }
+      }
 
-      let window_position =
-        PhysicalPosition::new(anchor_x + offset_x, anchor_y + offset_y);
+      info!(
+        "Restoring widget {} from {}.",
+        widget.widget_name, widget.pack_id
+      );
 
-      coordinates.push(WidgetCoordinates {
-        size: window_size,
-        position: window_position,
-        offset: PhysicalPosition::new(offset_x, offset_y),
-        monitor: monitor.clone(),
-        anchor: placement.anchor,
-      });
+      if let Err(err) = self
+        .start_widget_by_pack(
+          &widget_pack,
+          &widget.widget_name,
+          &widget.open_options,
+          false,
+        )
+        .await
+      {
+        error!("Failed to restore widget: {:?}", err);
+      }
     }
+  }
+
+  /// Saves the currently open widgets to the session file, if session
+  /// restore is enabled.
+  pub async fn save_session(&self) -> anyhow::Result<()> {
+    if self.is_session_frozen.load(Ordering::Relaxed)
+      || !self.app_settings.restore_session().await
+    {
+      return Ok(());
+    }
+
+    let session =
+      Session::from_states(self.widget_states.lock().await.values());
+
+    session.write(&self.app_settings.session_file)
+  }
+
+  /// Stops saving the session, such that widgets that are closed on exit
+  /// are restored on the next launch.
+  pub fn freeze_session(&self) {
+    self.is_session_frozen.store(true, Ordering::Relaxed);
+  }
+
+  fn initialization_script(
+    &self,
+    state: &WidgetState,
+  ) -> anyhow::Result<String> {
+    let state_script =
+      format!("window.__ZEBAR_STATE={};", serde_json::to_string(state)?);
+
+    let origin_script = format!(
+      "window.__ZEBAR_ASSET_SERVER_ORIGIN={};",
+      serde_json::to_string(&asset_server::origin())?
+    );
+
+    let sw_script = include_str!("../resources/initialization-script.js");
+
+    Ok(format!("{state_script}\n{origin_script}\n{sw_script}"))
+  }
+
+  /// Registers window events for a given widget.
+  ///
+  /// Popups are closed once they lose focus.
+  fn register_window_events(
+    &self,
+    window: &tauri::WebviewWindow,
+    widget_id: String,
+    is_popup: bool,
+  ) -> anyhow::Result<()> {
+    let app_handle = self.app_handle.clone();
+    let widget_states = self.widget_states.clone();
+    let close_tx = self.close_tx.clone();
+
+    window.on_window_event(move |event| {
+      if let WindowEvent::Focused(false) = event {
+        if is_popup {
+          if let Some(window) = app_handle.get_webview_window(&widget_id) {
+            info!("Closing popup {} on focus loss.", widget_id);
+            let _ = window.close();
+          }
+        }
+      }
+
+      if let WindowEvent::Destroyed = event {
+        let app_handle = app_handle.clone();
+        let widget_states = widget_states.clone();
+        let close_tx = close_tx.clone();
+        let widget_id = widget_id.clone();
+
+        task::spawn(async move {
+          let mut widget_states = widget_states.lock().await;
+
+          // Remove the widget state.
+          let state = widget_states.remove(&widget_id);
+          asset_server::revoke_token(&widget_id).await;
+
+          // Unregister the widget's hotkey if no other widget uses it.
+          if let Some(WidgetVisibility::Hotkey { hotkey }) = state
+            .as_ref()
+            .and_then(|state| state.visibility.as_ref())
+            .map(|visibility| &visibility.visibility)
+          {
+            let is_hotkey_used = widget_states.values().any(|state| {
+              state.visibility.as_ref().is_some_and(|visibility| {
+                visibility.visibility
+                  == WidgetVisibility::Hotkey {
+                    hotkey: hotkey.clone(),
+                  }
+              })
+            });
+
+            if !is_hotkey_used {
+              let _ =
+                app_handle.global_shortcut().unregister(hotkey.as_str());
+            }
+          }
+
+          // Ensure appbar space is deallocated on close.
+          #[cfg(target_os = "windows")]
+          {
+            if let Some(window_handle) =
+              state.and_then(|state| state.window_handle)
+            {
+              let _ = remove_app_bar(window_handle);
+            }
+          }
+
+          // Broadcast the close event.
+          if let Err(err) = close_tx.send(widget_id) {
+            error!("Failed to send window close event: {:?}", err);
+          }
+        });
+      }
+    });
+
+    Ok(())
+  }
+
+  /// Returns coordinates for placing a popup next to its anchor target.
+  ///
+  /// The popup is placed on the monitor that the target is on, and its
+  /// size is relative to that monitor.
+  async fn popup_coordinates(
+    &self,
+    placement: &WidgetPlacement,
+    anchor_target: &AnchorTarget,
+  ) -> anyhow::Result<WidgetCoordinates> {
+    let target = match anchor_target {
+      AnchorTarget::Cursor => {
+        let cursor = self.app_handle.cursor_position()?;
+
+        TargetRect {
+          x: cursor.x as i32,
+          y: cursor.y as i32,
+          width: 0,
+          height: 0,
+        }
+      }
+      AnchorTarget::Rect {
+        widget_id,
+        x,
+        y,
+        width,
+        height,
+      } => {
+        let window = self
+          .app_handle
+          .get_webview_window(widget_id)
+          .context("No Tauri window found for the anchor target.")?;
+
+        // Convert from the widget's viewport to physical screen
+        // coordinates.
+        let origin = window.inner_position()?;
+        let scale_factor = window.scale_factor()?;
+
+        TargetRect {
+          x: origin.x + (x * scale_factor) as i32,
+          y: origin.y + (y * scale_factor) as i32,
+          width: (width * scale_factor) as i32,
+          height: (height * scale_factor) as i32,
+        }
+      }
+    };
+
+    let monitors = self
+      .monitor_state
+      .monitors_by_selection(&MonitorSelection::All)
+      .await;
+
+    let monitor = monitors
+      .iter()
+      .find(|monitor| target.is_centered_on(monitor))
+      .or_else(|| monitors.iter().find(|monitor| monitor.is_primary))
+      .or(monitors.first())
+      .cloned()
+      .context("No monitors available for placing popup.")?;
+
+    let mut coordinates = Self::monitor_coordinates(placement, monitor);
+
+    coordinates.position = popup_placement::popup_position(
+      target,
+      coordinates.size,
+      placement.anchor,
+      coordinates.offset,
+      &coordinates.monitor,
+    );
+
+    Ok(coordinates)
+  }
+
+  /// Returns coordinates for window placement based on the given config.
+  async fn widget_coordinates(
+    &self,
+    placement: &WidgetPlacement,
+  ) -> Vec<WidgetCoordinates> {
+    self
+      .monitor_state
+      .monitors_by_selection(&placement.monitor_selection)
+      .await
+      .into_iter()
+      .map(|monitor| Self::monitor_coordinates(placement, monitor))
+      .collect()
+  }
+
+  /// Returns coordinates for window placement on the given monitor.
+  fn monitor_coordinates(
+    placement: &WidgetPlacement,
+    monitor: Monitor,
+  ) -> WidgetCoordinates {
+    let monitor_width = monitor.width as i32;
+    let monitor_height = monitor.height as i32;
+
+    // Pixel values should be scaled by the monitor's scale factor,
+    // whereas percentage values are left as-is. This is because the
+    // percentage values are already relative to the monitor's size.
+    let window_width = placement
+      .width
+      .to_px_scaled(monitor_width, monitor.scale_factor);
+
+    let window_height = placement
+      .height
+      .to_px_scaled(monitor_height, monitor.scale_factor);
+
+    let window_size = PhysicalSize::new(window_width, window_height);
+
+    let (anchor_x, anchor_y) = match placement.anchor {
+      AnchorPoint::TopLeft => (monitor.x, monitor.y),
+      AnchorPoint::TopCenter => (
+        monitor.x + (monitor_width / 2) - (window_size.width / 2),
+        monitor.y,
+      ),
+      AnchorPoint::TopRight => {
+        (monitor.x + monitor_width - window_size.width, monitor.y)
+      }
+      AnchorPoint::CenterLeft => (
+        monitor.x,
+        monitor.y + (monitor_height / 2) - (window_size.height / 2),
+      ),
+      AnchorPoint::Center => (
+        monitor.x + (monitor_width / 2) - (window_size.width / 2),
+        monitor.y + (monitor_height / 2) - (window_size.height / 2),
+      ),
+      AnchorPoint::CenterRight => (
+        monitor.x + monitor_width - window_size.width,
+        monitor.y + (monitor_height / 2) - (window_size.height / 2),
+      ),
+      AnchorPoint::BottomLeft => {
+        (monitor.x, monitor.y + monitor_height - window_size.height)
+      }
+      AnchorPoint::BottomCenter => (
+        monitor.x + (monitor_width / 2) - (window_size.width / 2),
+        monitor.y + monitor_height - window_size.height,
+      ),
+      AnchorPoint::BottomRight => (
+        monitor.x + monitor_width - window_size.width,
+        monitor.y + monitor_height - window_size.height,
+      ),
+    };
+
+    let offset_x = placement
+      .offset_x
+      .to_px_scaled(monitor_width, monitor.scale_factor);
+
+    let offset_y = placement
+      .offset_y
+      .to_px_scaled(monitor_height, monitor.scale_factor);
 
-    coordinates
+    let window_position =
+      PhysicalPosition::new(anchor_x + offset_x, anchor_y + offset_y);
+
+    WidgetCoordinates {
+      size: window_size,
+      position: window_position,
+      offset: PhysicalPosition::new(offset_x, offset_y),
+      monitor,
+      anchor: placement.anchor,
+    }
   }
 
   /// Closes a single widget by a given widget ID.
@@ -746,6 +1588,8 @@ impl WidgetFactory {
   }
 
   /// Closes all widgets of the given preset name.
+  ///
+  /// Widgets of presets that are kept alive are hidden instead.
   pub async fn stop_by_preset(
     &self,
     pack_id: &str,
@@ -764,7 +1608,129 @@ impl WidgetFactory {
     });
 
     for widget_state in found_widget_states {
-      self.stop_by_id(&widget_state.id)?;
+      let is_keep_alive = widget_state
+        .config
+        .presets
+        .iter()
+        .any(|preset| preset.name == preset_name && preset.keep_alive);
+
+      match is_keep_alive {
+        true => self.hide_by_id(&widget_state.id).await?,
+        false => self.stop_by_id(&widget_state.id)?,
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Hides a widget's window without destroying its webview.
+  ///
+  /// Docked widgets release their reserved space while hidden. No-op if
+  /// the widget is already hidden.
+  pub async fn hide_by_id(&self, widget_id: &str) -> anyhow::Result<()> {
+    let window = self
+      .app_handle
+      .get_webview_window(widget_id)
+      .context("No Tauri window found for the given widget ID.")?;
+
+    let (state, dock_offset) = {
+      let mut widget_states = self.widget_states.lock().await;
+      let state = widget_states
+        .get_mut(widget_id)
+        .context("No widget found for the given widget ID.")?;
+
+      if state.is_hidden {
+        return Ok(());
+      }
+
+      state.is_hidden = true;
+      state.hidden_at = Some(Instant::now());
+
+      (state.clone(), state.dock_offset.take())
+    };
+
+    info!("Hiding widget {} while keeping it alive.", widget_id);
+
+    window.hide()?;
+
+    // Release reserved space and let docked neighbors move in.
+    if dock_offset.is_some() {
+      #[cfg(not(target_os = "macos"))]
+      let _ = window.as_ref().window().deallocate_app_bar();
+
+      self.restack_docked().await?;
+    }
+
+    self.keep_alive_tx.send(state)?;
+
+    // Enforce the limit on hidden widgets right away, rather than
+    // waiting for the next eviction check.
+    self.evict_hidden().await
+  }
+
+  /// Shows a widget that was hidden via `hide_by_id`.
+  ///
+  /// No-op if the widget is already shown.
+  pub async fn show_by_id(&self, widget_id: &str) -> anyhow::Result<()> {
+    let window = self
+      .app_handle
+      .get_webview_window(widget_id)
+      .context("No Tauri window found for the given widget ID.")?;
+
+    let state = {
+      let mut widget_states = self.widget_states.lock().await;
+      let state = widget_states
+        .get_mut(widget_id)
+        .context("No widget found for the given widget ID.")?;
+
+      if !state.is_hidden {
+        return Ok(());
+      }
+
+      state.is_hidden = false;
+      state.hidden_at = None;
+      state.clone()
+    };
+
+    info!("Showing widget {}.", widget_id);
+
+    window.show()?;
+
+    // Reserve space again for docked widgets.
+    self.restack_docked().await?;
+
+    self.keep_alive_tx.send(state)?;
+
+    Ok(())
+  }
+
+  /// Closes hidden widgets that have been hidden for too long, or that
+  /// exceed the limit on hidden widgets.
+  pub async fn evict_hidden(&self) -> anyhow::Result<()> {
+    let hidden_widgets = {
+      self
+        .widget_states
+        .lock()
+        .await
+        .values()
+        .filter_map(|state| {
+          state
+            .hidden_at
+            .map(|hidden_at| (state.id.clone(), hidden_at))
+        })
+        .collect::<Vec<_>>()
+    };
+
+    let evicted_ids = keep_alive::widgets_to_evict(
+      &hidden_widgets,
+      Instant::now(),
+      keep_alive::MAX_HIDDEN_DURATION,
+      keep_alive::MAX_HIDDEN_WIDGETS,
+    );
+
+    for widget_id in evicted_ids {
+      info!("Evicting hidden widget {}.", widget_id);
+      let _ = self.stop_by_id(&widget_id);
     }
 
     Ok(())
@@ -800,6 +1766,207 @@ impl WidgetFactory {
     self.relaunch_by_ids(&widget_ids).await
   }
 
+  /// Re-places open widgets after the available monitors have changed.
+  ///
+  /// Widgets on unchanged monitors are left as-is, and widgets on
+  /// monitors whose geometry changed are moved and resized in place.
+  /// Windows are only opened or closed for monitors that were added or
+  /// removed.
+  pub async fn reconcile_monitors(&self) -> anyhow::Result<()> {
+    // Group widgets that were opened together (i.e. one window per
+    // monitor in the placement's monitor selection).
+    let widget_groups = {
+      let widget_states = self.widget_states.lock().await;
+      let mut groups: Vec<Vec<WidgetState>> = Vec::new();
+
+      for state in widget_states.values() {
+        let group = groups.iter_mut().find(|group| {
+          group[0].pack_id == state.pack_id
+            && group[0].name == state.name
+            && group[0].open_options == state.open_options
+            && group[0].is_preview == state.is_preview
+        });
+
+        match group {
+          Some(group) => group.push(state.clone()),
+          None => groups.push(vec![state.clone()]),
+        }
+      }
+
+      groups
+    };
+
+    for widget_group in widget_groups {
+      if let Err(err) = self.reconcile_widget_group(&widget_group).await {
+        error!(
+          "Failed to re-place widget {} from {}: {:?}",
+          widget_group[0].name, widget_group[0].pack_id, err
+        );
+      }
+    }
+
+    // Widgets that were moved to a changed monitor are docked again.
+    self.restack_docked().await
+  }
+
+  /// Re-places a group of widgets that were opened together.
+  async fn reconcile_widget_group(
+    &self,
+    widget_states: &[WidgetState],
+  ) -> anyhow::Result<()> {
+    let first_state = &widget_states[0];
+
+    // Popups are transient, so they're closed rather than re-placed.
+    if let WidgetOpenOptions::Popup { .. } = first_state.open_options {
+      for state in widget_states {
+        info!("Closing popup {} on monitor change.", state.id);
+        self.stop_by_id(&state.id)?;
+      }
+
+      return Ok(());
+    }
+
+    let widget_pack = self
+      .widget_pack_manager
+      .widget_pack_by_id(&first_state.pack_id)
+      .await
+      .with_context(|| {
+        format!("No widget pack found for '{}'.", first_state.pack_id)
+      })?;
+
+    let placement = Self::placement(
+      &widget_pack,
+      &first_state.config,
+      &first_state.open_options,
+    )?;
+
+    let current_monitors = widget_states
+      .iter()
+      .map(|state| state.monitor.clone())
+      .collect::<Vec<_>>();
+
+    let new_monitors = self
+      .monitor_state
+      .monitors_by_selection(&placement.monitor_selection)
+      .await;
+
+    let diff = MonitorDiff::new(&current_monitors, &new_monitors);
+
+    for monitor in &diff.removed {
+      for state in widget_states.iter().filter(|s| s.monitor == *monitor) {
+        info!("Closing widget {} on removed monitor.", state.id);
+        self.stop_by_id(&state.id)?;
+      }
+    }
+
+    for (old_monitor, new_monitor) in diff.changed {
+      // Widgets shouldn't be hidden or revealed while being moved.
+      let _visibility_guard = self.visibility_lock.lock().await;
+
+      for state in
+        widget_states.iter().filter(|s| s.monitor == old_monitor)
+      {
+        info!("Moving widget {} on changed monitor.", state.id);
+
+        let window = self
+          .app_handle
+          .get_webview_window(&state.id)
+          .context("No Tauri window found for the given widget ID.")?;
+
+        // Space reserved by the appbar is re-allocated when restacking.
+        #[cfg(not(target_os = "macos"))]
+        if state.dock_offset.is_some() {
+          let _ = window.as_ref().window().deallocate_app_bar();
+        }
+
+        let coordinates =
+          Self::monitor_coordinates(placement, new_monitor.clone());
+
+        let dock_slot = state.dock_slot.as_ref().and_then(|slot| {
+          Self::dock_slot(
+            &state.id,
+            slot.sequence,
+            &placement.dock_to_edge,
+            &coordinates,
+          )
+        });
+
+        // Edge of the monitor that the widget is hidden past, if any.
+        let hidden_edge = self
+          .widget_states
+          .lock()
+          .await
+          .get(&state.id)
+          .and_then(|state| state.visibility.as_ref())
+          .filter(|visibility| visibility.hidden_from.is_some())
+          .map(|visibility| visibility.edge);
+
+        match hidden_edge {
+          // Keep hidden widgets past the edge of the new monitor.
+          Some(edge) => {
+            self.position_window(
+              &window,
+              coordinates.size,
+              widget_visibility::hidden_position(
+                &new_monitor,
+                edge,
+                coordinates.position,
+                coordinates.size,
+              ),
+              new_monitor.scale_factor,
+            )?;
+          }
+          None if dock_slot.is_none() => {
+            self.position_window(
+              &window,
+              coordinates.size,
+              coordinates.position,
+              new_monitor.scale_factor,
+            )?;
+          }
+          None => {}
+        }
+
+        if let Some(state) =
+          self.widget_states.lock().await.get_mut(&state.id)
+        {
+          state.monitor = new_monitor.clone();
+          state.dock_slot = dock_slot;
+          state.dock_offset = None;
+
+          // Reveal hidden widgets at their position on the new monitor
+          // rather than the old one. Docked widgets are restacked once
+          // revealed.
+          if let Some(visibility) = state.visibility.as_mut() {
+            if visibility.hidden_from.is_some() {
+              visibility.hidden_from = Some(coordinates.position);
+            }
+          }
+        }
+      }
+    }
+
+    for monitor in diff.added {
+      info!(
+        "Opening widget {} from {} on added monitor.",
+        first_state.name, first_state.pack_id
+      );
+
+      self
+        .create_window(
+          &widget_pack,
+          &first_state.config,
+          placement,
+          &first_state.open_options,
+          first_state.is_preview,
+          Self::monitor_coordinates(placement, monitor),
+        )
+        .await?;
+    }
+
+    Ok(())
+  }
+
   /// Relaunches widgets with the given widget ID's.
   pub async fn relaunch_by_ids(
     &self,
@@ -867,6 +2034,25 @@ impl WidgetFactory {
     self.relaunch_by_ids(&widget_ids).await
   }
 
+  /// Relaunches all widgets from the given widget pack.
+  pub async fn relaunch_by_pack(
+    &self,
+    pack_id: &str,
+  ) -> anyhow::Result<()> {
+    let widget_ids = {
+      self
+        .widget_states
+        .lock()
+        .await
+        .iter()
+        .filter(|(_, state)| state.pack_id == *pack_id)
+        .map(|(id, _)| id.clone())
+        .collect::<Vec<_>>()
+    };
+
+    self.relaunch_by_ids(&widget_ids).await
+  }
+
   /// Clears the cache for all open widgets.
   pub fn clear_cache(&self) {
     for (_, window) in self.app_handle.webview_windows() {
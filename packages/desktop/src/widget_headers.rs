@@ -0,0 +1,399 @@
+use crate::{
+  marketplace_installer::STARTER_PACK_ID,
+  widget_pack::{WidgetConfig, WidgetPackType},
+};
+
+/// Path of the asset server endpoint that CSP violations are reported to.
+pub const CSP_REPORT_PATH: &str = "/__zebar/csp-report";
+
+/// Default Content-Security-Policy for widgets from marketplace packs.
+///
+/// Scripts, styles, and fonts can only be loaded from the pack itself.
+/// Remote data can be fetched via the caching proxy. Connections to
+/// `ipc:` are needed for Tauri commands and events.
+const MARKETPLACE_CSP: &str = "default-src 'self'; \
+  script-src 'self' 'wasm-unsafe-eval'; \
+  style-src 'self' 'unsafe-inline'; \
+  img-src 'self' data: blob: https:; \
+  font-src 'self' data:; \
+  media-src 'self' blob:; \
+  connect-src 'self' ipc: http://ipc.localhost; \
+  object-src 'none'; \
+  base-uri 'self'";
+
+/// Default Content-Security-Policy for widgets from the bundled starter
+/// pack.
+///
+/// The starter widgets are buildless, so they load Babel and their
+/// dependencies from CDNs and compile inline `text/babel` scripts at
+/// runtime.
+const STARTER_CSP: &str = "default-src 'self'; \
+  script-src 'self' 'unsafe-inline' 'unsafe-eval' https://unpkg.com https://esm.sh; \
+  style-src 'self' 'unsafe-inline'; \
+  img-src 'self' data: blob: https:; \
+  font-src 'self' data:; \
+  media-src 'self' blob:; \
+  connect-src 'self' ipc: http://ipc.localhost https://esm.sh; \
+  object-src 'none'; \
+  base-uri 'self'";
+
+/// Response headers that widget packs can't set via `responseHeaders`.
+const RESERVED_HEADERS: [&str; 2] =
+  ["Content-Security-Policy", "Set-Cookie"];
+
+/// Gets the headers to add to every asset server response for a widget.
+///
+/// Widgets from marketplace packs always get the default policy. A policy
+/// in the pack's config is enforced in addition to it, so packs can only
+/// tighten it. `csp_override` is the user's policy for the pack from the
+/// app settings, and replaces both.
+pub fn response_headers(
+  pack_id: &str,
+  pack_type: &WidgetPackType,
+  widget_config: &WidgetConfig,
+  csp_override: Option<&str>,
+) -> Vec<(String, String)> {
+  let mut headers =
+    vec![("X-Content-Type-Options".to_string(), "nosniff".to_string())];
+
+  let pack_csp = widget_config.content_security_policy.as_deref();
+
+  let policies = match (csp_override, pack_type) {
+    (Some(csp), _) => vec![csp],
+    (None, WidgetPackType::Marketplace) => {
+      let default_csp = match pack_id == STARTER_PACK_ID {
+        true => STARTER_CSP,
+        false => MARKETPLACE_CSP,
+      };
+
+      [Some(default_csp), pack_csp]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+    (None, WidgetPackType::Custom) => pack_csp.into_iter().collect(),
+  };
+
+  // Multiple policies in a single header are comma-separated, and a
+  // resource has to be allowed by each of them.
+  let csp = policies
+    .into_iter()
+    .filter(|csp| !csp.trim().is_empty())
+    .map(with_report_uri)
+    .collect::<Vec<_>>()
+    .join(", ");
+
+  if !csp.is_empty() {
+    headers.push(("Content-Security-Policy".to_string(), csp));
+  }
+
+  for (name, value) in &widget_config.response_headers {
+    if RESERVED_HEADERS
+      .iter()
+      .any(|reserved| reserved.eq_ignore_ascii_case(name))
+    {
+      tracing::warn!(
+        "Ignoring reserved response header {} for {}.",
+        name,
+        pack_id
+      );
+      continue;
+    }
+
+    headers.retain(|(existing, _)| !existing.eq_ignore_ascii_case(name));
+    headers.push((name.clone(), value.clone()));
+  }
+
+  headers
+}
+
+/// Adds a `report-uri` directive to the policy, unless it already has
+/// one.
+fn with_report_uri(csp: &str) -> String {
+  let csp = csp.trim().trim_end_matches(';');
+
+  let has_report_uri = csp.split(';').any(|directive| {
+    directive
+      .split_whitespace()
+      .next()
+      .is_some_and(|name| name.eq_ignore_ascii_case("report-uri"))
+  });
+
+  match has_report_uri {
+    true => csp.to_string(),
+    false => format!("{csp}; report-uri {CSP_REPORT_PATH}"),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::path::Path;
+
+  use super::*;
+  use crate::{
+    common::read_and_parse_json, widget_pack::WidgetPackConfig,
+  };
+
+  fn widget_config(
+    csp: Option<&str>,
+    headers: &[(&str, &str)],
+  ) -> WidgetConfig {
+    serde_json::from_value(serde_json::json!({
+      "name": "bar",
+      "htmlPath": "bar/index.html",
+      "zOrder": "normal",
+      "shownInTaskbar": false,
+      "focused": false,
+      "resizable": false,
+      "transparent": false,
+      "contentSecurityPolicy": csp,
+      "responseHeaders": headers
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect::<std::collections::BTreeMap<_, _>>(),
+      "presets": [],
+    }))
+    .unwrap()
+  }
+
+  fn header<'a>(
+    headers: &'a [(String, String)],
+    name: &str,
+  ) -> Option<&'a str> {
+    headers
+      .iter()
+      .find(|(existing, _)| existing.eq_ignore_ascii_case(name))
+      .map(|(_, value)| value.as_str())
+  }
+
+  #[test]
+  fn test_default_csp() {
+    let config = widget_config(None, &[]);
+
+    let headers = response_headers(
+      "test.pack",
+      &WidgetPackType::Marketplace,
+      &config,
+      None,
+    );
+    let csp = header(&headers, "Content-Security-Policy").unwrap();
+    assert!(csp.starts_with("default-src 'self';"));
+    assert!(csp.ends_with("; report-uri /__zebar/csp-report"));
+
+    let headers =
+      response_headers("test", &WidgetPackType::Custom, &config, None);
+    assert_eq!(header(&headers, "Content-Security-Policy"), None);
+    assert_eq!(
+      header(&headers, "X-Content-Type-Options"),
+      Some("nosniff")
+    );
+  }
+
+  #[test]
+  fn test_custom_csp() {
+    // Marketplace packs can only add a policy on top of the default.
+    let config = widget_config(Some("default-src *;"), &[]);
+    let headers = response_headers(
+      "test.pack",
+      &WidgetPackType::Marketplace,
+      &config,
+      None,
+    );
+
+    assert_eq!(
+      header(&headers, "Content-Security-Policy"),
+      Some(
+        format!(
+          "{}; report-uri /__zebar/csp-report, default-src *; report-uri /__zebar/csp-report",
+          MARKETPLACE_CSP
+        )
+        .as_str()
+      )
+    );
+
+    let config =
+      widget_config(Some("default-src *; report-uri /report"), &[]);
+    let headers =
+      response_headers("test", &WidgetPackType::Custom, &config, None);
+
+    assert_eq!(
+      header(&headers, "Content-Security-Policy"),
+      Some("default-src *; report-uri /report")
+    );
+  }
+
+  #[test]
+  fn test_csp_override() {
+    let config = widget_config(Some("script-src 'self';"), &[]);
+    let headers = response_headers(
+      "test.pack",
+      &WidgetPackType::Marketplace,
+      &config,
+      Some("default-src *"),
+    );
+
+    assert_eq!(
+      header(&headers, "Content-Security-Policy"),
+      Some("default-src *; report-uri /__zebar/csp-report")
+    );
+
+    // An empty override disables the policy.
+    let headers = response_headers(
+      "test.pack",
+      &WidgetPackType::Marketplace,
+      &config,
+      Some(""),
+    );
+
+    assert_eq!(header(&headers, "Content-Security-Policy"), None);
+  }
+
+  #[test]
+  fn test_custom_headers() {
+    let config = widget_config(
+      None,
+      &[
+        ("x-content-type-options", "custom"),
+        ("Permissions-Policy", "camera=()"),
+      ],
+    );
+
+    let headers =
+      response_headers("test", &WidgetPackType::Custom, &config, None);
+
+    assert_eq!(headers.len(), 2);
+    assert_eq!(header(&headers, "X-Content-Type-Options"), Some("custom"));
+    assert_eq!(header(&headers, "Permissions-Policy"), Some("camera=()"));
+  }
+
+  #[test]
+  fn test_reserved_headers() {
+    let config = widget_config(
+      None,
+      &[
+        ("content-security-policy", "default-src *"),
+        ("Set-Cookie", "session=1"),
+      ],
+    );
+
+    let headers = response_headers(
+      "test.pack",
+      &WidgetPackType::Marketplace,
+      &config,
+      None,
+    );
+
+    assert_eq!(headers.len(), 2);
+    assert_eq!(header(&headers, "Set-Cookie"), None);
+    assert!(header(&headers, "Content-Security-Policy")
+      .unwrap()
+      .starts_with(MARKETPLACE_CSP));
+  }
+
+  /// Whether each policy in the CSP header allows loading the source for
+  /// the directive (falling back to `default-src`).
+  ///
+  /// The source is either a keyword (e.g. `'unsafe-inline'`), an absolute
+  /// URL, or a relative URL.
+  fn csp_allows(csp: &str, directive: &str, source: &str) -> bool {
+    csp.split(',').all(|policy| {
+      let directives = policy
+        .split(';')
+        .map(|directive| directive.split_whitespace().collect::<Vec<_>>())
+        .filter(|directive| !directive.is_empty())
+        .collect::<Vec<_>>();
+
+      let allowed_sources = directives
+        .iter()
+        .find(|sources| sources[0] == directive)
+        .or_else(|| {
+          directives
+            .iter()
+            .find(|sources| sources[0] == "default-src")
+        });
+
+      let Some(allowed_sources) = allowed_sources else {
+        return true;
+      };
+
+      allowed_sources[1..].iter().any(|allowed| {
+        if source.starts_with('\'') {
+          *allowed == source
+        } else if source.contains("://") {
+          source.starts_with(allowed.trim_end_matches(':'))
+            && (allowed.ends_with(':')
+              || source[allowed.len()..].starts_with('/'))
+        } else {
+          *allowed == "'self'"
+        }
+      })
+    })
+  }
+
+  #[test]
+  fn test_starter_pack_csp() {
+    let pack_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+      .join("../../resources/starter");
+    let pack_config = read_and_parse_json::<WidgetPackConfig>(
+      &pack_dir.join("zpack.json"),
+    )
+    .unwrap();
+
+    let script_regex =
+      regex::Regex::new(r"(?s)<script([^>]*)>(.*?)</script>").unwrap();
+    let src_regex = regex::Regex::new(r#"src="([^"]+)""#).unwrap();
+    let import_regex =
+      regex::Regex::new(r#"from\s+['"]([^'"]+)['"]"#).unwrap();
+    let stylesheet_regex =
+      regex::Regex::new(r#"<link[^>]*href="([^"]+)""#).unwrap();
+
+    assert!(!pack_config.widgets.is_empty());
+
+    for widget_config in &pack_config.widgets {
+      let html =
+        std::fs::read_to_string(pack_dir.join(&widget_config.html_path))
+          .unwrap();
+
+      let headers = response_headers(
+        STARTER_PACK_ID,
+        &WidgetPackType::Marketplace,
+        widget_config,
+        None,
+      );
+      let csp = header(&headers, "Content-Security-Policy").unwrap();
+      let name = &widget_config.name;
+
+      for script in script_regex.captures_iter(&html) {
+        match src_regex.captures(&script[1]) {
+          Some(src) => {
+            assert!(csp_allows(csp, "script-src", &src[1]), "{name}");
+          }
+          None => {
+            assert!(
+              csp_allows(csp, "script-src", "'unsafe-inline'"),
+              "{name}"
+            );
+          }
+        }
+
+        for import in import_regex.captures_iter(&script[2]) {
+          assert!(csp_allows(csp, "script-src", &import[1]), "{name}");
+        }
+      }
+
+      for stylesheet in stylesheet_regex.captures_iter(&html) {
+        assert!(csp_allows(csp, "style-src", &stylesheet[1]), "{name}");
+      }
+    }
+
+    // Other marketplace packs don't get the starter pack's policy.
+    let headers = response_headers(
+      "test.pack",
+      &WidgetPackType::Marketplace,
+      &pack_config.widgets[0],
+      None,
+    );
+    let csp = header(&headers, "Content-Security-Policy").unwrap();
+    assert!(!csp_allows(csp, "script-src", "https://esm.sh/zebar@3.0"));
+  }
+}
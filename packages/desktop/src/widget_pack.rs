@@ -1,5 +1,5 @@
 use std::{
-  collections::HashMap,
+  collections::{BTreeMap, HashMap},
   fs::{self},
   path::{Path, PathBuf},
   sync::Arc,
@@ -135,6 +135,20 @@ pub struct WidgetConfig {
   #[serde(default)]
   pub privileges: WidgetPrivileges,
 
+  /// Variables available to `.html.tera` entry points.
+  #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+  pub template_variables: serde_json::Map<String, serde_json::Value>,
+
+  /// Content-Security-Policy for responses from the asset server. For
+  /// marketplace packs, it's enforced in addition to the default policy.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub content_security_policy: Option<String>,
+
+  /// Additional headers for responses from the asset server. Can't
+  /// include `Content-Security-Policy` or `Set-Cookie`.
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub response_headers: BTreeMap<String, String>,
+
   /// Where to place the widget. Add alias for `defaultPlacements` for
   /// compatibility with v2.3.0 and earlier.
   #[serde(alias = "defaultPlacements")]
@@ -168,6 +182,46 @@ impl Default for WidgetCaching {
   }
 }
 
+impl WidgetCaching {
+  /// Compiles the regexes of the caching rules. Rules with invalid
+  /// regexes are ignored.
+  pub fn compile_rules(&self) -> CompiledCachingRules {
+    let rules = self
+      .rules
+      .iter()
+      .filter_map(|rule| match regex::Regex::new(&rule.url_regex) {
+        Ok(regex) => Some((regex, rule.duration)),
+        Err(err) => {
+          tracing::warn!(
+            "Ignoring caching rule with invalid regex {}: {}",
+            rule.url_regex,
+            err
+          );
+          None
+        }
+      })
+      .collect();
+
+    CompiledCachingRules(rules)
+  }
+}
+
+/// Caching rules with their URL regexes compiled.
+#[derive(Clone, Debug)]
+pub struct CompiledCachingRules(Vec<(regex::Regex, u32)>);
+
+impl CompiledCachingRules {
+  /// Gets the duration to cache the given URL for, using the first rule
+  /// whose regex matches the URL.
+  ///
+  /// Returns `None` if no rule matches.
+  pub fn duration_for(&self, url: &str) -> Option<std::time::Duration> {
+    self.0.iter().find(|(regex, _)| regex.is_match(url)).map(
+      |(_, duration)| std::time::Duration::from_secs((*duration).into()),
+    )
+  }
+}
+
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WidgetCachingRule {
@@ -184,6 +238,16 @@ pub struct WidgetPreset {
   #[serde(default = "default_preset_name")]
   pub name: String,
 
+  /// Whether to hide the widget instead of closing it when the preset is
+  /// stopped. Starting the preset again shows the existing window.
+  #[serde(default)]
+  pub keep_alive: bool,
+
+  /// Variables available to `.html.tera` entry points. Overrides the
+  /// widget's variables of the same name.
+  #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+  pub template_variables: serde_json::Map<String, serde_json::Value>,
+
   #[serde(flatten)]
   pub placement: WidgetPlacement,
 }
@@ -212,6 +276,40 @@ pub struct WidgetPlacement {
   /// How to reserve space for the widget.
   #[serde(default)]
   pub dock_to_edge: DockConfig,
+
+  /// When the widget is shown.
+  #[serde(default)]
+  pub visibility: WidgetVisibility,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WidgetVisibility {
+  /// Widget is always shown.
+  #[default]
+  Always,
+
+  /// Widget slides off-screen when the cursor leaves it, and is revealed
+  /// when the cursor hits the monitor edge.
+  #[serde(rename_all = "camelCase")]
+  AutoHide {
+    /// Delay (in milliseconds) before hiding once the cursor leaves the
+    /// widget.
+    #[serde(default = "default_hide_delay")]
+    hide_delay: u64,
+
+    /// Delay (in milliseconds) before revealing once the cursor hits
+    /// the monitor edge.
+    #[serde(default = "default_reveal_delay")]
+    reveal_delay: u64,
+  },
+
+  /// Widget is hidden until the hotkey is pressed, and hidden again on
+  /// the next press.
+  Hotkey {
+    /// Key combination to toggle the widget (e.g. `Alt+Shift+B`).
+    hotkey: String,
+  },
 }
 
 #[derive(
@@ -273,6 +371,11 @@ pub struct DockConfig {
   /// negative.
   #[serde(default)]
   pub window_margin: LengthValue,
+
+  /// Order of the widget when stacked with other widgets docked to the
+  /// same edge. Widgets with a lower order are placed closer to the edge.
+  #[serde(default)]
+  pub order: i32,
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -772,8 +875,13 @@ impl WidgetPackManager {
       },
       caching: WidgetCaching::default(),
       privileges: WidgetPrivileges::default(),
+      template_variables: serde_json::Map::new(),
+      content_security_policy: None,
+      response_headers: BTreeMap::new(),
       presets: vec![WidgetPreset {
         name: "default".to_string(),
+        keep_alive: false,
+        template_variables: serde_json::Map::new(),
         placement: WidgetPlacement {
           anchor: AnchorPoint::TopLeft,
           offset_x: "0px".parse()?,
@@ -782,6 +890,7 @@ impl WidgetPackManager {
           height: "40px".parse()?,
           monitor_selection: MonitorSelection::All,
           dock_to_edge: DockConfig::default(),
+          visibility: WidgetVisibility::default(),
         },
       }],
     };
@@ -846,6 +955,18 @@ const fn default_bool<const V: bool>() -> bool {
   V
 }
 
+/// Helper function for setting the default value for a
+/// `WidgetVisibility::AutoHide::hide_delay` field.
+const fn default_hide_delay() -> u64 {
+  500
+}
+
+/// Helper function for setting the default value for a
+/// `WidgetVisibility::AutoHide::reveal_delay` field.
+const fn default_reveal_delay() -> u64 {
+  100
+}
+
 /// Helper function for setting the default value for a
 /// `WidgetPreset::name` field.
 fn default_preset_name() -> String {
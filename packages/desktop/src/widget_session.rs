@@ -0,0 +1,182 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  app_settings::StartupConfig,
+  common::{read_and_parse_json, write_atomic},
+  widget_factory::{WidgetOpenOptions, WidgetState},
+};
+
+/// Snapshot of open widgets, used for restoring them on the next
+/// launch.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Session {
+  pub widgets: Vec<SessionWidget>,
+}
+
+/// Widget that was open when the session was saved.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionWidget {
+  /// ID of the widget pack.
+  pub pack_id: String,
+
+  /// Name of the widget within the widget pack.
+  pub widget_name: String,
+
+  /// How the widget was opened.
+  pub open_options: WidgetOpenOptions,
+}
+
+impl Session {
+  /// Creates a snapshot from the states of open widgets.
+  ///
+  /// Previews, popups, and widgets that are hidden while being kept
+  /// alive aren't restored. Widgets opened together (i.e. one window per
+  /// monitor) are only added once.
+  pub fn from_states<'a>(
+    widget_states: impl IntoIterator<Item = &'a WidgetState>,
+  ) -> Self {
+    let mut widget_states = widget_states
+      .into_iter()
+      .filter(|state| {
+        !state.is_preview
+          && !state.is_hidden
+          && !matches!(state.open_options, WidgetOpenOptions::Popup { .. })
+      })
+      .collect::<Vec<_>>();
+
+    // Sort by widget ID, such that widgets are restored in the order
+    // they were opened.
+    widget_states.sort_by_key(|state| {
+      state
+        .id
+        .trim_start_matches("widget-")
+        .parse::<u32>()
+        .unwrap_or_default()
+    });
+
+    let mut session = Self::default();
+
+    for state in widget_states {
+      session.add(SessionWidget {
+        pack_id: state.pack_id.clone(),
+        widget_name: state.name.clone(),
+        open_options: state.open_options.clone(),
+      });
+    }
+
+    session
+  }
+
+  /// Adds a widget to the session, unless it's already been added.
+  pub fn add(&mut self, widget: SessionWidget) {
+    if !self.widgets.contains(&widget) {
+      self.widgets.push(widget);
+    }
+  }
+
+  /// Removes widgets that were opened from any of the given startup
+  /// configs (e.g. ones whose conditions are no longer met).
+  pub fn without_startup_configs(
+    mut self,
+    startup_configs: &[StartupConfig],
+  ) -> Self {
+    self.widgets.retain(|widget| {
+      let WidgetOpenOptions::Preset(preset_name) = &widget.open_options
+      else {
+        return true;
+      };
+
+      !startup_configs.iter().any(|config| {
+        config.is_preset(&widget.pack_id, &widget.widget_name, preset_name)
+      })
+    });
+
+    self
+  }
+
+  /// Reads the session file, or returns an empty session if the file
+  /// doesn't exist.
+  pub fn read(path: &Path) -> anyhow::Result<Self> {
+    match path.exists() {
+      true => read_and_parse_json(path),
+      false => Ok(Self::default()),
+    }
+  }
+
+  /// Writes the session file.
+  pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+    write_atomic(path, serde_json::to_string_pretty(self)?.as_bytes())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::common::test_util::temp_dir;
+
+  fn preset_widget(widget_name: &str, preset: &str) -> SessionWidget {
+    SessionWidget {
+      pack_id: "glzr-io.starter".to_string(),
+      widget_name: widget_name.to_string(),
+      open_options: WidgetOpenOptions::Preset(preset.to_string()),
+    }
+  }
+
+  #[test]
+  fn test_add_deduplicates() {
+    let mut session = Session::default();
+
+    session.add(preset_widget("vanilla", "default"));
+    session.add(preset_widget("vanilla", "default"));
+    session.add(preset_widget("vanilla", "bottom"));
+
+    assert_eq!(
+      session.widgets,
+      vec![
+        preset_widget("vanilla", "default"),
+        preset_widget("vanilla", "bottom")
+      ]
+    );
+  }
+
+  #[test]
+  fn test_without_startup_configs() {
+    let mut session = Session::default();
+    session.add(preset_widget("vanilla", "default"));
+    session.add(preset_widget("vanilla", "bottom"));
+
+    let unmet_config = StartupConfig {
+      pack: "glzr-io.starter".to_string(),
+      widget: "vanilla".to_string(),
+      preset: "bottom".to_string(),
+      condition: None,
+    };
+
+    assert_eq!(
+      session.without_startup_configs(&[unmet_config]).widgets,
+      vec![preset_widget("vanilla", "default")]
+    );
+  }
+
+  #[test]
+  fn test_read_write() {
+    let dir = temp_dir("session");
+    let path = dir.join(".session.json");
+    std::fs::create_dir_all(&dir).unwrap();
+
+    // Missing session file is treated as an empty session.
+    assert_eq!(Session::read(&path).unwrap(), Session::default());
+
+    let mut session = Session::default();
+    session.add(preset_widget("with-glazewm", "default"));
+    session.write(&path).unwrap();
+
+    assert_eq!(Session::read(&path).unwrap(), session);
+
+    std::fs::remove_dir_all(dir).unwrap();
+  }
+}
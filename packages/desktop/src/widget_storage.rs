@@ -0,0 +1,342 @@
+use std::{
+  collections::HashMap,
+  fmt, fs,
+  path::{Path, PathBuf},
+  sync::Arc,
+};
+
+use anyhow::{bail, Context};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tauri::ipc::Channel;
+use tokio::sync::Mutex;
+
+use crate::{
+  common::{read_and_parse_json, write_atomic},
+  widget_factory::{WidgetFactory, WidgetState},
+};
+
+/// Maximum size of a widget's storage file (in bytes).
+const STORAGE_QUOTA: usize = 1024 * 1024;
+
+/// Change to a stored value.
+///
+/// Sent to other instances of the widget via their watch channel.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageChange {
+  /// Key that was changed.
+  pub key: String,
+
+  /// New value of the key. `None` if the key was deleted.
+  pub value: Option<Value>,
+
+  /// ID of the widget instance that made the change.
+  pub sender_id: String,
+}
+
+/// IPC channel that a widget instance receives changes on.
+///
+/// Changes aren't emitted as events, since any widget can listen to
+/// events regardless of their target.
+#[derive(Clone)]
+struct Watcher(Channel<StorageChange>);
+
+impl fmt::Debug for Watcher {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_tuple("Watcher").field(&self.0.id()).finish()
+  }
+}
+
+/// Pack ID and widget name that a store belongs to.
+type StoreId = (String, String);
+
+/// Key-value store of a single widget, persisted as a JSON file.
+#[derive(Debug)]
+struct Store {
+  path: PathBuf,
+  values: Map<String, Value>,
+}
+
+impl Store {
+  /// Reads the store from the given path, or creates an empty store if
+  /// the file doesn't exist yet.
+  fn load(path: PathBuf) -> anyhow::Result<Self> {
+    let values = match path.exists() {
+      true => read_and_parse_json(&path)?,
+      false => Map::new(),
+    };
+
+    Ok(Self { path, values })
+  }
+
+  /// Sets the value of a key, or deletes the key if `value` is `None`.
+  /// Changes are written to disk right away.
+  ///
+  /// The store is left unchanged if it would exceed the size quota.
+  fn update(
+    &mut self,
+    key: &str,
+    value: Option<Value>,
+    quota: usize,
+  ) -> anyhow::Result<()> {
+    let mut values = self.values.clone();
+
+    match value {
+      Some(value) => values.insert(key.to_string(), value),
+      None => values.remove(key),
+    };
+
+    let contents = serde_json::to_vec(&values)?;
+
+    if contents.len() > quota {
+      bail!(
+        "Storage quota of {} bytes exceeded ({} bytes).",
+        quota,
+        contents.len()
+      );
+    }
+
+    if let Some(dir) = self.path.parent() {
+      fs::create_dir_all(dir)?;
+    }
+
+    write_atomic(&self.path, &contents)?;
+    self.values = values;
+
+    Ok(())
+  }
+}
+
+/// Persistent key-value storage for widgets.
+///
+/// Each widget has its own store that is shared between all of its
+/// instances (e.g. the same widget open on multiple monitors). Unlike
+/// webview storage, it isn't wiped when clearing the cache.
+#[derive(Debug)]
+pub struct WidgetStorage {
+  /// Directory where storage files are stored.
+  storage_dir: PathBuf,
+
+  /// Loaded stores by pack ID and widget name.
+  stores: Mutex<HashMap<StoreId, Store>>,
+
+  /// Channels of widget instances that are watching their store for
+  /// changes, by widget ID.
+  watchers: Mutex<HashMap<String, Watcher>>,
+
+  widget_factory: Arc<WidgetFactory>,
+}
+
+impl WidgetStorage {
+  /// Creates a new `WidgetStorage` instance.
+  pub fn new(
+    storage_dir: PathBuf,
+    widget_factory: Arc<WidgetFactory>,
+  ) -> Self {
+    Self {
+      storage_dir,
+      stores: Mutex::new(HashMap::new()),
+      watchers: Mutex::new(HashMap::new()),
+      widget_factory,
+    }
+  }
+
+  /// Gets the value of a key in the widget's store.
+  pub async fn get(
+    &self,
+    widget_id: &str,
+    key: &str,
+  ) -> anyhow::Result<Option<Value>> {
+    let state = self.widget_state(widget_id).await?;
+    let store_id = (state.pack_id, state.name);
+
+    let mut stores = self.stores.lock().await;
+    let store = self.load_store(&mut stores, &store_id)?;
+
+    Ok(store.values.get(key).cloned())
+  }
+
+  /// Sets the value of a key in the widget's store, or deletes the key if
+  /// `value` is `None`.
+  ///
+  /// The change is sent to other instances of the widget that are
+  /// watching the store.
+  pub async fn update(
+    &self,
+    widget_id: &str,
+    key: &str,
+    value: Option<Value>,
+  ) -> anyhow::Result<()> {
+    let state = self.widget_state(widget_id).await?;
+    let store_id = (state.pack_id, state.name);
+
+    {
+      let mut stores = self.stores.lock().await;
+      let store = self.load_store(&mut stores, &store_id)?;
+      store.update(key, value.clone(), STORAGE_QUOTA)?;
+    }
+
+    let change = StorageChange {
+      key: key.to_string(),
+      value,
+      sender_id: widget_id.to_string(),
+    };
+
+    let watchers = self.watchers.lock().await.clone();
+
+    for state in self.widget_factory.states().await.values() {
+      if state.id == widget_id
+        || state.pack_id != store_id.0
+        || state.name != store_id.1
+      {
+        continue;
+      }
+
+      if let Some(Watcher(channel)) = watchers.get(&state.id) {
+        channel.send(change.clone())?;
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Sends changes made by other instances of the widget to the given
+  /// widget over the channel. Replaces any previous channel of the
+  /// widget.
+  pub async fn watch(
+    &self,
+    widget_id: &str,
+    channel: Channel<StorageChange>,
+  ) -> anyhow::Result<()> {
+    // Ensure the widget exists.
+    self.widget_state(widget_id).await?;
+
+    self
+      .watchers
+      .lock()
+      .await
+      .insert(widget_id.to_string(), Watcher(channel));
+
+    Ok(())
+  }
+
+  /// Stops sending changes to a closed widget.
+  pub async fn remove_widget(&self, widget_id: &str) {
+    self.watchers.lock().await.remove(widget_id);
+  }
+
+  /// Gets a loaded store, or reads it from disk.
+  fn load_store<'a>(
+    &self,
+    stores: &'a mut HashMap<StoreId, Store>,
+    store_id: &StoreId,
+  ) -> anyhow::Result<&'a mut Store> {
+    if !stores.contains_key(store_id) {
+      let path = store_path(&self.storage_dir, &store_id.0, &store_id.1)?;
+      stores.insert(store_id.clone(), Store::load(path)?);
+    }
+
+    Ok(stores.get_mut(store_id).unwrap())
+  }
+
+  async fn widget_state(
+    &self,
+    widget_id: &str,
+  ) -> anyhow::Result<WidgetState> {
+    self
+      .widget_factory
+      .state_by_id(widget_id)
+      .await
+      .with_context(|| format!("Widget with ID '{widget_id}' not found."))
+  }
+}
+
+/// Gets the path to the storage file of a widget.
+///
+/// Files are stored at `<storage_dir>/<pack_id>/<widget_name>.json`.
+fn store_path(
+  storage_dir: &Path,
+  pack_id: &str,
+  widget_name: &str,
+) -> anyhow::Result<PathBuf> {
+  for segment in [pack_id, widget_name] {
+    if segment.is_empty()
+      || segment.starts_with('.')
+      || segment.contains(['/', '\\'])
+    {
+      bail!("Invalid storage path segment '{}'.", segment);
+    }
+  }
+
+  Ok(
+    storage_dir
+      .join(pack_id)
+      .join(format!("{widget_name}.json")),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use super::*;
+  use crate::common::test_util::temp_dir;
+
+  #[test]
+  fn test_store_persists() {
+    let dir = temp_dir("storage-persist");
+    let path = store_path(&dir, "glzr-io.starter", "vanilla").unwrap();
+
+    let mut store = Store::load(path.clone()).unwrap();
+    store
+      .update("theme", Some(json!({ "accent": "blue" })), STORAGE_QUOTA)
+      .unwrap();
+    store.update("tab", Some(json!(2)), STORAGE_QUOTA).unwrap();
+    store.update("tab", None, STORAGE_QUOTA).unwrap();
+
+    // Reading the file again gives the same values.
+    let store = Store::load(path.clone()).unwrap();
+    assert_eq!(
+      store.values.get("theme"),
+      Some(&json!({ "accent": "blue" }))
+    );
+    assert_eq!(store.values.get("tab"), None);
+
+    // No temporary file is left behind.
+    assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+
+    fs::remove_dir_all(dir).unwrap();
+  }
+
+  #[test]
+  fn test_store_quota() {
+    let dir = temp_dir("storage-quota");
+    let path = store_path(&dir, "glzr-io.starter", "vanilla").unwrap();
+
+    let mut store = Store::load(path.clone()).unwrap();
+    store.update("small", Some(json!("a")), 32).unwrap();
+
+    // Exceeding the quota leaves the store unchanged.
+    let res = store.update("large", Some(json!("a".repeat(32))), 32);
+
+    assert!(res.is_err());
+    assert_eq!(store.values.get("large"), None);
+    assert_eq!(Store::load(path).unwrap().values.len(), 1);
+
+    fs::remove_dir_all(dir).unwrap();
+  }
+
+  #[test]
+  fn test_store_path() {
+    let dir = PathBuf::from("storage");
+
+    assert_eq!(
+      store_path(&dir, "glzr-io.starter", "vanilla").unwrap(),
+      dir.join("glzr-io.starter").join("vanilla.json")
+    );
+
+    assert!(store_path(&dir, "..", "vanilla").is_err());
+    assert!(store_path(&dir, "glzr-io.starter", "a/b").is_err());
+  }
+}
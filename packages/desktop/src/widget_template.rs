@@ -0,0 +1,129 @@
+use std::path::Path;
+
+use anyhow::Context;
+use serde_json::json;
+
+use crate::{
+  common::has_extension,
+  monitor_state::Monitor,
+  widget_factory::WidgetOpenOptions,
+  widget_pack::{WidgetConfig, WidgetPack},
+};
+
+/// File extension of entry points that are rendered with Tera before
+/// being served.
+const TEMPLATE_EXTENSION: &str = ".html.tera";
+
+/// Whether the file is an HTML entry point that should be rendered.
+pub fn is_template(path: &Path) -> bool {
+  has_extension(path, TEMPLATE_EXTENSION)
+}
+
+/// Creates the context that a widget's entry point is rendered with.
+///
+/// Available variables:
+///  * `widget`: ID, name, and preset name (if opened via a preset).
+///  * `pack`: ID, name, and version of the widget pack.
+///  * `monitor`: Monitor that the widget is placed on.
+///  * `variables`: User-defined `templateVariables` of the widget,
+///    overridden by those of the preset.
+pub fn template_context(
+  widget_id: &str,
+  widget_pack: &WidgetPack,
+  widget_config: &WidgetConfig,
+  open_options: &WidgetOpenOptions,
+  monitor: &Monitor,
+) -> anyhow::Result<tera::Context> {
+  let preset = match open_options {
+    WidgetOpenOptions::Preset(name) => widget_config
+      .presets
+      .iter()
+      .find(|preset| preset.name == *name),
+    _ => None,
+  };
+
+  let mut variables = widget_config.template_variables.clone();
+
+  if let Some(preset) = preset {
+    variables.extend(preset.template_variables.clone());
+  }
+
+  let context = json!({
+    "widget": {
+      "id": widget_id,
+      "name": widget_config.name,
+      "preset": preset.map(|preset| &preset.name),
+    },
+    "pack": {
+      "id": widget_pack.id,
+      "name": widget_pack.config.name,
+      "version": widget_pack.config.version,
+    },
+    "monitor": monitor,
+    "variables": variables,
+  });
+
+  Ok(tera::Context::from_value(context)?)
+}
+
+/// Renders a template entry point with the given context.
+///
+/// HTML in variables is escaped.
+pub fn render(
+  path: &Path,
+  context: &tera::Context,
+) -> anyhow::Result<String> {
+  let template = std::fs::read_to_string(path).with_context(|| {
+    format!("Failed to read template: {}", path.display())
+  })?;
+
+  tera::Tera::one_off(&template, context, true).with_context(|| {
+    format!("Failed to render template: {}", path.display())
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::common::test_util::temp_dir;
+
+  fn context(variables: serde_json::Value) -> tera::Context {
+    tera::Context::from_value(json!({
+      "widget": { "id": "widget-1", "name": "bar", "preset": "top" },
+      "monitor": { "name": "DISPLAY1", "width": 1920, "scaleFactor": 1.5 },
+      "variables": variables,
+    }))
+    .unwrap()
+  }
+
+  #[test]
+  fn test_is_template() {
+    assert!(is_template(Path::new("bar/index.html.tera")));
+    assert!(!is_template(Path::new("bar/index.html")));
+    assert!(!is_template(Path::new("bar/index.tera")));
+  }
+
+  #[test]
+  fn test_render() {
+    let dir = temp_dir("template");
+    let path = dir.join("index.html.tera");
+    std::fs::create_dir_all(&dir).unwrap();
+
+    std::fs::write(
+      &path,
+      "<body class=\"{{ widget.preset }}\">\
+       {% if monitor.width > 1280 %}wide{% endif %} {{ variables.label }}\
+       </body>",
+    )
+    .unwrap();
+
+    let html = render(&path, &context(json!({ "label": "<b>Hi</b>" })));
+
+    assert_eq!(
+      html.unwrap(),
+      "<body class=\"top\">wide &lt;b&gt;Hi&lt;&#x2F;b&gt;</body>"
+    );
+
+    std::fs::remove_dir_all(dir).unwrap();
+  }
+}
@@ -0,0 +1,382 @@
+use std::{
+  collections::HashMap,
+  sync::Arc,
+  time::{Duration, Instant},
+};
+
+use tauri::{AppHandle, Manager, PhysicalPosition, PhysicalSize};
+use tokio::{
+  sync::{broadcast, Mutex},
+  task,
+};
+
+use crate::{
+  monitor_state::Monitor,
+  widget_factory::WidgetState,
+  widget_pack::{DockEdge, WidgetVisibility},
+};
+
+/// Interval for checking the cursor position of auto-hide widgets.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Distance from the monitor edge (in physical pixels) at which the
+/// cursor reveals a hidden widget.
+const REVEAL_THRESHOLD: i32 = 2;
+
+/// Number of steps that the slide animation is split into.
+pub const SLIDE_STEPS: i32 = 8;
+
+/// Delay between each step of the slide animation.
+pub const SLIDE_STEP_DELAY: Duration = Duration::from_millis(15);
+
+/// Visibility mode of a widget, and whether it's currently hidden by it.
+#[derive(Clone, Debug)]
+pub struct VisibilityState {
+  pub visibility: WidgetVisibility,
+
+  /// Edge that the widget slides past when hidden.
+  pub edge: DockEdge,
+
+  /// Position that the widget was shown at before being hidden. `None`
+  /// if the widget is shown.
+  pub hidden_from: Option<PhysicalPosition<i32>>,
+}
+
+/// Request to hide or reveal widgets.
+#[derive(Clone, Debug)]
+pub enum VisibilityChange {
+  /// Hide (`true`) or reveal (`false`) an auto-hide widget.
+  AutoHide { widget_id: String, hidden: bool },
+
+  /// Hotkey of widgets with hotkey visibility was pressed.
+  Hotkey(String),
+}
+
+/// Tracks how long the cursor has been away from an auto-hide widget (or
+/// at the monitor edge while hidden).
+#[derive(Debug)]
+pub struct AutoHideTimer {
+  hide_delay: Duration,
+  reveal_delay: Duration,
+
+  /// When the cursor started requesting a change in visibility.
+  pending_since: Option<Instant>,
+}
+
+impl AutoHideTimer {
+  pub fn new(hide_delay: Duration, reveal_delay: Duration) -> Self {
+    Self {
+      hide_delay,
+      reveal_delay,
+      pending_since: None,
+    }
+  }
+
+  /// Updates the timer with the latest cursor state.
+  ///
+  /// Returns whether the widget should now be hidden, or `None` if its
+  /// visibility should stay as-is.
+  pub fn update(
+    &mut self,
+    is_hidden: bool,
+    is_hovered: bool,
+    is_at_edge: bool,
+    now: Instant,
+  ) -> Option<bool> {
+    let wants_change = match is_hidden {
+      true => is_at_edge,
+      false => !is_hovered,
+    };
+
+    if !wants_change {
+      self.pending_since = None;
+      return None;
+    }
+
+    let pending_since = *self.pending_since.get_or_insert(now);
+
+    let delay = match is_hidden {
+      true => self.reveal_delay,
+      false => self.hide_delay,
+    };
+
+    if now.duration_since(pending_since) < delay {
+      return None;
+    }
+
+    self.pending_since = None;
+    Some(!is_hidden)
+  }
+}
+
+/// Whether the cursor is within the given rect.
+pub fn is_within(
+  cursor: PhysicalPosition<i32>,
+  position: PhysicalPosition<i32>,
+  size: PhysicalSize<i32>,
+) -> bool {
+  cursor.x >= position.x
+    && cursor.x < position.x + size.width
+    && cursor.y >= position.y
+    && cursor.y < position.y + size.height
+}
+
+/// Whether the cursor is at the monitor edge that a widget is hidden
+/// past, and within the span of the widget along that edge.
+pub fn is_at_edge(
+  cursor: PhysicalPosition<i32>,
+  monitor: &Monitor,
+  edge: DockEdge,
+  position: PhysicalPosition<i32>,
+  size: PhysicalSize<i32>,
+) -> bool {
+  let monitor_position = PhysicalPosition::new(monitor.x, monitor.y);
+  let monitor_size =
+    PhysicalSize::new(monitor.width as i32, monitor.height as i32);
+
+  if !is_within(cursor, monitor_position, monitor_size) {
+    return false;
+  }
+
+  match edge {
+    DockEdge::Top => {
+      cursor.y < monitor.y + REVEAL_THRESHOLD
+        && (position.x..position.x + size.width).contains(&cursor.x)
+    }
+    DockEdge::Bottom => {
+      cursor.y >= monitor.y + monitor_size.height - REVEAL_THRESHOLD
+        && (position.x..position.x + size.width).contains(&cursor.x)
+    }
+    DockEdge::Left => {
+      cursor.x < monitor.x + REVEAL_THRESHOLD
+        && (position.y..position.y + size.height).contains(&cursor.y)
+    }
+    DockEdge::Right => {
+      cursor.x >= monitor.x + monitor_size.width - REVEAL_THRESHOLD
+        && (position.y..position.y + size.height).contains(&cursor.y)
+    }
+  }
+}
+
+/// Gets the position of a widget once slid past the given monitor edge.
+pub fn hidden_position(
+  monitor: &Monitor,
+  edge: DockEdge,
+  position: PhysicalPosition<i32>,
+  size: PhysicalSize<i32>,
+) -> PhysicalPosition<i32> {
+  match edge {
+    DockEdge::Top => {
+      PhysicalPosition::new(position.x, monitor.y - size.height)
+    }
+    DockEdge::Bottom => {
+      PhysicalPosition::new(position.x, monitor.y + monitor.height as i32)
+    }
+    DockEdge::Left => {
+      PhysicalPosition::new(monitor.x - size.width, position.y)
+    }
+    DockEdge::Right => {
+      PhysicalPosition::new(monitor.x + monitor.width as i32, position.y)
+    }
+  }
+}
+
+/// Gets the intermediate positions of the slide animation, ending with
+/// the target position.
+pub fn slide_positions(
+  from: PhysicalPosition<i32>,
+  to: PhysicalPosition<i32>,
+) -> Vec<PhysicalPosition<i32>> {
+  (1..=SLIDE_STEPS)
+    .map(|step| {
+      PhysicalPosition::new(
+        from.x + (to.x - from.x) * step / SLIDE_STEPS,
+        from.y + (to.y - from.y) * step / SLIDE_STEPS,
+      )
+    })
+    .collect()
+}
+
+/// Watches the cursor position and requests an auto-hide widget to be
+/// hidden or revealed.
+///
+/// Stops once the widget is closed.
+pub fn watch_auto_hide(
+  app_handle: AppHandle,
+  widget_states: Arc<Mutex<HashMap<String, WidgetState>>>,
+  widget_id: String,
+  hide_delay: Duration,
+  reveal_delay: Duration,
+  visibility_tx: broadcast::Sender<VisibilityChange>,
+) {
+  task::spawn(async move {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    let mut timer = AutoHideTimer::new(hide_delay, reveal_delay);
+
+    loop {
+      interval.tick().await;
+
+      let Some(window) = app_handle.get_webview_window(&widget_id) else {
+        break;
+      };
+
+      let Some((monitor, visibility)) = ({
+        let widget_states = widget_states.lock().await;
+
+        widget_states.get(&widget_id).and_then(|state| {
+          state
+            .visibility
+            .clone()
+            .map(|visibility| (state.monitor.clone(), visibility))
+        })
+      }) else {
+        break;
+      };
+
+      let (Ok(cursor), Ok(position), Ok(size)) = (
+        app_handle.cursor_position(),
+        window.outer_position(),
+        window.outer_size(),
+      ) else {
+        continue;
+      };
+
+      let cursor = PhysicalPosition::new(cursor.x as i32, cursor.y as i32);
+      let size = PhysicalSize::new(size.width as i32, size.height as i32);
+
+      // Use the shown position while hidden, so that the widget is
+      // revealed along its span of the edge.
+      let position = visibility.hidden_from.unwrap_or(position);
+
+      let is_hidden = visibility.hidden_from.is_some();
+      let is_hovered = is_within(cursor, position, size);
+      let is_at_edge =
+        is_at_edge(cursor, &monitor, visibility.edge, position, size);
+
+      if let Some(hidden) =
+        timer.update(is_hidden, is_hovered, is_at_edge, Instant::now())
+      {
+        let _ = visibility_tx.send(VisibilityChange::AutoHide {
+          widget_id: widget_id.clone(),
+          hidden,
+        });
+      }
+    }
+  });
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn monitor() -> Monitor {
+    Monitor {
+      name: Some("DISPLAY1".to_string()),
+      is_primary: true,
+      x: 0,
+      y: 0,
+      width: 1920,
+      height: 1080,
+      scale_factor: 1.0,
+    }
+  }
+
+  #[test]
+  fn test_auto_hide_timer() {
+    let mut timer = AutoHideTimer::new(
+      Duration::from_millis(500),
+      Duration::from_millis(100),
+    );
+
+    let start = Instant::now();
+    let after = |ms| start + Duration::from_millis(ms);
+
+    // Hides once the cursor has been away for the hide delay.
+    assert_eq!(timer.update(false, false, false, start), None);
+    assert_eq!(timer.update(false, false, false, after(400)), None);
+    assert_eq!(timer.update(false, false, false, after(500)), Some(true));
+
+    // Cursor has to stay at the edge for the reveal delay.
+    assert_eq!(timer.update(true, false, true, after(600)), None);
+    assert_eq!(timer.update(true, false, false, after(650)), None);
+    assert_eq!(timer.update(true, false, true, after(700)), None);
+    assert_eq!(timer.update(true, false, true, after(800)), Some(false));
+
+    // Hovering the widget resets the hide delay.
+    assert_eq!(timer.update(false, false, false, after(900)), None);
+    assert_eq!(timer.update(false, true, false, after(1300)), None);
+    assert_eq!(timer.update(false, false, false, after(1400)), None);
+    assert_eq!(timer.update(false, false, false, after(1900)), Some(true));
+  }
+
+  #[test]
+  fn test_is_at_edge() {
+    let monitor = monitor();
+    let position = PhysicalPosition::new(0, 0);
+    let size = PhysicalSize::new(1920, 40);
+
+    let at_edge = |x, y, edge| {
+      is_at_edge(
+        PhysicalPosition::new(x, y),
+        &monitor,
+        edge,
+        position,
+        size,
+      )
+    };
+
+    assert!(at_edge(500, 0, DockEdge::Top));
+    assert!(at_edge(500, 1, DockEdge::Top));
+    assert!(!at_edge(500, 2, DockEdge::Top));
+    assert!(!at_edge(500, -1, DockEdge::Top));
+    assert!(at_edge(500, 1079, DockEdge::Bottom));
+
+    // Must be within the widget's span along the edge.
+    let size = PhysicalSize::new(400, 40);
+    assert!(!is_at_edge(
+      PhysicalPosition::new(500, 0),
+      &monitor,
+      DockEdge::Top,
+      position,
+      size
+    ));
+  }
+
+  #[test]
+  fn test_hidden_position() {
+    let monitor = monitor();
+    let size = PhysicalSize::new(1920, 40);
+
+    assert_eq!(
+      hidden_position(
+        &monitor,
+        DockEdge::Top,
+        PhysicalPosition::new(0, 0),
+        size
+      ),
+      PhysicalPosition::new(0, -40)
+    );
+
+    assert_eq!(
+      hidden_position(
+        &monitor,
+        DockEdge::Bottom,
+        PhysicalPosition::new(0, 1040),
+        size
+      ),
+      PhysicalPosition::new(0, 1080)
+    );
+  }
+
+  #[test]
+  fn test_slide_positions() {
+    let positions = slide_positions(
+      PhysicalPosition::new(0, 0),
+      PhysicalPosition::new(0, -40),
+    );
+
+    assert_eq!(positions.len(), SLIDE_STEPS as usize);
+    assert_eq!(positions[0], PhysicalPosition::new(0, -5));
+    assert_eq!(positions.last(), Some(&PhysicalPosition::new(0, -40)));
+  }
+}
@@ -483,6 +483,20 @@ export function WidgetConfigForm(props: WidgetConfigFormProps) {
                 </Field>
               </div>
 
+              <Field of={configForm} path={`presets.${index}.keepAlive`}>
+                {(inputProps, field) => (
+                  <SwitchField
+                    id={`keep-alive-${index}`}
+                    class="flex flex-wrap items-center gap-x-4 [&>:last-child]:w-full"
+                    label="Keep alive"
+                    description="Whether to hide the widget instead of closing it when the preset is stopped."
+                    error={field.error()}
+                    disabled={props.disabled}
+                    {...inputProps()}
+                  />
+                )}
+              </Field>
+
               <div class="flex justify-between">
                 <Field
                   of={configForm}
@@ -492,7 +506,7 @@ export function WidgetConfigForm(props: WidgetConfigFormProps) {
                     <SwitchField
                       id={`dock-enabled-${index}`}
                       class="flex flex-wrap items-center gap-x-4 [&>:last-child]:w-full"
-                      label="Dock to edge (Windows-only)"
+                      label="Dock to edge (Windows and Linux)"
                       description="Whether to dock the widget to the monitor edge and reserve screen space for it."
                       error={field.error()}
                       disabled={props.disabled}